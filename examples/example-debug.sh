@@ -30,16 +30,19 @@ ${SEVENTH_ESTATE_BINARY} step4 --config example.yaml.secure --serial-file serial
 recordstep step4
 
 # Record some votes at random
-#  Pick the first three "For" votes (lines 2-4)
+#  ballots.csv has one row per choice: serial,question,votecode,choice
+#  Pick the first option from ballots 1-5
 #  Pick both options in ballots 4 and 5
-#  Pick "Against" votes from ballots 6-10.
-(echo votecode; cut -d, -f 2 ballots.csv| sed -n '2,6p'; cut -d, -f 4 ballots.csv| sed -n '5,11p'; ) > votes.csv
+#  Pick the second option from ballots 4-10.
+(echo votecode;
+ awk -F, 'NR > 1 && (NR - 2) % 2 == 0 { print $3 }' ballots.csv | sed -n '1,5p';
+ awk -F, 'NR > 1 && (NR - 2) % 2 == 1 { print $3 }' ballots.csv | sed -n '4,10p'; ) > votes.csv
 
 cat <<EOF
 
 Time to simulate actual voting.  The votecodes are random, so this can't be predetermined.
 Choose some actual votecodes for this election from ballots.csv
-with some from column 2 and a different number from column 4,
+with some for the first option and a different number for the second,
 so you don't end up with a tie"
 Make sure you avoid the ones you've spoiled, and have enough to deal with random decoys.
 Some have been prechosen for you, but you can edit votes.csv now if you like.
@@ -63,6 +66,6 @@ ${SEVENTH_ESTATE_BINARY} step8 --config example.yaml.secure
 recordstep step8
 
 echo "Here are the valid votes. We tally the vote ourselves."
-grep ,Voted, ExamplePoll/final_plane_04.csv | sort -t , -k +3
+grep ,Voted, ExamplePoll/final_q01_plane_04.csv | sort -t , -k +3
 
 # Note: you'll need to do one final `git add example.out` and commit it for the output written after that final recordstep
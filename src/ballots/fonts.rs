@@ -0,0 +1,238 @@
+//! # Ballot Fonts
+//!
+//! A template font is a PDF built-in font or a TrueType/OpenType font
+//! file. The built-in fonts only cover Latin-1 text. A font file covers
+//! whatever its glyphs cover (accents, Cyrillic, CJK, ...) and is embedded
+//! in each PDF, subset to the glyphs the poll prints.
+//!
+//! Glyphs are placed one after the other, left to right, without shaping.
+//! Texts in scripts that need shaping or are written right to left, such
+//! as Arabic, Hebrew or Devanagari, are rejected when the poll and its
+//! template are loaded rather than printed wrongly.
+//!
+//! Text is measured with the advance widths of the font file, or as
+//! Courier (600/1000 em per character) for the built-in fonts, which
+//! overestimates most other built-in fonts, and wrapped to fit.
+
+use std::collections::{BTreeMap, BTreeSet};
+use sha2::{Sha256, Digest};
+use printpdf::{BuiltinFont, PdfDocumentReference, IndirectFontRef};
+use crate::Result;
+use super::template::{BallotTemplate, TemplateFont, builtin_font};
+use super::subset::subset_font;
+
+const POINTS_PER_MM: f64 = 72.0 / 25.4;
+/// Advance width, in em, of a Courier character.
+const BUILTIN_ADVANCE: f64 = 0.6;
+/// Unicode blocks of the scripts that need shaping or are written right
+/// to left.
+const UNSHAPED_SCRIPTS: [(char, char, &str); 17] = [
+    ('\u{0590}', '\u{05FF}', "Hebrew"),
+    ('\u{0600}', '\u{06FF}', "Arabic"),
+    ('\u{0700}', '\u{074F}', "Syriac"),
+    ('\u{0750}', '\u{077F}', "Arabic"),
+    ('\u{0780}', '\u{07BF}', "Thaana"),
+    ('\u{07C0}', '\u{07FF}', "N'Ko"),
+    ('\u{0800}', '\u{085F}', "Samaritan or Mandaic"),
+    ('\u{0860}', '\u{08FF}', "Arabic"),
+    ('\u{0900}', '\u{0DFF}', "an Indic script"),
+    ('\u{0F00}', '\u{0FFF}', "Tibetan"),
+    ('\u{1000}', '\u{109F}', "Myanmar"),
+    ('\u{1780}', '\u{17FF}', "Khmer"),
+    ('\u{1800}', '\u{18AF}', "Mongolian"),
+    ('\u{A8E0}', '\u{A8FF}', "Devanagari"),
+    ('\u{FB1D}', '\u{FB4F}', "Hebrew"),
+    ('\u{FB50}', '\u{FDFF}', "Arabic"),
+    ('\u{FE70}', '\u{FEFE}', "Arabic")
+];
+
+
+pub enum FontData {
+    Builtin(BuiltinFont),
+    External(Vec<u8>)
+}
+
+/// The fonts of a template, loaded once for all the ballots.
+pub struct BallotFonts {
+    pub fonts: BTreeMap<String, FontData>
+}
+
+
+/// Check that a text can be printed without shaping, left to right.
+///
+/// # Examples
+///
+/// ```
+/// use seventh_estate::ballots::check_unshaped_text;
+///
+/// assert!(check_unshaped_text("Référendum, Референдум, 公投").is_ok());
+/// assert!(check_unshaped_text("استفتاء").is_err());
+/// ```
+pub fn check_unshaped_text(text: &str) -> Result<()> {
+    for c in text.chars() {
+        if let Some((_, _, script)) = UNSHAPED_SCRIPTS.iter().find(|(first, last, _)| *first <= c && c <= *last) {
+            return Err(From::from(format!("Cannot print \"{}\": {} needs shaping or right-to-left layout, which printing does not support.", text, script)));
+        }
+    }
+    Ok(())
+}
+
+/// SHA-256 hash, in hex, of a font file.
+pub fn font_hash(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.input(data);
+    hex::encode(hasher.result())
+}
+
+impl FontData {
+    /// Load a font, checking a font file against its recorded hash.
+    pub fn load(name: &str, font: &TemplateFont) -> Result<Self> {
+        match font {
+            TemplateFont::Builtin(font_name) => {
+                let builtin = builtin_font(font_name)
+                    .ok_or_else(|| format!("Ballot template font {} is not a built-in font: {}.", name, font_name))?;
+                Ok(FontData::Builtin(builtin))
+            },
+            TemplateFont::File { file, hash } => {
+                let data = std::fs::read(file)?;
+                if hash.as_ref().map_or(false, |hash| *hash != font_hash(&data)) {
+                    return Err(From::from(format!("Ballot template font {} changed since the poll was created: {}.", name, file)));
+                }
+                if ttf_parser::Face::from_slice(&data, 0).is_err() {
+                    return Err(From::from(format!("Ballot template font {} is not a TrueType or OpenType font: {}.", name, file)));
+                }
+                Ok(FontData::External(data))
+            }
+        }
+    }
+
+    /// Add the font to a document, embedding a font file.
+    pub fn add_to(self: &Self, doc: &PdfDocumentReference) -> Result<IndirectFontRef> {
+        let reference = match self {
+            FontData::Builtin(builtin) => doc.add_builtin_font(*builtin)?,
+            FontData::External(data) => doc.add_external_font(data.as_slice())?
+        };
+        Ok(reference)
+    }
+
+    /// Subset a font file to the glyphs of the given characters and of
+    /// printable ASCII. Built-in fonts are left as they are.
+    pub fn subset(self: Self, characters: &BTreeSet<char>) -> Result<Self> {
+        match self {
+            FontData::External(data) => {
+                let characters: BTreeSet<char> = characters.iter().cloned()
+                    .chain((0x20u8..0x7f).map(char::from))
+                    .collect();
+                Ok(FontData::External(subset_font(&data, &characters)?))
+            },
+            builtin => Ok(builtin)
+        }
+    }
+
+    /// Width in millimeters of a line of text at a font size in points.
+    pub fn text_width(self: &Self, text: &str, size: f64) -> f64 {
+        let em: f64 = match self {
+            FontData::Builtin(_) => BUILTIN_ADVANCE * text.chars().count() as f64,
+            FontData::External(data) => match ttf_parser::Face::from_slice(data, 0) {
+                Ok(face) => {
+                    let units_per_em = face.units_per_em().unwrap_or(1000) as f64;
+                    text.chars()
+                        .map(|c| face.glyph_index(c)
+                            .and_then(|glyph| face.glyph_hor_advance(glyph))
+                            .map_or(BUILTIN_ADVANCE, |advance| advance as f64 / units_per_em))
+                        .sum()
+                },
+                Err(_) => BUILTIN_ADVANCE * text.chars().count() as f64
+            }
+        };
+        em * size / POINTS_PER_MM
+    }
+}
+
+impl BallotFonts {
+    /// Load the fonts of a template, checking each font file against the
+    /// hash recorded when the poll was created.
+    pub fn load(template: &BallotTemplate) -> Result<Self> {
+        let fonts = template.fonts.iter()
+            .map(|(name, font)| -> Result<(String, FontData)> {
+                Ok((name.clone(), FontData::load(name, font)?))
+            })
+            .collect::<Result<BTreeMap<String, FontData>>>()?;
+        Ok(BallotFonts { fonts: fonts })
+    }
+
+    /// Subset the font files to the glyphs of the given characters and
+    /// of printable ASCII, in which vote codes, serials and URLs are
+    /// printed.
+    pub fn subset(self: Self, characters: &BTreeSet<char>) -> Result<Self> {
+        let fonts = self.fonts.into_iter()
+            .map(|(name, font)| -> Result<(String, FontData)> {
+                Ok((name, font.subset(characters)?))
+            })
+            .collect::<Result<BTreeMap<String, FontData>>>()?;
+        Ok(BallotFonts { fonts: fonts })
+    }
+
+    pub fn get(self: &Self, name: &str) -> &FontData {
+        &self.fonts[name]
+    }
+
+    /// Wrap text set in a font to lines no wider than `width` millimeters.
+    pub fn wrap(self: &Self, name: &str, text: &str, size: f64, width: f64) -> Vec<String> {
+        let font = self.get(name);
+        wrap_text(text, width, &|line| font.text_width(line, size))
+    }
+}
+
+/// Wrap text to lines no wider than `width`, as measured by `measure`.
+/// Lines break at spaces; a word too wide for a line, or text without
+/// spaces such as CJK, breaks between characters. Line breaks and blank
+/// lines of the text are kept.
+///
+/// # Examples
+///
+/// ```
+/// use seventh_estate::ballots::wrap_text;
+///
+/// let measure = |line: &str| line.chars().count() as f64;
+/// assert_eq!(vec!["one two", "three"], wrap_text("one two three", 7.0, &measure));
+/// assert_eq!(vec!["abcd", "efgh", "ij"], wrap_text("abcdefghij", 4.0, &measure));
+/// assert_eq!(vec!["a", "", "b"], wrap_text("a\n\nb", 4.0, &measure));
+/// ```
+pub fn wrap_text(text: &str, width: f64, measure: &dyn Fn(&str) -> f64) -> Vec<String> {
+    text.lines()
+        .flat_map(|line| wrap_line(line, width, measure))
+        .collect()
+}
+
+fn wrap_line(line: &str, width: f64, measure: &dyn Fn(&str) -> f64) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for word in line.split(' ') {
+        let candidate = match current.is_empty() {
+            true => word.to_owned(),
+            false => format!("{} {}", current, word)
+        };
+        if measure(&candidate) <= width {
+            current = candidate;
+            continue;
+        }
+        if !current.is_empty() {
+            lines.push(current);
+            current = String::new();
+        }
+        // Break the word between characters, at least one per line.
+        for c in word.chars() {
+            let mut candidate = current.clone();
+            candidate.push(c);
+            if !current.is_empty() && measure(&candidate) > width {
+                lines.push(current);
+                current = c.to_string();
+            } else {
+                current = candidate;
+            }
+        }
+    }
+    lines.push(current);
+    lines
+}
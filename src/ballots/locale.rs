@@ -0,0 +1,146 @@
+//! # Ballot Languages
+//!
+//! The texts printed on a ballot, the questions, option labels and the
+//! template texts, in the language of its voter. A voter's language is
+//! used when every question is translated to it; the template texts fall
+//! back to the template's own where not translated. Other voters, and
+//! voters without a language, get the poll's default language.
+//!
+//! A bilingual ballot prints the voter's language first and the default
+//! language after it: single lines are joined with " / " and longer texts
+//! are stacked.
+//!
+//! Only the printed texts change. The ballots, their vote codes and the
+//! order of the options are the same in every language.
+
+use std::collections::BTreeSet;
+use serde::{Serialize, Deserialize};
+use crate::poll_configuration::PollQuestion;
+use super::template::BallotTemplate;
+
+const BILINGUAL_SEPARATOR: &str = " / ";
+
+
+/// Languages of the printed ballots.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BallotLanguages {
+    /// Language of the untranslated texts, e.g. `en`.
+    #[serde(default)]
+    pub default: String,
+    /// Print translated ballots in both the voter's and the default language.
+    #[serde(default)]
+    pub bilingual: bool
+}
+
+/// The texts of one printed ballot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BallotTexts {
+    /// Each question, in the order of the poll questions.
+    pub questions: Vec<String>,
+    /// The option labels of each question, in the order of the poll options.
+    pub options: Vec<Vec<String>>,
+    pub instructions: String,
+    pub several_questions_title: String,
+    /// Text of each template block, in the order of the blocks.
+    pub blocks: Vec<String>
+}
+
+
+impl BallotTexts {
+    /// The untranslated texts.
+    pub fn untranslated(questions: &Vec<PollQuestion>, template: &BallotTemplate) -> Self {
+        BallotTexts {
+            questions: questions.iter().map(|question| question.question.clone()).collect(),
+            options: questions.iter().map(|question| question.options.clone()).collect(),
+            instructions: template.instructions.clone(),
+            several_questions_title: template.several_questions_title.clone(),
+            blocks: template.blocks.iter().map(|block| block.text.clone()).collect()
+        }
+    }
+
+    /// The texts in a language, if every question is translated to it.
+    pub fn translated(questions: &Vec<PollQuestion>, template: &BallotTemplate, language: &str) -> Option<Self> {
+        let translations = questions.iter()
+            .map(|question| question.translation(language))
+            .collect::<Option<Vec<_>>>()?;
+        let mut texts = BallotTexts::untranslated(questions, template);
+        texts.questions = translations.iter().map(|translation| translation.question.clone()).collect();
+        texts.options = translations.iter().map(|translation| translation.options.clone()).collect();
+        if let Some(translation) = template.translations.get(language) {
+            if let Some(instructions) = &translation.instructions {
+                texts.instructions = instructions.clone();
+            }
+            if let Some(title) = &translation.several_questions_title {
+                texts.several_questions_title = title.clone();
+            }
+            if let Some(blocks) = &translation.blocks {
+                texts.blocks = blocks.clone();
+            }
+        }
+        Some(texts)
+    }
+
+    /// The texts of a voter's ballot, and whether they are in the voter's
+    /// language. Voters without a language get the default language.
+    pub fn for_language(questions: &Vec<PollQuestion>, template: &BallotTemplate, languages: &BallotLanguages, language: &str) -> (Self, bool) {
+        let untranslated = BallotTexts::untranslated(questions, template);
+        if language.is_empty() || language == languages.default {
+            return (untranslated, true);
+        }
+        match BallotTexts::translated(questions, template, language) {
+            Some(texts) => match languages.bilingual {
+                true => (texts.bilingual(&untranslated), true),
+                false => (texts, true)
+            },
+            None => (untranslated, false)
+        }
+    }
+
+    /// The characters of the texts.
+    pub fn characters(self: &Self) -> BTreeSet<char> {
+        self.questions.iter()
+            .chain(self.options.iter().flatten())
+            .chain(vec![&self.instructions, &self.several_questions_title])
+            .chain(self.blocks.iter())
+            .flat_map(|text| text.chars())
+            .collect()
+    }
+
+    /// These texts followed by the texts of another language.
+    pub fn bilingual(self: &Self, other: &Self) -> Self {
+        let join_all = |first: &Vec<String>, second: &Vec<String>| -> Vec<String> {
+            first.iter().zip(second.iter())
+                .map(|(first, second)| join_texts(first, second))
+                .collect()
+        };
+        BallotTexts {
+            questions: join_all(&self.questions, &other.questions),
+            options: self.options.iter().zip(other.options.iter())
+                .map(|(first, second)| join_all(first, second))
+                .collect(),
+            instructions: join_texts(&self.instructions, &other.instructions),
+            several_questions_title: join_texts(&self.several_questions_title, &other.several_questions_title),
+            blocks: join_all(&self.blocks, &other.blocks)
+        }
+    }
+}
+
+/// Join a text with its translation: identical texts once, single lines
+/// side by side and longer texts one above the other.
+///
+/// # Examples
+///
+/// ```
+/// use seventh_estate::ballots::join_texts;
+///
+/// assert_eq!("Sí / Yes", join_texts("Sí", "Yes"));
+/// assert_eq!("{question}", join_texts("{question}", "{question}"));
+/// assert_eq!("uno\ndos\n\none\ntwo", join_texts("uno\ndos", "one\ntwo"));
+/// ```
+pub fn join_texts(first: &str, second: &str) -> String {
+    match (first == second, first.contains('\n') || second.contains('\n')) {
+        (true, _) => first.to_owned(),
+        (false, false) => format!("{}{}{}", first, BILINGUAL_SEPARATOR, second),
+        (false, true) => format!("{}\n\n{}", first, second)
+    }
+}
@@ -2,10 +2,13 @@
 //!
 //! `ballots` contains only the information needed to manage a poll.
 //! For example, the poll question is not included. The poll choices
-//! are limited to For and Against.
+//! are identified by the index of the option in the poll configuration.
 
 use serde::{Serialize, Deserialize};
 
+pub mod votecode;
+pub use votecode::*;
+
 pub mod untagged;
 pub use untagged::*;
 
@@ -15,6 +18,21 @@ pub use tagged::*;
 pub mod printed;
 pub use printed::*;
 
+pub mod template;
+pub use template::*;
+
+pub mod fonts;
+pub use fonts::*;
+
+pub mod subset;
+pub use subset::*;
+
+pub mod locale;
+pub use locale::*;
+
+pub mod url;
+pub use url::*;
+
 pub mod print;
 pub use print::*;
 
@@ -1,25 +1,50 @@
 //! # Print ballot to PDF
 //!
-//! //! CreateBallot (ballot)
-//!     Create new pdf
-//!     Write title ("YES/NO Ballot")
-//!     Write Instructions("vote online by..")
-//!     Write Ballot serial ("ballot serial: {ballot.serial}")
-//!     Write choices
-//!         Write choice1 ("{ballot.choice1.votecode} {ballot.choice1.choice")
-//!         Write choice2 ("{ballot.choice2.votecode} {ballot.choice2.choice")
+//! CreateBallot (ballot, texts, template)
+//!     Create new pdf, of the template paper size, embedding its font files
+//!     Write the text blocks of the template (title, instructions, serial),
+//!     in the language of the texts, wrapped to fit
+//!     Draw the QR code of the voting URL and serial, and the short URL
+//!     (only if the poll has a voting URL)
+//!     For each question
+//!         Write question (only if there are several)
+//!         For each choice ("{choice.votecode} {option}", scratch-off area)
 //!     Leavy empty space for Decoy text ("This ballot is a decoy!...")
+//!
+//! PrintBallotsBatched (ballots of every serial)
+//!     Keep the ballots in the order given
+//!     For each chunk of sheets, create new pdf
+//!         For each sheet, place 1, 2 or 4 ballots (CreateBallot)
+//!         Draw crop marks around the ballots (2-up and 4-up)
+//!
+//! For two-pass printing, the scratch-off areas go on their own layer:
+//! the base layer holds the text and vote codes, the scratch mask the
+//! solid areas to coat. The mask is an optional content group of the same
+//! PDF or a PDF of its own, and both carry registration marks.
+//!
+//! CreateDecoyStickers (decoy serials)
+//!     Create new pdf
+//!     For each decoy serial, add a sticker page
+//!         Write Decoy text ("This ballot is a decoy!...")
+//!         Write Ballot serial ("ballot serial: {serial}")
 
 use super::*;
-use std::result::Result;
+use crate::Result;
 use printpdf::*;
 use std::fs::File;
 use std::io::BufWriter;
-use super::untagged::{Ballot, BallotChoice, ChoiceValue, string_from_votecode};
+use super::untagged::{Ballot, BallotChoice, BallotSerial, string_from_ballotserial};
+use super::votecode::{VoteCodeFormat, string_from_votecode};
+use super::template::{BallotTemplate, ChoicesLayout, ChoiceText, QrCodeLayout, fill_placeholders};
+use super::fonts::BallotFonts;
+use super::locale::BallotTexts;
+use super::url::VotingUrl;
+use qrcode::QrCode;
+use std::collections::BTreeMap;
 
 struct Text<'a> {
     pub text: String,
-    pub size: i64,
+    pub size: f64,
     pub startx: Mm,
     pub starty: Mm,
     pub font: &'a IndirectFontRef,
@@ -30,197 +55,712 @@ struct FileSize {
     pub width: Mm,
 }
 
-const _A5: FileSize = FileSize {
-    height: Mm(210.0), 
-    width: Mm(148.0)
+/// Lower left corner of a ballot on its page.
+#[derive(Clone, Copy)]
+struct Origin {
+    pub x: Mm,
+    pub y: Mm,
+}
+
+const PAGE_ORIGIN: Origin = Origin {
+    x: Mm(0.0),
+    y: Mm(0.0)
+};
+
+const _SRA4_LANDSCAPE: FileSize = FileSize {
+    height: Mm(225.0),
+    width: Mm(320.0)
 };
 
-const _A6: FileSize = FileSize {
-    height: Mm(148.0), 
-    width: Mm(105.0)
+const _SRA3: FileSize = FileSize {
+    height: Mm(450.0),
+    width: Mm(320.0)
+};
+
+const _STICKER: FileSize = FileSize {
+    height: Mm(50.0),
+    width: Mm(80.0)
 };
 
 // Text to be printed to PDF file
 pub const BALLOTS_PATH: &str = "ballots/";
-const BALLOT_SIZE: FileSize = _A5;
-//const TITLE_TEXT: &str = "YES/NO Ballot";
-const INST_TITLE: &str = "Instructions";
-const INST_TEXT: &str = 
-"vote online by\n
-entering ballot serial\n
-number and the vote\n
-code printed under\n
-the scratch-off next\n
-to your choice:";
+pub const DECOYS_PATH: &str = "decoys/";
+const DECOY_STICKER_SIZE: FileSize = _STICKER;
+const DECOY_STICKERS_FILE: &str = "decoy_stickers.pdf";
+const BATCH_FILE_STEM: &str = "ballots";
+const BASE_LAYER: &str = "Base";
+const MASK_LAYER: &str = "Scratch mask";
+const MASK_FILE_SUFFIX: &str = "_mask";
+const REGISTRATION_MARK_INSET: Mm = Mm(5.0);
+const REGISTRATION_MARK_RADIUS: Mm = Mm(2.0);
+const CROP_MARK_OFFSET: Mm = Mm(1.5);
+const CROP_MARK_LENGTH: Mm = Mm(5.0);
+/// Light modules around a QR code, included in its size.
+const QR_QUIET_ZONE: usize = 4;
+const MM_PER_POINT: f64 = 25.4 / 72.0;
 const BALLOT_SERIAL_TEXT: &str = "ballot serial: ";
-const VOTE_CODE_TEXT: &str = "vote code:           choice:";
-const _DECOY_TEXT: &str = 
+const DECOY_TEXT: &str = 
 "This ballot is a decoy!\n
 Remove this sticker\n
 and sell this vote!";
 
 
+/// Number of ballots per printed sheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Imposition {
+    /// One ballot per page.
+    OneUp,
+    /// Two ballots side by side, with crop marks. A5 ballots go on an
+    /// SRA4 sheet.
+    TwoUp,
+    /// Four ballots, two by two, with crop marks. A5 ballots go on an
+    /// SRA3 sheet.
+    FourUp
+}
+
+impl Imposition {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "1-up" => Some(Imposition::OneUp),
+            "2-up" => Some(Imposition::TwoUp),
+            "4-up" => Some(Imposition::FourUp),
+            _ => None
+        }
+    }
+
+    pub fn ballots_per_sheet(self: &Self) -> usize {
+        let (columns, rows) = self.grid();
+        columns * rows
+    }
+
+    /// Columns and rows of ballots on a sheet.
+    fn grid(self: &Self) -> (usize, usize) {
+        match self {
+            Imposition::OneUp => (1, 1),
+            Imposition::TwoUp => (2, 1),
+            Imposition::FourUp => (2, 2)
+        }
+    }
+
+    /// The standard sheet when the ballots and their crop marks fit on it,
+    /// else a sheet just large enough for them.
+    fn sheet_size(self: &Self, page: &FileSize) -> FileSize {
+        let (columns, rows) = self.grid();
+        let bleed = (CROP_MARK_OFFSET + CROP_MARK_LENGTH) * 2.0;
+        let needed = FileSize {
+            height: page.height * rows as f64 + bleed,
+            width: page.width * columns as f64 + bleed
+        };
+        let standard = match self {
+            Imposition::OneUp => return FileSize { height: page.height, width: page.width },
+            Imposition::TwoUp => _SRA4_LANDSCAPE,
+            Imposition::FourUp => _SRA3
+        };
+        match needed.height.0 <= standard.height.0 && needed.width.0 <= standard.width.0 {
+            true => standard,
+            false => needed
+        }
+    }
+}
+
+/// Where the scratch-off areas are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScratchOffLayers {
+    /// Grey dashed areas under the vote codes, on the same layer, for
+    /// proofs and single-pass printing.
+    Combined,
+    /// A base layer and a scratch mask layer in the same PDF.
+    Layers,
+    /// The scratch mask in a PDF of its own, `<name>_mask.pdf`.
+    Separate
+}
+
+impl ScratchOffLayers {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "combined" => Some(ScratchOffLayers::Combined),
+            "layers" => Some(ScratchOffLayers::Layers),
+            "separate" => Some(ScratchOffLayers::Separate),
+            _ => None
+        }
+    }
+}
+
+/// Where and how step 3 writes the ballot PDFs.
+#[derive(Debug, Clone)]
+pub struct BallotPdfOutput {
+    /// Directory of the PDFs.
+    pub directory: String,
+    /// One PDF for all serials (or one per chunk) instead of one per serial.
+    pub batched: bool,
+    pub imposition: Imposition,
+    /// Sheets per batched PDF. All sheets go in one PDF if `None`.
+    pub sheets_per_file: Option<usize>,
+    pub scratch_off: ScratchOffLayers,
+    /// Directory of the decoy packet, kept apart from the ballots.
+    pub decoy_directory: String
+}
+
+impl Default for BallotPdfOutput {
+    fn default() -> Self {
+        BallotPdfOutput {
+            directory: BALLOTS_PATH.to_owned(),
+            batched: false,
+            imposition: Imposition::OneUp,
+            sheets_per_file: None,
+            scratch_off: ScratchOffLayers::Combined,
+            decoy_directory: DECOYS_PATH.to_owned()
+        }
+    }
+}
+
+
 fn add_text(layer: &PdfLayerReference, text: &Text){
-    layer.use_text(text.text.to_string(), text.size as f64, text.startx, text.starty, &text.font);
+    layer.use_text(text.text.to_string(), text.size, text.startx, text.starty, &text.font);
 }
 
-fn make_circle(radius: Pt, startx: Pt, starty: Pt) -> Line{
+fn make_scratch_off(width: Pt, height: Pt, centerx: Pt, centery: Pt) -> Line{
 
-    // Make circle
-    let circle = Line {
-        points: utils::calculate_points_for_rect(radius, Pt(20.0), startx, starty),
+    // Make scratch-off area
+    let area = Line {
+        points: utils::calculate_points_for_rect(width, height, centerx, centery),
         is_closed: true,
         has_fill: true,
         has_stroke: true,
         is_clipping_path: false,
     };
     
-    circle
+    area
+}
+
+fn page_size(template: &BallotTemplate) -> FileSize {
+    let (width, height) = template.page_size();
+    FileSize {
+        height: Mm(height),
+        width: Mm(width)
+    }
+}
+
+/// The fonts of a template, added to a document.
+struct DocumentFonts<'a> {
+    fonts: &'a BallotFonts,
+    references: BTreeMap<String, IndirectFontRef>
+}
+
+impl<'a> DocumentFonts<'a> {
+    /// Add the fonts to a document, embedding the font files.
+    fn add(doc: &PdfDocumentReference, fonts: &'a BallotFonts) -> Result<Self> {
+        let references = fonts.fonts.iter()
+            .map(|(name, font)| -> Result<(String, IndirectFontRef)> {
+                Ok((name.clone(), font.add_to(doc)?))
+            })
+            .collect::<Result<BTreeMap<String, IndirectFontRef>>>()?;
+        Ok(DocumentFonts {
+            fonts: fonts,
+            references: references
+        })
+    }
+
+    fn reference(self: &Self, name: &str) -> &IndirectFontRef {
+        &self.references[name]
+    }
+}
+
+/// The layers a ballot is drawn on: the base, and the scratch mask when
+/// the scratch-off is printed in a pass of its own.
+struct BallotLayers {
+    base: PdfLayerReference,
+    mask: Option<PdfLayerReference>
+}
+
+/// The PDF of a ballot file, and the PDF of its scratch mask when it is
+/// separate.
+struct BallotDocuments {
+    base: PdfDocumentReference,
+    mask: Option<PdfDocumentReference>,
+    scratch_off: ScratchOffLayers,
+    /// Layers of the first page, created with the documents.
+    first_page: Option<BallotLayers>
 }
 
-fn make_dir() -> Result<(), std::io::Error>{
-    match DirBuilder::new().create(Path::new(BALLOTS_PATH)) {
+impl BallotDocuments {
+    fn new(title: &str, size: &FileSize, scratch_off: ScratchOffLayers) -> Self {
+        let (base, page, layer) = PdfDocument::new(title.to_string(), size.width, size.height, BASE_LAYER.to_string());
+        let base_layer = base.get_page(page).get_layer(layer);
+        let (mask, mask_layer) = match scratch_off {
+            ScratchOffLayers::Combined => (None, None),
+            ScratchOffLayers::Layers => (None, Some(base.get_page(page).add_layer(MASK_LAYER))),
+            ScratchOffLayers::Separate => {
+                let (mask, page, layer) = PdfDocument::new(format!("{} {}", title, MASK_LAYER), size.width, size.height, MASK_LAYER.to_string());
+                let mask_layer = mask.get_page(page).get_layer(layer);
+                (Some(mask), Some(mask_layer))
+            }
+        };
+        BallotDocuments {
+            base: base,
+            mask: mask,
+            scratch_off: scratch_off,
+            first_page: Some(BallotLayers {
+                base: base_layer,
+                mask: mask_layer
+            })
+        }
+    }
+
+    /// Layers of the next page, with registration marks when there is a
+    /// scratch mask.
+    fn next_page(self: &mut Self, size: &FileSize) -> BallotLayers {
+        let layers = match self.first_page.take() {
+            Some(layers) => layers,
+            None => {
+                let (page, layer) = self.base.add_page(size.width, size.height, BASE_LAYER.to_string());
+                let mask = match (self.scratch_off, &self.mask) {
+                    (ScratchOffLayers::Layers, _) => Some(self.base.get_page(page).add_layer(MASK_LAYER)),
+                    (ScratchOffLayers::Separate, Some(mask)) => {
+                        let (page, layer) = mask.add_page(size.width, size.height, MASK_LAYER.to_string());
+                        Some(mask.get_page(page).get_layer(layer))
+                    },
+                    _ => None
+                };
+                BallotLayers {
+                    base: self.base.get_page(page).get_layer(layer),
+                    mask: mask
+                }
+            }
+        };
+        if let Some(mask) = &layers.mask {
+            draw_registration_marks(&layers.base, size);
+            draw_registration_marks(mask, size);
+        }
+        layers
+    }
+
+    /// Save to `<directory>/<stem>.pdf`, and a separate mask to
+    /// `<directory>/<stem>_mask.pdf`. Returns the paths written.
+    fn save(self: Self, directory: &str, stem: &str) -> Result<Vec<String>> {
+        let save = |doc: PdfDocumentReference, name: String| -> Result<String> {
+            let file = Path::new(directory).join(name);
+            let mut file_writer = BufWriter::new(File::create(&file)?);
+            doc.save(&mut file_writer)?;
+            Ok(file.display().to_string())
+        };
+        let mut files = vec![save(self.base, format!("{}.pdf", stem))?];
+        if let Some(mask) = self.mask {
+            files.push(save(mask, format!("{}{}.pdf", stem, MASK_FILE_SUFFIX))?);
+        }
+        Ok(files)
+    }
+}
+
+fn make_dir(path: &str) -> Result<()> {
+    match DirBuilder::new().create(Path::new(path)) {
         Ok(_) => Ok(()),
         Err(err) => {
             match err.kind() {
                 // Do nothing if directory already exists
                 ErrorKind::AlreadyExists => Ok(()),
-                // Else panic
-                _ => Err(err)
+                // Else fail
+                _ => Err(From::from(err))
             }
         }
     }
 }
 
-pub fn print_ballot(ballot: &Ballot, question: &str, option1: &str, option2: &str) -> () {
+/// Print the ballot of one serial to `<directory>/<serial>.pdf`, and its
+/// scratch mask to `<directory>/<serial>_mask.pdf` when separate.
+/// `ballots` holds the ballot of each question, in the order of the
+/// questions of `texts`. With a voting URL, the ballot carries its QR
+/// code where the template places it. Returns the paths of the PDFs
+/// written.
+pub fn print_ballot(ballots: &Vec<Ballot>, texts: &BallotTexts, votecode_format: &VoteCodeFormat, voting_url: Option<&VotingUrl>, template: &BallotTemplate, fonts: &BallotFonts, output: &BallotPdfOutput) -> Result<Vec<String>> {
+    check_serial_ballots(ballots, texts, template)?;
+    let ballot: &Ballot = &ballots[0];
 
     // Create ballots dir
-    make_dir().unwrap();
+    make_dir(&output.directory)?;
 
-    // Create new document
-    let file = BALLOTS_PATH.to_string() + &ballot.serial.to_string()  + ".pdf";
-    let mut file_writer = BufWriter::new(File::create(file).unwrap());
-    
     // Start new PDF
-    let (doc, page1, layer1) = PdfDocument::new(ballot.serial.to_string(), BALLOT_SIZE.width, BALLOT_SIZE.height, "layer1".to_string());
-    let current_layer = doc.get_page(page1).get_layer(layer1);
+    let page = page_size(template);
+    let mut documents = BallotDocuments::new(&ballot.serial.to_string(), &page, output.scratch_off);
+    let layers = documents.next_page(&page);
 
-    // Add fonts for title and text
-    let font_title = doc.add_builtin_font(BuiltinFont::CourierBold).unwrap();
-    let font_text = doc.add_builtin_font(BuiltinFont::Courier).unwrap();
-
-    let title: Text = Text {
-        text: question.to_string(), 
-        size: 20, 
-        startx: Mm(10.0), 
-        starty: BALLOT_SIZE.height - Mm(20.0),
-        font: &font_title,
-    };
+    // Add fonts of the template
+    let document_fonts = DocumentFonts::add(&documents.base, fonts)?;
+
+    draw_ballot(&layers, PAGE_ORIGIN, ballots, texts, votecode_format, voting_url, template, &document_fonts)?;
 
-    let instructions_title: Text = Text {
-        text: INST_TITLE.to_string(),
-        size: 15,
-        startx: Mm(10.0),
-        starty: BALLOT_SIZE.height - Mm(35.0),
-        ..title
+    // Save documents
+    documents.save(&output.directory, &ballot.serial.to_string())
+}
+
+/// Print the ballots of every serial, in the order given, to one PDF, or
+/// to one PDF per `sheets_per_file` sheets, imposed 1, 2 or 4 to a sheet.
+/// `serial_ballots` holds, per serial, the ballot of each question and
+/// the texts to print it with. Returns the paths of the PDFs written,
+/// each followed by its scratch mask when separate.
+pub fn print_ballots_batched(serial_ballots: &Vec<(Vec<Ballot>, &BallotTexts)>, votecode_format: &VoteCodeFormat, voting_url: Option<&VotingUrl>, template: &BallotTemplate, fonts: &BallotFonts, output: &BallotPdfOutput) -> Result<Vec<String>> {
+    serial_ballots.iter()
+        .map(|(ballots, texts)| check_serial_ballots(ballots, texts, template))
+        .collect::<Result<()>>()?;
+    if output.sheets_per_file == Some(0) {
+        return Err(From::from("A batched PDF must hold at least one sheet."));
+    }
+
+    // Create ballots dir
+    make_dir(&output.directory)?;
+
+    let sheets: Vec<&[(Vec<Ballot>, &BallotTexts)]> = serial_ballots.chunks(output.imposition.ballots_per_sheet()).collect();
+    let files: Vec<&[&[(Vec<Ballot>, &BallotTexts)]]> = match output.sheets_per_file {
+        Some(sheets_per_file) => sheets.chunks(sheets_per_file).collect(),
+        None => vec![&sheets[..]]
     };
-    let instructions_text: Text = Text {
-        text: INST_TEXT.to_string(),
-        size: 10,
-        starty: BALLOT_SIZE.height - Mm(40.0),
-        font: &font_text,
-        ..instructions_title
+
+    let page = page_size(template);
+    let sheet_size = output.imposition.sheet_size(&page);
+    let (columns, rows) = output.imposition.grid();
+    // The ballots are centered on the sheet, the first one top left.
+    let grid_origin = Origin {
+        x: (sheet_size.width - page.width * columns as f64) / 2.0,
+        y: (sheet_size.height - page.height * rows as f64) / 2.0
     };
+    let digits = files.len().to_string().len();
+
+    let mut written: Vec<String> = Vec::new();
+    for (n, file_sheets) in files.iter().enumerate()
+        .filter(|(_, file_sheets)| !file_sheets.is_empty()) {
+        let stem = match files.len() {
+            1 => BATCH_FILE_STEM.to_string(),
+            _ => format!("{}_{:0width$}", BATCH_FILE_STEM, n + 1, width = digits)
+        };
+
+        // Start new PDF
+        let mut documents = BallotDocuments::new(BATCH_FILE_STEM, &sheet_size, output.scratch_off);
+
+        // Add fonts of the template
+        let document_fonts = DocumentFonts::add(&documents.base, fonts)?;
+
+        file_sheets.iter()
+            .map(|sheet| -> Result<()> {
+                let layers = documents.next_page(&sheet_size);
+                sheet.iter().enumerate()
+                    .map(|(slot, (ballots, texts))| -> Result<()> {
+                        let (column, row) = (slot % columns, slot / columns);
+                        let origin = Origin {
+                            x: grid_origin.x + page.width * column as f64,
+                            y: grid_origin.y + page.height * (rows - 1 - row) as f64
+                        };
+                        draw_ballot(&layers, origin, ballots, texts, votecode_format, voting_url, template, &document_fonts)
+                    })
+                    .collect::<Result<()>>()?;
+                if output.imposition != Imposition::OneUp {
+                    draw_crop_marks(&layers.base, &page, grid_origin, columns, rows);
+                }
+                Ok(())
+            })
+            .collect::<Result<()>>()?;
+
+        // Save documents
+        written.extend(documents.save(&output.directory, &stem)?);
+    }
+    Ok(written)
+}
+
+fn check_serial_ballots(ballots: &Vec<Ballot>, texts: &BallotTexts, template: &BallotTemplate) -> Result<()> {
+    if ballots.is_empty() || ballots.len() != texts.questions.len() {
+        return Err(From::from("One ballot is needed per question."));
+    }
+    if ballots.len() != texts.options.len()
+        || ballots.iter().zip(texts.options.iter()).any(|(question_ballot, options)| question_ballot.choices.len() != options.len()) {
+        return Err(From::from("Each choice of a ballot needs an option label."));
+    }
+    if texts.blocks.len() != template.blocks.len() {
+        return Err(From::from("Each block of the template needs a text."));
+    }
+    if ballots.iter().any(|question_ballot| question_ballot.serial != ballots[0].serial) {
+        return Err(From::from("The ballots of all questions must share a serial."));
+    }
+    Ok(())
+}
 
-    let ballot_serial: Text = Text {
-        text: BALLOT_SERIAL_TEXT.to_string() + &ballot.serial.to_string(),
-        size: 12,
-        starty: BALLOT_SIZE.height - Mm(70.0),
-        ..instructions_text
+/// Short marks outside the ballots, in line with every cut.
+fn draw_crop_marks(layer: &PdfLayerReference, page: &FileSize, grid_origin: Origin, columns: usize, rows: usize) {
+    let mark = |x1: Mm, y1: Mm, x2: Mm, y2: Mm| Line {
+        points: vec![(Point::new(x1, y1), false), (Point::new(x2, y2), false)],
+        is_closed: false,
+        has_fill: false,
+        has_stroke: true,
+        is_clipping_path: false,
     };
+    let bottom = grid_origin.y;
+    let top = grid_origin.y + page.height * rows as f64;
+    let left = grid_origin.x;
+    let right = grid_origin.x + page.width * columns as f64;
 
-    let vote_code: Text = Text {
-        text: VOTE_CODE_TEXT.to_string(),
-        ..ballot_serial
+    layer.set_line_dash_pattern(LineDashPattern::default());
+    layer.set_outline_color(Color::Greyscale(Greyscale::new(0.0, None)));
+    layer.set_outline_thickness(0.25);
+    (0..=columns)
+        .map(|column| left + page.width * column as f64)
+        .for_each(|x| {
+            layer.add_shape(mark(x, bottom - CROP_MARK_OFFSET - CROP_MARK_LENGTH, x, bottom - CROP_MARK_OFFSET));
+            layer.add_shape(mark(x, top + CROP_MARK_OFFSET, x, top + CROP_MARK_OFFSET + CROP_MARK_LENGTH));
+        });
+    (0..=rows)
+        .map(|row| bottom + page.height * row as f64)
+        .for_each(|y| {
+            layer.add_shape(mark(left - CROP_MARK_OFFSET - CROP_MARK_LENGTH, y, left - CROP_MARK_OFFSET, y));
+            layer.add_shape(mark(right + CROP_MARK_OFFSET, y, right + CROP_MARK_OFFSET + CROP_MARK_LENGTH, y));
+        });
+}
+
+/// Targets, a circle and a cross, 5 mm in from each corner of the page,
+/// on the base and on the scratch mask, to register the two passes.
+fn draw_registration_marks(layer: &PdfLayerReference, size: &FileSize) {
+    let line = |x1: Mm, y1: Mm, x2: Mm, y2: Mm| Line {
+        points: vec![(Point::new(x1, y1), false), (Point::new(x2, y2), false)],
+        is_closed: false,
+        has_fill: false,
+        has_stroke: true,
+        is_clipping_path: false,
     };
+    let inset = REGISTRATION_MARK_INSET;
+    let radius = REGISTRATION_MARK_RADIUS;
 
-    // Add ballot/instructions title
-    add_text(&current_layer, &title);
-    add_text(&current_layer, &instructions_title);
-
-    // Start instructions text section
-    current_layer.begin_text_section();
-        current_layer.set_font(&font_text, instructions_text.size as f64);
-        current_layer.set_text_cursor(instructions_text.startx, instructions_text.starty);
-        current_layer.set_line_height(6.0);
-        
-        // Write lines of instructions
-        instructions_text.text.lines()
-            .for_each(|line|{
-                current_layer.write_text(line, &font_text);
-                current_layer.add_line_break();
+    layer.set_line_dash_pattern(LineDashPattern::default());
+    layer.set_outline_color(Color::Greyscale(Greyscale::new(0.0, None)));
+    layer.set_outline_thickness(0.25);
+    vec![(inset, inset), (size.width - inset, inset), (inset, size.height - inset), (size.width - inset, size.height - inset)].into_iter()
+        .for_each(|(x, y)| {
+            layer.add_shape(Line {
+                points: utils::calculate_points_for_circle(radius, x, y),
+                is_closed: true,
+                has_fill: false,
+                has_stroke: true,
+                is_clipping_path: false,
             });
-    current_layer.end_text_section();
-    
-    // Start ballot serial/vote code section
-    current_layer.begin_text_section();
-        current_layer.set_font(&font_title, ballot_serial.size as f64);
-        current_layer.set_text_cursor(ballot_serial.startx, ballot_serial.starty);
-        current_layer.set_line_height(10.0);
-        
-        // Write Ballot Serial
-        current_layer.write_text(ballot_serial.text, &font_title);
-        current_layer.add_line_break();
-
-        // Write Ballot vote code text
-        current_layer.write_text(vote_code.text, &font_title);
-    current_layer.end_text_section();
-    // End text section
+            layer.add_shape(line(x - radius * 1.5, y, x + radius * 1.5, y));
+            layer.add_shape(line(x, y - radius * 1.5, x, y + radius * 1.5));
+        });
+}
 
-    // Add choices
-    make_choice(ballot.choice1, &current_layer, &font_text, option1);
-    make_choice(ballot.choice2, &current_layer, &font_text, option2);
+/// Draw the ballot of one serial, laid out by the template, with its
+/// lower left corner at `origin`.
+fn draw_ballot(layers: &BallotLayers, origin: Origin, ballots: &Vec<Ballot>, texts: &BallotTexts, votecode_format: &VoteCodeFormat, voting_url: Option<&VotingUrl>, template: &BallotTemplate, fonts: &DocumentFonts) -> Result<()> {
+    let ballot: &Ballot = &ballots[0];
+    let (_, page_height) = template.page_size();
 
-    // Save document
-    doc.save(&mut file_writer).unwrap()
-}
+    // Template positions are measured from the top left corner inside the margins.
+    let left: Mm = origin.x + Mm(template.margins.left);
+    let top: Mm = origin.y + Mm(page_height - template.margins.top);
 
-fn make_choice(choice: BallotChoice, layer: &PdfLayerReference, font: &IndirectFontRef, option: &str){
-    let votecode: String = string_from_votecode(&choice.votecode);
-    let width = BALLOT_SIZE.width/2.0 - Mm(20.0);
-    let height: Mm = match choice.choice {
-        ChoiceValue::For => BALLOT_SIZE.height/2.0 + Mm(20.0),
-        ChoiceValue::Against => BALLOT_SIZE.height/2.0 - Mm(20.0),
+    // Write the text blocks
+    let title: String = match texts.questions.len() {
+        1 => texts.questions[0].to_string(),
+        _ => texts.several_questions_title.to_string()
     };
+    let serial: String = ballot.serial.to_string();
+    template.blocks.iter().zip(texts.blocks.iter())
+        .for_each(|(block, block_text)| {
+            let text = fill_placeholders(block_text, &[
+                ("question", title.as_str()),
+                ("serial", serial.as_str()),
+                ("instructions", texts.instructions.as_str())]);
+            let width = block.width.unwrap_or(template.content_width() - block.x);
+            let lines = fonts.fonts.wrap(&block.font, &text, block.size, width);
+            write_lines(&layers.base, &lines, block.size, left + Mm(block.x), top - Mm(block.y), fonts.reference(&block.font), block.line_height);
+        });
+
+    // Draw the QR code of the voting URL
+    if let (Some(voting_url), Some(layout)) = (voting_url, &template.qr_code) {
+        let code = voting_url.ballot_qr_code(&ballot.serial)?;
+        draw_qr_code(&layers.base, layout, &code, &voting_url.short_url(), template, left, top, fonts);
+    }
 
-    // Make dash
-    let mut dash_pattern = LineDashPattern::default();
-    dash_pattern.dash_1 = Some(3);
+    // Add choices
+    // Lines are stacked downwards from the first line, `spacing` apart
+    // unless more lines need to fit above the bottom of the choices. With
+    // several questions each one is headed by its own line.
+    let layout: &ChoicesLayout = &template.choices;
+    let has_headings: bool = texts.questions.len() > 1;
+    let num_lines: usize = ballots.iter()
+        .map(|question_ballot| question_ballot.choices.len() + if has_headings { 1 } else { 0 })
+        .sum();
+    let first_height: Mm = top - Mm(layout.top);
+    let lowest_height: Mm = origin.y + Mm(template.margins.bottom + layout.bottom);
+    let spacing: f64 = match num_lines {
+        0 | 1 => 0.0,
+        n => f64::min(layout.spacing, (first_height.0 - lowest_height.0) / (n - 1) as f64)
+    };
+    let mut line: usize = 0;
+    ballots.iter().zip(texts.questions.iter().zip(texts.options.iter()))
+        .for_each(|(question_ballot, (question, options))| {
+            if has_headings {
+                let height: Mm = Mm(first_height.0 - spacing * line as f64);
+                write_choice_text(&layers.base, template, &layout.heading, &[("question", question.as_str())], left, height, fonts);
+                line += 1;
+            }
+            question_ballot.choices.iter()
+                .for_each(|choice| {
+                    let height: Mm = Mm(first_height.0 - spacing * line as f64);
+                    make_choice(choice, votecode_format, template, left, height, layers, fonts, &options[choice.choice.0]);
+                    line += 1;
+                });
+        });
+    Ok(())
+}
 
-    // Make choice circle
-    layer.set_line_dash_pattern(dash_pattern);
-    layer.set_fill_color(Color::Greyscale(Greyscale::new(0.8, None)));
-    let circle = make_circle(Mm((votecode.len() * 2) as f64).into(), width.into(), height.into());
-    layer.add_shape(circle);
+/// Draw a QR code, dark modules filled black, and write the short URL
+/// below it. Runs of dark modules in a row are drawn as one rectangle.
+fn draw_qr_code(layer: &PdfLayerReference, layout: &QrCodeLayout, code: &QrCode, short_url: &str, template: &BallotTemplate, left: Mm, top: Mm, fonts: &DocumentFonts) {
+    let width = code.width();
+    let colors = code.to_colors();
+    let module: f64 = layout.size / (width + 2 * QR_QUIET_ZONE) as f64;
+    let code_left: Mm = left + Mm(layout.x + module * QR_QUIET_ZONE as f64);
+    let code_top: Mm = top - Mm(layout.y + module * QR_QUIET_ZONE as f64);
 
-    // Make choice value
-    dash_pattern.dash_1 = None;
+    layer.set_line_dash_pattern(LineDashPattern::default());
     layer.set_fill_color(Color::Greyscale(Greyscale::new(0.0, None)));
-    let choice: Text = Text {
-        text: option.to_owned(), 
-        size: 15, 
-        startx: width + Mm(40.0), 
-        starty: height,
-        font: font,
-    };
-    add_text(layer, &choice);
-
-    // Make vote code
-    let votetext: Text = Text {
-        text: votecode, 
-        startx: width - Mm(22.0),
-        size: 9, 
-        ..choice
-    };
-    add_text(layer, &votetext);
-}
\ No newline at end of file
+    (0..width)
+        .for_each(|row| {
+            let is_dark = |column: usize| colors[row * width + column] == qrcode::Color::Dark;
+            let mut column = 0;
+            while column < width {
+                if !is_dark(column) {
+                    column += 1;
+                    continue;
+                }
+                let start = column;
+                while column < width && is_dark(column) { column += 1; }
+                let run: f64 = module * (column - start) as f64;
+                let centerx: Mm = code_left + Mm(module * start as f64 + run / 2.0);
+                let centery: Mm = code_top - Mm(module * row as f64 + module / 2.0);
+                layer.add_shape(Line {
+                    points: utils::calculate_points_for_rect(Mm(run).into(), Mm(module).into(), centerx.into(), centery.into()),
+                    is_closed: true,
+                    has_fill: true,
+                    has_stroke: false,
+                    is_clipping_path: false,
+                });
+            }
+        });
+
+    let width = template.content_width() - layout.x;
+    let lines = fonts.fonts.wrap(&layout.font, short_url, layout.url_size, width);
+    let baseline: Mm = top - Mm(layout.y + layout.size + layout.url_size * MM_PER_POINT);
+    write_lines(layer, &lines, layout.url_size, left + Mm(layout.x), baseline, fonts.reference(&layout.font), None);
+}
+
+/// Write lines of text, the first one at (`x`, `y`).
+fn write_lines(layer: &PdfLayerReference, lines: &Vec<String>, size: f64, x: Mm, y: Mm, font: &IndirectFontRef, line_height: Option<f64>) {
+    layer.begin_text_section();
+        layer.set_font(font, size);
+        layer.set_text_cursor(x, y);
+        layer.set_line_height(line_height.unwrap_or(size));
+
+        lines.iter()
+            .for_each(|line| {
+                layer.write_text(line.as_str(), font);
+                layer.add_line_break();
+            });
+    layer.end_text_section();
+}
+
+/// Write a text of a choice line, wrapped to fit, the first line at the
+/// height of the choice line.
+fn write_choice_text(layer: &PdfLayerReference, template: &BallotTemplate, choice_text: &ChoiceText, values: &[(&str, &str)], left: Mm, height: Mm, fonts: &DocumentFonts) {
+    let text = fill_placeholders(&choice_text.text, values);
+    let width = choice_text.width.unwrap_or(template.content_width() - choice_text.x);
+    let lines = fonts.fonts.wrap(&choice_text.font, &text, choice_text.size, width);
+    write_lines(layer, &lines, choice_text.size, left + Mm(choice_text.x), height, fonts.reference(&choice_text.font), None);
+}
+
+/// Print one decoy indicator sticker per decoy serial to
+/// `<directory>/decoy_stickers.pdf`, with the serial as in the list of
+/// decoy serials. The stickers are kept apart from the ballots so the
+/// print shop cannot tell decoys apart. Returns the path written.
+pub fn print_decoy_stickers(serials: &Vec<BallotSerial>, num_ballots: usize, directory: &str) -> Result<String> {
+    // Create decoys dir
+    make_dir(directory)?;
+
+    // Create new document
+    let file = Path::new(directory).join(DECOY_STICKERS_FILE);
+    let mut file_writer = BufWriter::new(File::create(&file)?);
+
+    // Start new PDF
+    let (doc, page1, layer1) = PdfDocument::new("Decoy stickers".to_string(), DECOY_STICKER_SIZE.width, DECOY_STICKER_SIZE.height, "layer1".to_string());
+
+    // Add fonts for title and text
+    let font_title = doc.add_builtin_font(BuiltinFont::CourierBold)?;
+    let font_text = doc.add_builtin_font(BuiltinFont::Courier)?;
+
+    // One sticker per page
+    serials.iter().enumerate()
+        .for_each(|(n, serial)| {
+            let current_layer = match n {
+                0 => doc.get_page(page1).get_layer(layer1),
+                _ => {
+                    let (page, layer) = doc.add_page(DECOY_STICKER_SIZE.width, DECOY_STICKER_SIZE.height, "layer1".to_string());
+                    doc.get_page(page).get_layer(layer)
+                }
+            };
+
+            // Start decoy text section
+            current_layer.begin_text_section();
+                current_layer.set_font(&font_text, 10.0);
+                current_layer.set_text_cursor(Mm(8.0), DECOY_STICKER_SIZE.height - Mm(10.0));
+                current_layer.set_line_height(5.0);
+
+                // Write lines of decoy text
+                DECOY_TEXT.lines()
+                    .for_each(|line| {
+                        current_layer.write_text(line, &font_text);
+                        current_layer.add_line_break();
+                    });
+            current_layer.end_text_section();
+
+            // Write Ballot Serial
+            let ballot_serial: Text = Text {
+                text: BALLOT_SERIAL_TEXT.to_string() + &string_from_ballotserial(serial, num_ballots),
+                size: 12.0,
+                startx: Mm(8.0),
+                starty: Mm(8.0),
+                font: &font_title,
+            };
+            add_text(&current_layer, &ballot_serial);
+        });
+
+    // Save document
+    doc.save(&mut file_writer)?;
+    Ok(file.display().to_string())
+}
+
+fn make_choice(choice: &BallotChoice, votecode_format: &VoteCodeFormat, template: &BallotTemplate, left: Mm, height: Mm, layers: &BallotLayers, fonts: &DocumentFonts, option: &str){
+    let layout: &ChoicesLayout = &template.choices;
+    let votecode: String = string_from_votecode(&choice.votecode, votecode_format);
+
+    // Make choice scratch-off area
+    let width: Mm = Mm(layout.scratch_off.width.unwrap_or((votecode.len() * 2) as f64));
+    let mut area = make_scratch_off(width.into(), Mm(layout.scratch_off.height).into(), (left + Mm(layout.scratch_off.x)).into(), height.into());
+    match &layers.mask {
+        // A grey dashed area under the vote code
+        None => {
+            let mut dash_pattern = LineDashPattern::default();
+            dash_pattern.dash_1 = Some(3);
+            layers.base.set_line_dash_pattern(dash_pattern);
+            layers.base.set_fill_color(Color::Greyscale(Greyscale::new(0.8, None)));
+            layers.base.add_shape(area);
+            layers.base.set_fill_color(Color::Greyscale(Greyscale::new(0.0, None)));
+        },
+        // The exact area to coat, solid, on the mask
+        Some(mask) => {
+            area.has_stroke = false;
+            mask.set_fill_color(Color::Greyscale(Greyscale::new(0.0, None)));
+            mask.add_shape(area);
+        }
+    }
+
+    // Make choice value and vote code
+    let values = [("option", option), ("votecode", votecode.as_str())];
+    write_choice_text(&layers.base, template, &layout.option, &values, left, height, fonts);
+    write_choice_text(&layers.base, template, &layout.votecode, &values, left, height, fonts);
+}
@@ -0,0 +1,202 @@
+//! # Font Subsetting
+//!
+//! A font file is embedded in every ballot PDF, and a font covering CJK
+//! holds tens of thousands of glyphs. Before a TrueType font is embedded,
+//! the outlines of the glyphs the poll never prints are dropped from its
+//! `glyf` table. Glyph IDs, the character map and the metrics are kept as
+//! they are, so the font still maps each character to the same glyph and
+//! measures text the same way.
+//!
+//! A glyph is kept if a character of the poll maps to it, if a kept
+//! composite glyph is built from it, or if it is glyph 0 (`.notdef`).
+//! Fonts with CFF outlines (`OTTO`) and font collections are embedded
+//! whole.
+
+use std::collections::BTreeSet;
+use crate::Result;
+
+/// `sfntVersion` of a font with TrueType outlines.
+const TRUETYPE_VERSIONS: [&[u8; 4]; 2] = [b"\x00\x01\x00\x00", b"true"];
+/// `head.checkSumAdjustment` is chosen so the font sums to this.
+const CHECKSUM_MAGIC: u32 = 0xB1B0_AFBA;
+const HEAD_CHECKSUM_ADJUSTMENT: usize = 8;
+const HEAD_INDEX_TO_LOC_FORMAT: usize = 50;
+
+// Flags of a composite glyph component.
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+
+struct TableRecord {
+    tag: [u8; 4],
+    offset: usize,
+    length: usize
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| From::from("Font file is truncated."))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| From::from("Font file is truncated."))
+}
+
+/// Length padded to a 32-bit boundary, as tables and glyphs are aligned.
+fn padded(length: usize) -> usize {
+    (length + 3) & !3
+}
+
+/// OpenType table checksum: the sum of its big-endian 32-bit words.
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4)
+        .map(|chunk| {
+            let mut word = [0u8; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u32::from_be_bytes(word)
+        })
+        .fold(0u32, |sum, word| sum.wrapping_add(word))
+}
+
+fn table_records(data: &[u8]) -> Result<Vec<TableRecord>> {
+    let num_tables = read_u16(data, 4)? as usize;
+    (0..num_tables)
+        .map(|n| -> Result<TableRecord> {
+            let record = 12 + 16 * n;
+            let mut tag = [0u8; 4];
+            tag.copy_from_slice(data.get(record..record + 4).ok_or("Font file is truncated.")?);
+            let offset = read_u32(data, record + 8)? as usize;
+            let length = read_u32(data, record + 12)? as usize;
+            if data.len() < offset + length {
+                return Err(From::from(format!("Font table {} is truncated.", String::from_utf8_lossy(&tag))));
+            }
+            Ok(TableRecord { tag: tag, offset: offset, length: length })
+        })
+        .collect()
+}
+
+fn table<'a>(data: &'a [u8], records: &[TableRecord], tag: &[u8; 4]) -> Result<&'a [u8]> {
+    records.iter()
+        .find(|record| &record.tag == tag)
+        .map(|record| &data[record.offset..record.offset + record.length])
+        .ok_or_else(|| From::from(format!("Font has no {} table.", String::from_utf8_lossy(tag))))
+}
+
+/// Glyphs a composite glyph is built from; none for a simple glyph.
+fn components(glyph: &[u8]) -> Result<Vec<u16>> {
+    let mut components = Vec::new();
+    if glyph.is_empty() || (read_u16(glyph, 0)? as i16) >= 0 {
+        return Ok(components);
+    }
+    let mut offset = 10;
+    loop {
+        let flags = read_u16(glyph, offset)?;
+        components.push(read_u16(glyph, offset + 2)?);
+        offset += 4;
+        offset += if flags & ARG_1_AND_2_ARE_WORDS != 0 { 4 } else { 2 };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            offset += 2;
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            offset += 4;
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            offset += 8;
+        }
+        if flags & MORE_COMPONENTS == 0 {
+            return Ok(components);
+        }
+    }
+}
+
+/// Subset a font file to the glyphs of the given characters. Returns the
+/// font file unchanged when it does not have TrueType outlines.
+pub fn subset_font(data: &[u8], characters: &BTreeSet<char>) -> Result<Vec<u8>> {
+    if data.len() < 12 || !TRUETYPE_VERSIONS.iter().any(|version| &data[..4] == *version) {
+        return Ok(data.to_vec());
+    }
+    let face = ttf_parser::Face::from_slice(data, 0)
+        .map_err(|err| format!("Font file cannot be read: {}.", err))?;
+    let records = table_records(data)?;
+    let head = table(data, &records, b"head")?;
+    let loca = table(data, &records, b"loca")?;
+    let glyf = table(data, &records, b"glyf")?;
+    let num_glyphs = face.number_of_glyphs() as usize;
+
+    // Where each glyph lies in the glyf table.
+    let long_offsets = read_u16(head, HEAD_INDEX_TO_LOC_FORMAT)? != 0;
+    let offsets = (0..=num_glyphs)
+        .map(|glyph| match long_offsets {
+            true => read_u32(loca, 4 * glyph).map(|offset| offset as usize),
+            false => read_u16(loca, 2 * glyph).map(|offset| 2 * offset as usize)
+        })
+        .collect::<Result<Vec<usize>>>()?;
+    let glyph_data = |glyph: usize| -> Result<&[u8]> {
+        glyf.get(offsets[glyph]..offsets[glyph + 1])
+            .ok_or_else(|| From::from(format!("Glyph {} lies outside the glyf table.", glyph)))
+    };
+
+    // The glyphs of the characters, and the glyphs they are built from.
+    let mut kept: BTreeSet<usize> = characters.iter()
+        .filter_map(|&c| face.glyph_index(c))
+        .map(|glyph| glyph.0 as usize)
+        .collect();
+    kept.insert(0);
+    let mut pending: Vec<usize> = kept.iter().cloned().collect();
+    while let Some(glyph) = pending.pop() {
+        for component in components(glyph_data(glyph)?)? {
+            let component = component as usize;
+            if component < num_glyphs && kept.insert(component) {
+                pending.push(component);
+            }
+        }
+    }
+
+    // The glyf table of the kept glyphs, with long offsets.
+    let mut new_glyf: Vec<u8> = Vec::new();
+    let mut new_loca: Vec<u8> = Vec::with_capacity(4 * (num_glyphs + 1));
+    for glyph in 0..num_glyphs {
+        new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+        if kept.contains(&glyph) {
+            new_glyf.extend_from_slice(glyph_data(glyph)?);
+            new_glyf.resize(padded(new_glyf.len()), 0);
+        }
+    }
+    new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+    let mut new_head = head.to_vec();
+    new_head[HEAD_CHECKSUM_ADJUSTMENT..HEAD_CHECKSUM_ADJUSTMENT + 4].copy_from_slice(&[0; 4]);
+    new_head[HEAD_INDEX_TO_LOC_FORMAT..HEAD_INDEX_TO_LOC_FORMAT + 2].copy_from_slice(&1u16.to_be_bytes());
+
+    // Write the font: the same tables, in the same order.
+    let tables: Vec<(&TableRecord, &[u8])> = records.iter()
+        .map(|record| (record, match &record.tag {
+            b"head" => new_head.as_slice(),
+            b"loca" => new_loca.as_slice(),
+            b"glyf" => new_glyf.as_slice(),
+            _ => &data[record.offset..record.offset + record.length]
+        }))
+        .collect();
+    let directory_size = 12 + 16 * records.len();
+    let mut font: Vec<u8> = data[..12].to_vec();
+    let mut body: Vec<u8> = Vec::new();
+    let mut head_offset = 0;
+    for (record, table) in tables.iter() {
+        let offset = directory_size + body.len();
+        if &record.tag == b"head" { head_offset = offset; }
+        font.extend_from_slice(&record.tag);
+        font.extend_from_slice(&checksum(table).to_be_bytes());
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        body.extend_from_slice(table);
+        body.resize(padded(body.len()), 0);
+    }
+    font.extend_from_slice(&body);
+    let adjustment = CHECKSUM_MAGIC.wrapping_sub(checksum(&font));
+    font[head_offset + HEAD_CHECKSUM_ADJUSTMENT..head_offset + HEAD_CHECKSUM_ADJUSTMENT + 4]
+        .copy_from_slice(&adjustment.to_be_bytes());
+    Ok(font)
+}
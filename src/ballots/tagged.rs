@@ -4,6 +4,7 @@
 //! planes. The decoy ballots have been appropriately marked so their votes
 //! will not be counted as part of the tally.
 
+use std::cmp::max;
 use std::collections::HashSet;
 use std::convert::From;
 use strum_macros::Display;
@@ -18,9 +19,9 @@ pub struct TaggedSerial {
 }
 
 #[derive(Debug, Display, Clone, Copy, Serialize, Deserialize)]
-pub enum TaggedChoiceValue { For, Against, Decoy }
+pub enum TaggedChoiceValue { Choice(usize), Decoy }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct TaggedBallotChoice {
     serial: TaggedSerial,
     votecode: VoteCode,
@@ -30,16 +31,12 @@ pub struct TaggedBallotChoice {
 #[allow(dead_code)]
 pub struct TaggedBallot {
     serial: TaggedSerial,
-    choice1: TaggedBallotChoice,
-    choice2: TaggedBallotChoice
+    choices: Vec<TaggedBallotChoice>
 }
 
 impl From<ChoiceValue> for TaggedChoiceValue {
     fn from(value: ChoiceValue) -> Self {
-        match value {
-            ChoiceValue::For => TaggedChoiceValue::For,
-            ChoiceValue::Against => TaggedChoiceValue::Against
-        }
+        TaggedChoiceValue::Choice(value.0)
     }
 }
 
@@ -47,15 +44,17 @@ type ListOfTaggedBallots = Vec<TaggedBallot>;
 
 pub fn string_from_taggedchoicevalue(choice: &TaggedChoiceValue) -> String {
     match choice {
-        TaggedChoiceValue::For => "For".to_owned(),
-        TaggedChoiceValue::Against => "Against".to_owned(),
+        TaggedChoiceValue::Choice(n) => format!("Option {}", n + 1),
         TaggedChoiceValue::Decoy => "Decoy".to_owned()
     }
 }
 
-pub fn string_from_taggedchoicevalue_padded(choice: &TaggedChoiceValue) -> String {
-    const TCV_PADDED_LENGTH: usize = 7;
-    format!("{:width$}", string_from_taggedchoicevalue(choice), width=TCV_PADDED_LENGTH).to_owned()
+/// Pad the tagged choice so every entry of a poll encrypts to the same length.
+pub fn string_from_taggedchoicevalue_padded(choice: &TaggedChoiceValue, num_options: usize) -> String {
+    let tcv_padded_length: usize = max(
+        string_from_taggedchoicevalue(&TaggedChoiceValue::Decoy).len(),
+        string_from_taggedchoicevalue(&TaggedChoiceValue::Choice(num_options - 1)).len());
+    format!("{:width$}", string_from_taggedchoicevalue(choice), width=tcv_padded_length).to_owned()
 }
 
 
@@ -80,41 +79,26 @@ pub fn tag_serials(serials: &Vec<BallotSerial>, decoys: &Vec<BallotSerial>) -> V
         }).collect::<Vec<TaggedSerial>>()
 }
 
-pub fn generate_tagged_ballots(serials: &Vec<TaggedSerial>, votecodes: &Vec<VoteCode>) -> ListOfTaggedBallots {
-    assert!((2 * serials.len()) <= votecodes.len(),
-        "Too many vote codes supplied.");
-    assert!((2 * serials.len()) >= votecodes.len(),
+pub fn generate_tagged_ballots(serials: &Vec<TaggedSerial>, votecodes: &Vec<VoteCode>, num_options: usize) -> ListOfTaggedBallots {
+    assert!((num_options * serials.len()) <= votecodes.len(),
         "Too many ballot serials supplied.");
-    let for_choices = serials.iter().zip(votecodes.iter().step_by(2))
-        .map(|(&serial, &votecode)| {
-            TaggedBallotChoice {
-                serial: serial,
-                votecode: votecode,
-                choice: match serial.is_decoy {
-                    true => TaggedChoiceValue::Decoy,
-                    false => TaggedChoiceValue::For
-                }
-            }
-        }).collect::<Vec<TaggedBallotChoice>>();
-    let against_choices = serials.iter().zip(votecodes.iter().step_by(2))
-        .map(|(&serial, &votecode)| {
-            TaggedBallotChoice {
-                serial: serial,
-                votecode: votecode,
-                choice: match serial.is_decoy {
-                    true => TaggedChoiceValue::Decoy,
-                    false => TaggedChoiceValue::Against
-                }
-            }
-        }).collect::<Vec<TaggedBallotChoice>>();
-    for_choices.iter().zip(against_choices.iter())
-        .map(|(&for_choice, &against_choice)| {
-            assert!(for_choice.serial == against_choice.serial,
-                "Cannot generate ballot with mismatched serials.");
+    assert!((num_options * serials.len()) >= votecodes.len(),
+        "Too many vote codes supplied.");
+    serials.iter().zip(votecodes.chunks(num_options))
+        .map(|(&serial, ballot_votecodes)| {
             TaggedBallot {
-                serial: for_choice.serial,
-                choice1: for_choice,
-                choice2: against_choice
+                serial: serial,
+                choices: ballot_votecodes.iter().enumerate()
+                    .map(|(n, votecode)| {
+                        TaggedBallotChoice {
+                            serial: serial,
+                            votecode: votecode.clone(),
+                            choice: match serial.is_decoy {
+                                true => TaggedChoiceValue::Decoy,
+                                false => TaggedChoiceValue::Choice(n)
+                            }
+                        }
+                    }).collect()
             }
         }).collect::<ListOfTaggedBallots>()
 }
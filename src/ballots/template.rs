@@ -0,0 +1,370 @@
+//! # Ballot Template
+//!
+//! A ballot template sets the paper size, the margins, the fonts (PDF
+//! built-in fonts or TrueType/OpenType files, see `fonts`), the text
+//! blocks and where the choices and their scratch-off areas go.
+//! Positions and lengths are in millimeters, measured from the top left
+//! corner of the area inside the margins; font sizes and line heights
+//! are in points. Text may hold placeholders:
+//!
+//! * `{question}`: the question, or `several_questions_title` when the
+//!   ballot asks several questions (the heading of each question's
+//!   choices gets its own question),
+//! * `{serial}`: the ballot serial,
+//! * `{instructions}`: the instructions of the template,
+//! * `{option}` and `{votecode}`: in the choice lines.
+//!
+//! A template may place a QR code of the voting URL (see `url`), printed
+//! when the poll has a voting URL.
+//!
+//! The texts of a template, its instructions, title and blocks, may be
+//! translated, keyed by language (see `locale`). A text not translated is
+//! printed as it is.
+//!
+//! Text wider than its block, or than the room left on the page, wraps
+//! onto the following lines.
+//!
+//! The default template is the A5 layout ballots have always had.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use printpdf::BuiltinFont;
+use serde::{Serialize, Deserialize};
+use crate::Result;
+use super::*;
+use super::fonts::font_hash;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PaperSize {
+    #[serde(rename = "a4")]
+    A4,
+    #[serde(rename = "a5")]
+    A5,
+    #[serde(rename = "a6")]
+    A6,
+    #[serde(rename = "letter")]
+    Letter,
+    #[serde(rename = "custom")]
+    Custom { width: f64, height: f64 }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Margins {
+    #[serde(default)]
+    pub top: f64,
+    #[serde(default)]
+    pub bottom: f64,
+    #[serde(default)]
+    pub left: f64,
+    #[serde(default)]
+    pub right: f64
+}
+
+/// A PDF built-in font, e.g. `Courier`, or a font file, e.g.
+/// `{file: NotoSans-Regular.ttf}` (relative to the template). The hash of
+/// a font file is recorded when the poll is created.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TemplateFont {
+    Builtin(String),
+    File {
+        file: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hash: Option<String>
+    }
+}
+
+/// A block of text, one or more lines, written once per ballot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextBlock {
+    pub text: String,
+    pub x: f64,
+    pub y: f64,
+    pub size: f64,
+    pub font: String,
+    /// Distance between lines, the font size if not given.
+    #[serde(default)]
+    pub line_height: Option<f64>,
+    /// Width the text wraps to, up to the right margin if not given.
+    #[serde(default)]
+    pub width: Option<f64>
+}
+
+/// A line of text written on each choice line, at the height of the line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChoiceText {
+    pub text: String,
+    pub x: f64,
+    pub size: f64,
+    pub font: String,
+    /// Width the text wraps to, up to the right margin if not given.
+    #[serde(default)]
+    pub width: Option<f64>
+}
+
+/// The scratch-off area hiding a vote code, centered on `x` and on the
+/// choice line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScratchOff {
+    pub x: f64,
+    /// Width of the area, 2 mm per vote code character if not given.
+    #[serde(default)]
+    pub width: Option<f64>,
+    pub height: f64
+}
+
+/// The QR code of a ballot's voting URL, `size` millimeters square with
+/// its top left corner at (`x`, `y`), and the short URL written below it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QrCodeLayout {
+    pub x: f64,
+    pub y: f64,
+    pub size: f64,
+    pub font: String,
+    /// Font size of the short URL.
+    pub url_size: f64
+}
+
+/// Choice lines are stacked downwards from `top`, `spacing` apart unless
+/// more lines need to fit above `bottom` (measured up from the bottom
+/// margin). With several questions, each question is headed by its own
+/// line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChoicesLayout {
+    pub top: f64,
+    pub bottom: f64,
+    pub spacing: f64,
+    pub heading: ChoiceText,
+    pub option: ChoiceText,
+    pub votecode: ChoiceText,
+    pub scratch_off: ScratchOff
+}
+
+/// The texts of a template in another language.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TemplateTranslation {
+    #[serde(default)]
+    pub instructions: Option<String>,
+    #[serde(default)]
+    pub several_questions_title: Option<String>,
+    /// Text of each block, in the order of the blocks.
+    #[serde(default)]
+    pub blocks: Option<Vec<String>>
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BallotTemplate {
+    pub paper: PaperSize,
+    #[serde(default)]
+    pub margins: Margins,
+    /// Font names used by the blocks.
+    pub fonts: BTreeMap<String, TemplateFont>,
+    #[serde(default)]
+    pub instructions: String,
+    pub several_questions_title: String,
+    pub blocks: Vec<TextBlock>,
+    pub choices: ChoicesLayout,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qr_code: Option<QrCodeLayout>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub translations: BTreeMap<String, TemplateTranslation>
+}
+
+
+impl PaperSize {
+    /// Width and height in millimeters.
+    pub fn dimensions(self: &Self) -> (f64, f64) {
+        match self {
+            PaperSize::A4 => (210.0, 297.0),
+            PaperSize::A5 => (148.0, 210.0),
+            PaperSize::A6 => (105.0, 148.0),
+            PaperSize::Letter => (215.9, 279.4),
+            PaperSize::Custom { width, height } => (*width, *height)
+        }
+    }
+}
+
+/// PDF built-in font of a name, e.g. `Courier` or `HelveticaBold`.
+pub fn builtin_font(name: &str) -> Option<BuiltinFont> {
+    match name {
+        "TimesRoman" => Some(BuiltinFont::TimesRoman),
+        "TimesBold" => Some(BuiltinFont::TimesBold),
+        "TimesItalic" => Some(BuiltinFont::TimesItalic),
+        "TimesBoldItalic" => Some(BuiltinFont::TimesBoldItalic),
+        "Helvetica" => Some(BuiltinFont::Helvetica),
+        "HelveticaBold" => Some(BuiltinFont::HelveticaBold),
+        "HelveticaOblique" => Some(BuiltinFont::HelveticaOblique),
+        "HelveticaBoldOblique" => Some(BuiltinFont::HelveticaBoldOblique),
+        "Courier" => Some(BuiltinFont::Courier),
+        "CourierOblique" => Some(BuiltinFont::CourierOblique),
+        "CourierBold" => Some(BuiltinFont::CourierBold),
+        "CourierBoldOblique" => Some(BuiltinFont::CourierBoldOblique),
+        _ => None
+    }
+}
+
+/// Replace the `{name}` placeholders of a text in a single pass, so
+/// replaced values are never themselves expanded. Unknown placeholders
+/// are kept as they are.
+///
+/// # Examples
+///
+/// ```
+/// use seventh_estate::ballots::fill_placeholders;
+///
+/// let text = fill_placeholders("ballot serial: {serial} {other}", &[("serial", "{serial}7")]);
+/// assert_eq!("ballot serial: {serial}7 {other}", text);
+/// ```
+pub fn fill_placeholders(text: &str, values: &[(&str, &str)]) -> String {
+    let mut filled = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        let placeholder = &rest[start..];
+        let value = placeholder.find('}')
+            .and_then(|end| values.iter()
+                .find(|(name, _)| *name == &placeholder[1..end])
+                .map(|(_, value)| (end, value)));
+        match value {
+            Some((end, value)) => {
+                filled.push_str(value);
+                rest = &placeholder[end + 1..];
+            },
+            None => {
+                filled.push('{');
+                rest = &placeholder[1..];
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+impl BallotTemplate {
+    /// Read a template, resolve its font files relative to it and record
+    /// (or check) their hashes.
+    pub fn from_file(path: &dyn AsRef<Path>) -> Result<Self> {
+        let mut template: BallotTemplate = serde_yaml::from_reader(File::open(path)?)?;
+        template.check()?;
+        let directory = path.as_ref().parent().unwrap_or(Path::new(""));
+        for (name, font) in template.fonts.iter_mut() {
+            if let TemplateFont::File { file, hash } = font {
+                let font_path = directory.join(file.as_str()).canonicalize()?;
+                let file_hash = font_hash(&std::fs::read(&font_path)?);
+                if hash.as_ref().map_or(false, |hash| *hash != file_hash) {
+                    return Err(From::from(format!("Ballot template font {} does not match its hash: {}.", name, file)));
+                }
+                *file = font_path.display().to_string();
+                *hash = Some(file_hash);
+            }
+        }
+        Ok(template)
+    }
+
+    /// Width in millimeters between the left and right margins.
+    pub fn content_width(self: &Self) -> f64 {
+        let (width, _) = self.page_size();
+        width - self.margins.left - self.margins.right
+    }
+
+    /// Page width and height in millimeters.
+    pub fn page_size(self: &Self) -> (f64, f64) {
+        self.paper.dimensions()
+    }
+
+    /// Check the page is larger than its margins, every font a block
+    /// uses is defined, translations give the text of every block and
+    /// every text can be printed without shaping.
+    pub fn check(self: &Self) -> Result<()> {
+        let (width, height) = self.page_size();
+        if width <= self.margins.left + self.margins.right || height <= self.margins.top + self.margins.bottom {
+            return Err(From::from("Ballot template margins leave no room on the page."));
+        }
+        let unknown_builtin = self.fonts.iter()
+            .find(|(_, font)| match font {
+                TemplateFont::Builtin(font) => builtin_font(font).is_none(),
+                TemplateFont::File { .. } => false
+            });
+        if let Some((name, font)) = unknown_builtin {
+            return Err(From::from(format!("Ballot template font {} is not a built-in font: {:?}.", name, font)));
+        }
+        let used_fonts = self.blocks.iter().map(|block| &block.font)
+            .chain(vec![&self.choices.heading.font, &self.choices.option.font, &self.choices.votecode.font])
+            .chain(self.qr_code.iter().map(|qr_code| &qr_code.font));
+        for font in used_fonts {
+            if !self.fonts.contains_key(font) {
+                return Err(From::from(format!("Ballot template font {} is not defined.", font)));
+            }
+        }
+        for (language, translation) in self.translations.iter() {
+            if translation.blocks.as_ref().map_or(false, |blocks| blocks.len() != self.blocks.len()) {
+                return Err(From::from(format!("Ballot template translation {} must give the text of each of the {} blocks.", language, self.blocks.len())));
+            }
+        }
+        let texts = vec![&self.instructions, &self.several_questions_title].into_iter()
+            .chain(self.blocks.iter().map(|block| &block.text))
+            .chain(self.translations.values().flat_map(|translation| {
+                translation.instructions.iter()
+                    .chain(translation.several_questions_title.iter())
+                    .chain(translation.blocks.iter().flatten())
+            }));
+        for text in texts {
+            check_unshaped_text(text)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for BallotTemplate {
+    fn default() -> Self {
+        let fonts = vec![("title", "CourierBold"), ("text", "Courier")].into_iter()
+            .map(|(name, font)| (name.to_owned(), TemplateFont::Builtin(font.to_owned())))
+            .collect();
+        let block = |text: &str, y: f64, size: f64, font: &str, line_height: Option<f64>| TextBlock {
+            text: text.to_owned(),
+            x: 10.0,
+            y: y,
+            size: size,
+            font: font.to_owned(),
+            line_height: line_height,
+            width: None
+        };
+        let choice_text = |text: &str, x: f64, size: f64, font: &str| ChoiceText {
+            text: text.to_owned(),
+            x: x,
+            size: size,
+            font: font.to_owned(),
+            width: None
+        };
+        BallotTemplate {
+            paper: PaperSize::A5,
+            margins: Margins::default(),
+            fonts: fonts,
+            instructions: "vote online by\n\nentering ballot serial\n\nnumber and the vote\n\ncode printed under\n\nthe scratch-off next\n\nto your choice:".to_owned(),
+            several_questions_title: "Ballot".to_owned(),
+            blocks: vec![
+                block("{question}", 20.0, 20.0, "title", None),
+                block("Instructions", 35.0, 15.0, "title", None),
+                block("{instructions}", 40.0, 10.0, "text", Some(6.0)),
+                block("ballot serial: {serial}\nvote code:           choice:", 70.0, 12.0, "title", Some(10.0))
+            ],
+            choices: ChoicesLayout {
+                top: 85.0,
+                bottom: 15.0,
+                spacing: 40.0,
+                heading: choice_text("{question}", 10.0, 12.0, "title"),
+                option: choice_text("{option}", 94.0, 15.0, "text"),
+                votecode: choice_text("{votecode}", 32.0, 9.0, "text"),
+                scratch_off: ScratchOff {
+                    x: 54.0,
+                    width: None,
+                    // 20 points
+                    height: 7.0556
+                }
+            },
+            qr_code: None,
+            translations: BTreeMap::new()
+        }
+    }
+}
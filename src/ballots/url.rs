@@ -0,0 +1,115 @@
+//! # Voting URL
+//!
+//! A ballot may carry a QR code of the voting page with its serial
+//! prefilled, so voters only type the vote code they scratched off:
+//!
+//! ```text
+//! <url>?serial=<serial>    or    <url>&serial=<serial>
+//! ```
+//!
+//! The URL holds the serial and nothing else; the vote codes are never
+//! printed outside their scratch-off areas.
+
+use serde::{Serialize, Deserialize};
+use qrcode::{QrCode, EcLevel};
+use crate::Result;
+use super::untagged::BallotSerial;
+
+/// Query parameter of the ballot serial.
+pub const SERIAL_PARAMETER: &str = "serial";
+
+
+/// The voting page, and the short form printed below the QR code.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VotingUrl {
+    pub url: String,
+    /// Printed below the QR code, the URL without its scheme if not given.
+    #[serde(default)]
+    pub short_url: Option<String>
+}
+
+impl VotingUrl {
+    /// Check the URL is an http(s) URL without a fragment or a serial, and
+    /// short enough for a QR code with any serial.
+    pub fn check(self: &Self) -> Result<()> {
+        if !self.url.starts_with("https://") && !self.url.starts_with("http://") {
+            return Err(From::from(format!("Voting URL must start with https:// or http://: {}", self.url)));
+        }
+        if self.url.contains('#') {
+            return Err(From::from(format!("Voting URL must not have a fragment: {}", self.url)));
+        }
+        if query_value(&self.url, SERIAL_PARAMETER).is_some() {
+            return Err(From::from(format!("Voting URL must not have a {} parameter: {}", SERIAL_PARAMETER, self.url)));
+        }
+        self.ballot_qr_code(&BallotSerial::max_value())
+            .map_err(|_| format!("Voting URL is too long for a QR code: {}", self.url))?;
+        Ok(())
+    }
+
+    /// URL of the voting page with a ballot serial.
+    pub fn ballot_url(self: &Self, serial: &BallotSerial) -> String {
+        let separator = match self.url.contains('?') {
+            true => '&',
+            false => '?'
+        };
+        format!("{}{}{}={}", self.url, separator, SERIAL_PARAMETER, serial)
+    }
+
+    /// QR code of the URL of a ballot, with medium error correction.
+    pub fn ballot_qr_code(self: &Self, serial: &BallotSerial) -> Result<QrCode> {
+        Ok(QrCode::with_error_correction_level(self.ballot_url(serial).as_bytes(), EcLevel::M)?)
+    }
+
+    /// The short URL printed for voters.
+    pub fn short_url(self: &Self) -> String {
+        match &self.short_url {
+            Some(short_url) => short_url.clone(),
+            None => self.url.splitn(2, "://").last().unwrap_or("").to_owned()
+        }
+    }
+
+    /// Ballot serial of a URL of this voting page.
+    pub fn decode(self: &Self, url: &str) -> Result<BallotSerial> {
+        if !url.starts_with(self.url.as_str()) {
+            return Err(From::from(format!("Not a URL of the voting page {}: {}", self.url, url)));
+        }
+        decode_ballot_url(url)
+    }
+}
+
+/// Value of a query parameter of a URL.
+fn query_value<'a>(url: &'a str, name: &str) -> Option<&'a str> {
+    let url = url.split('#').next().unwrap_or("");
+    let query = url.splitn(2, '?').nth(1)?;
+    query.split('&')
+        .filter_map(|parameter| {
+            let mut parts = parameter.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) if key == name => Some(value),
+                _ => None
+            }
+        })
+        .next()
+}
+
+/// Ballot serial of a ballot URL.
+///
+/// # Examples
+///
+/// ```
+/// use seventh_estate::ballots::{decode_ballot_url, VotingUrl};
+///
+/// let voting_url = VotingUrl { url: "https://vote.example.org/poll?lang=es".to_owned(), short_url: None };
+/// let url = voting_url.ballot_url(&1234);
+/// assert_eq!("https://vote.example.org/poll?lang=es&serial=1234", url);
+/// assert_eq!(1234, decode_ballot_url(&url).unwrap());
+/// assert!(decode_ballot_url("https://vote.example.org/poll?serial=12a").is_err());
+/// ```
+pub fn decode_ballot_url(url: &str) -> Result<BallotSerial> {
+    let serial = query_value(url, SERIAL_PARAMETER)
+        .ok_or_else(|| format!("No {} parameter in the URL: {}", SERIAL_PARAMETER, url))?;
+    if serial.is_empty() || !serial.chars().all(|c| c.is_ascii_digit()) {
+        return Err(From::from(format!("Ballot serial must be decimal digits: {}", serial)));
+    }
+    Ok(usize::from_str_radix(serial, 10)?)
+}
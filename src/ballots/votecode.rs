@@ -0,0 +1,319 @@
+//! # Vote Codes
+//!
+//! `votecode` generates, prints and parses the vote codes printed under
+//! the scratch-offs. Each poll picks its own vote code format: the number
+//! of groups, the number of symbols per group, the alphabet and the check
+//! symbol appended to every group.
+//!
+//! A vote code is stored as the values of its symbols, check symbols
+//! included, so `1234-5678` in the digit alphabet is `[1, 2, 3, 4, 5, 6, 7, 8]`.
+
+use std::fmt;
+use std::error::Error;
+use super::{Serialize, Deserialize};
+use crate::cryptography::csprng::*;
+use crate::cryptography::fast_dice_roller::*;
+
+pub type VoteCode = Vec<u8>;
+
+const VOTE_CODE_GROUP_SEPARATOR: &str = "-";
+const CROCKFORD32_SYMBOLS: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const DAMM10_TABLE: [[u8; 10]; 10] = [
+    [0, 3, 1, 7, 5, 9, 8, 6, 4, 2],
+    [7, 0, 9, 2, 1, 5, 4, 8, 6, 3],
+    [4, 2, 0, 6, 8, 7, 1, 3, 5, 9],
+    [1, 7, 5, 0, 9, 8, 3, 4, 2, 6],
+    [6, 1, 2, 3, 0, 4, 5, 9, 7, 8],
+    [3, 6, 7, 4, 2, 0, 9, 5, 8, 1],
+    [5, 8, 6, 9, 7, 2, 0, 1, 3, 4],
+    [8, 9, 4, 5, 3, 6, 2, 0, 1, 7],
+    [9, 4, 3, 8, 6, 1, 7, 2, 0, 5],
+    [2, 5, 8, 1, 4, 3, 6, 7, 9, 0]
+];
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VoteCodeAlphabet {
+    /// Decimal digits `0-9`.
+    Digits,
+    /// Crockford's base32 `0-9A-Z` without `I`, `L`, `O` and `U`.
+    Crockford32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VoteCodeCheck {
+    /// Complement of the sum of the group. Misses transpositions.
+    Parity,
+    /// Luhn mod N. Catches most adjacent transpositions.
+    Luhn,
+    /// Damm. Catches all single errors and adjacent transpositions.
+    Damm
+}
+
+/// Reason a typed vote code was rejected. Groups are counted from 1,
+/// as the voter reads them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VoteCodeError {
+    WrongLength { expected: usize, found: usize },
+    InvalidCharacter { group: usize, character: char },
+    CheckSymbolMismatch { group: usize }
+}
+
+impl fmt::Display for VoteCodeError {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::WrongLength { expected, found } =>
+                write!(f, "Vote code has {} characters, expected {}.", found, expected),
+            Self::InvalidCharacter { group, character } =>
+                write!(f, "Vote code group {} contains the invalid character '{}'.", group, character),
+            Self::CheckSymbolMismatch { group } =>
+                write!(f, "Vote code group {} is mistyped.", group)
+        }
+    }
+}
+
+impl Error for VoteCodeError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoteCodeFormat {
+    pub num_groups: usize,
+    pub group_size: usize,
+    pub alphabet: VoteCodeAlphabet,
+    pub check: VoteCodeCheck
+}
+
+impl Default for VoteCodeFormat {
+    /// Four groups of four digits, each followed by a parity digit.
+    fn default() -> Self {
+        VoteCodeFormat {
+            num_groups: 4,
+            group_size: 4,
+            alphabet: VoteCodeAlphabet::Digits,
+            check: VoteCodeCheck::Parity
+        }
+    }
+}
+
+impl VoteCodeFormat {
+    /// Number of distinct symbols in the alphabet.
+    pub fn radix(self: &Self) -> u8 {
+        match self.alphabet {
+            VoteCodeAlphabet::Digits => 10,
+            VoteCodeAlphabet::Crockford32 => 32
+        }
+    }
+
+    /// Number of symbols in a vote code, check symbols included.
+    pub fn length(self: &Self) -> usize {
+        self.num_groups * (self.group_size + 1)
+    }
+
+    /// Number of random symbols in a vote code.
+    pub fn payload_length(self: &Self) -> usize {
+        self.num_groups * self.group_size
+    }
+
+    /// Number of distinct vote codes, if it fits the vote code generator.
+    pub fn num_votecodes(self: &Self) -> Option<u128> {
+        let modulus = u128::checked_pow(self.radix() as u128, self.payload_length() as u32)?;
+        // The Fast Dice Roller doubles its range before comparing it.
+        if modulus <= (1 << 126) { Some(modulus) }
+        else { None }
+    }
+
+    /// Compute the check symbol of a group.
+    pub fn check_symbol(self: &Self, group: &[u8]) -> u8 {
+        let radix = self.radix() as usize;
+        match self.check {
+            VoteCodeCheck::Parity => {
+                let sum = group.iter().map(|&x| x as usize).sum::<usize>();
+                (((radix * self.group_size) - sum) % radix) as u8
+            },
+            VoteCodeCheck::Luhn => {
+                // Double every other symbol, starting from the rightmost.
+                let sum = group.iter().rev().enumerate()
+                    .map(|(n, &x)| {
+                        let addend = if n % 2 == 0 { 2 * x as usize } else { x as usize };
+                        (addend / radix) + (addend % radix)
+                    })
+                    .sum::<usize>();
+                ((radix - (sum % radix)) % radix) as u8
+            },
+            VoteCodeCheck::Damm => {
+                match self.alphabet {
+                    VoteCodeAlphabet::Digits => {
+                        group.iter().fold(0, |interim, &x| DAMM10_TABLE[interim as usize][x as usize])
+                    },
+                    VoteCodeAlphabet::Crockford32 => {
+                        // Quasigroup x * y = 2x + y over GF(32), reduced by x^5 + x^2 + 1.
+                        fn double(x: u8) -> u8 {
+                            let doubled = x << 1;
+                            if doubled & 0x20 != 0 { doubled ^ 0x25 } else { doubled }
+                        }
+                        double(group.iter().fold(0, |interim, &x| double(interim) ^ x))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Printable character of a symbol.
+    pub fn char_from_symbol(self: &Self, symbol: u8) -> char {
+        match self.alphabet {
+            VoteCodeAlphabet::Digits => (b'0' + symbol) as char,
+            VoteCodeAlphabet::Crockford32 => CROCKFORD32_SYMBOLS[symbol as usize] as char
+        }
+    }
+
+    /// Symbol of a typed character, if it belongs to the alphabet.
+    ///
+    /// Crockford's base32 is case insensitive and reads `I` and `L`
+    /// as `1` and `O` as `0`.
+    pub fn symbol_from_char(self: &Self, value: char) -> Option<u8> {
+        match self.alphabet {
+            VoteCodeAlphabet::Digits => {
+                value.to_digit(10).map(|digit| digit as u8)
+            },
+            VoteCodeAlphabet::Crockford32 => {
+                let normalized = match value.to_ascii_uppercase() {
+                    'I' | 'L' => '1',
+                    'O' => '0',
+                    other => other
+                };
+                CROCKFORD32_SYMBOLS.iter()
+                    .position(|&symbol| symbol as char == normalized)
+                    .map(|position| position as u8)
+            }
+        }
+    }
+}
+
+
+pub fn string_from_votecode(votecode: &VoteCode, format: &VoteCodeFormat) -> String {
+    votecode.chunks(format.group_size + 1)
+        .map(|group| {
+            group.iter()
+                .map(|&symbol| format.char_from_symbol(symbol))
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join(VOTE_CODE_GROUP_SEPARATOR)
+        .to_owned()
+}
+
+/// Parse a typed vote code without verifying its check symbols.
+/// Group separators and whitespace are ignored.
+pub fn votecode_from_string(value: &str, format: &VoteCodeFormat) -> Option<VoteCode> {
+    parse(value, format).ok()
+}
+
+/// Parse a typed vote code and verify the check symbol of each group.
+/// Group separators and whitespace are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use seventh_estate::ballots::*;
+///
+/// let format = VoteCodeFormat::default();
+/// assert!(validate("12340-56784-00000-99994", &format).is_ok());
+/// assert_eq!(Err(VoteCodeError::CheckSymbolMismatch { group: 2 }),
+///     validate("12340-57684-00000-99994", &format));
+/// ```
+pub fn validate(value: &str, format: &VoteCodeFormat) -> std::result::Result<VoteCode, VoteCodeError> {
+    let votecode = parse(value, format)?;
+    match votecode.chunks(format.group_size + 1)
+        .position(|group| group[format.group_size] != format.check_symbol(&group[..format.group_size])) {
+        Some(n) => Err(VoteCodeError::CheckSymbolMismatch { group: n + 1 }),
+        None => Ok(votecode)
+    }
+}
+
+fn parse(value: &str, format: &VoteCodeFormat) -> std::result::Result<VoteCode, VoteCodeError> {
+    let characters: Vec<char> = value.chars()
+        .filter(|c| !c.is_whitespace() && !VOTE_CODE_GROUP_SEPARATOR.contains(*c))
+        .collect();
+    if characters.len() != format.length() {
+        return Err(VoteCodeError::WrongLength { expected: format.length(), found: characters.len() });
+    }
+    characters.iter().enumerate()
+        .map(|(n, &c)| {
+            format.symbol_from_char(c)
+                .ok_or(VoteCodeError::InvalidCharacter { group: n / (format.group_size + 1) + 1, character: c })
+        })
+        .collect()
+}
+
+/// Format-independent encoding of a vote code: one Crockford base32
+/// character per symbol, no separators. Digit vote codes read the same
+/// as printed, without the separators.
+pub fn canonical_string_from_votecode(votecode: &VoteCode) -> String {
+    votecode.iter()
+        .map(|&symbol| CROCKFORD32_SYMBOLS[symbol as usize] as char)
+        .collect()
+}
+
+/// Parse the canonical encoding of a vote code.
+pub fn votecode_from_canonical_string(value: &str) -> Option<VoteCode> {
+    value.chars()
+        .map(|c| {
+            CROCKFORD32_SYMBOLS.iter()
+                .position(|&symbol| symbol as char == c)
+                .map(|position| position as u8)
+        })
+        .collect()
+}
+
+pub fn generate_votecodes(seed: CSPRNGSeed, count: usize, format: &VoteCodeFormat) -> Vec<VoteCode> {
+    fn try_generate(seed: CSPRNGSeed, count: usize, num_bytes: usize, modulus: u128) -> Option<Vec<u128>> {
+        let mut prng = CSPRNG::from_csprng_seed(seed);
+        let mut bytes = Vec::<u8>::new();
+        bytes.resize_with(num_bytes, || {0});
+        prng.fill_bytes(&mut bytes);
+        let mut fdr = FastDiceRoller::from_bytes(&bytes);
+        let npvotecodes: Vec<Option<u128>> = (0..count).into_iter()
+            .map(|_| { fdr.random(modulus) })
+            .collect();
+        match npvotecodes.iter().any(|s| s.is_none()) {
+            true => None,
+            false => Some(npvotecodes.iter().map(|s| s.unwrap()).collect())
+        }
+    }
+
+    let modulus: u128 = format.num_votecodes()
+        .expect("Vote code format has too many symbols.");
+    let radix: u128 = format.radix() as u128;
+
+    let mut num_bytes: usize = 1024;
+    let npvotecodes: Vec<Vec<u8>>;
+    loop {
+        let maybe_npvotecodes = try_generate(seed, count, num_bytes, modulus);
+        if let Some(npvcs) = maybe_npvotecodes {
+            // Write each number in the alphabet, most significant symbol first.
+            npvotecodes = npvcs.iter()
+                .map(|&npvc| {
+                    (0..format.payload_length()).into_iter()
+                        .rev()
+                        .map(|n| {
+                            let shift = u128::checked_pow(radix, n as u32).unwrap();
+                            ((npvc / shift) % radix) as u8
+                        })
+                        .collect::<Vec<u8>>()
+                }).collect();
+            break;
+        }
+        num_bytes = num_bytes + 1024;
+    }
+
+    // Append the check symbol to each group.
+    npvotecodes.iter()
+        .map(|npvc| {
+            npvc.chunks(format.group_size)
+                .flat_map(|group| {
+                    let mut checked_group = group.to_vec();
+                    checked_group.push(format.check_symbol(group));
+                    checked_group
+                })
+                .collect::<VoteCode>()
+        }).collect::<Vec<VoteCode>>()
+}
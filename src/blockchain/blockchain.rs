@@ -4,12 +4,15 @@
 //! Information posted is a merkle root
 
 // Imports for merkle tree handling
-use crate::blockchain::merkle::{CryptoSHA3256Hash, new_tree, CryptoHashData, store_tree};
+use crate::blockchain::merkle::{CryptoSHA3256Hash, MerkleRoot, new_tree, CryptoHashData, store_tree};
+use crate::blockchain::roster_proof::roster_tree_from_records;
 use crate::Result;
-use crate::voter_roster::VoterRoster;
+use crate::voter_roster::{bound_roster_records, roster_salts};
+use crate::cryptography::AEADKey;
+use crate::secrets::PollSecrets;
 use crate::poll_configuration::PollConfiguration;
 use crate::planes::Plane;
-use crate::debug;
+use crate::{debug, warn};
 use hex;
 use std::fs::File;
 use serde::{Serialize, Deserialize};
@@ -18,7 +21,8 @@ use serde::{Serialize, Deserialize};
 // Imports for blockchain audit
 use crate::untagged::*;
 use crate::untagged::{Ballot};
-use std::collections::HashMap;
+use crate::ballots::VoteCode;
+use std::collections::{HashMap, BTreeMap, BTreeSet};
 use crate::blockchain::etherscan::{Transaction, Response, SubmittedVote, ResponseBlockNumber};
 use chrono::NaiveDateTime;
 
@@ -41,33 +45,34 @@ pub fn retrieve_from_chain(value: Vec<u8>) -> u64 {
     0
 }
 
-// Map votecodes to choice value
+// Map votecodes to question and choice value
 // More efficient for vote count
-pub fn map_votes(ballots: &Vec<Ballot>) -> Result<HashMap<VoteCode, ChoiceValue>> {
+pub fn map_votes(ballots: &Vec<Vec<Ballot>>) -> Result<HashMap<VoteCode, (usize, ChoiceValue)>> {
     let mut choices = HashMap::new();
     
-    // Each votecode is maped to its corresponding Choice value
-    // p.e 1234-1234-1234-1234 => ChoiceValue::For
-    ballots.into_iter()
-            .for_each(|ballot| {
-                // println!("{} {:?} {:?}", ballot.serial, ballot.choice1.votecode, ballot.choice2.votecode);
-                choices.insert(ballot.choice1.votecode, ballot.choice1.choice);
-                choices.insert(ballot.choice2.votecode, ballot.choice2.choice);
-    });
+    // Each votecode is maped to its question and corresponding Choice value
+    // p.e 1234-1234-1234-1234 => (0, ChoiceValue(0))
+    ballots.iter().enumerate()
+        .for_each(|(q, question_ballots)| {
+            question_ballots.iter()
+                .for_each(|ballot| {
+                    ballot.choices.iter()
+                        .for_each(|choice| { choices.insert(choice.votecode.clone(), (q, choice.choice)); });
+                });
+        });
 
     Ok(choices)
 }
 
-pub fn map_serials(ballots: &Vec<Ballot>) -> Result<HashMap<VoteCode, BallotSerial>> {
+pub fn map_serials(ballots: &Vec<Vec<Ballot>>) -> Result<HashMap<VoteCode, BallotSerial>> {
     let mut serials = HashMap::new();
     
-    // Each votecode is maped to its corresponding Choice value
-    // p.e 1234-1234-1234-1234 => ChoiceValue::For
-    ballots.into_iter()
+    // Each votecode is maped to its ballot serial, shared by all questions
+    // p.e 1234-1234-1234-1234 => 12
+    ballots.iter().flatten()
             .for_each(|ballot| {
-                // println!("{} {:?} {:?}", ballot.serial, ballot.choice1.votecode, ballot.choice2.votecode);
-                serials.insert(ballot.choice1.votecode, ballot.serial);
-                serials.insert(ballot.choice2.votecode, ballot.serial);
+                ballot.choices.iter()
+                    .for_each(|choice| { serials.insert(choice.votecode.clone(), ballot.serial); });
     });
 
     Ok(serials)
@@ -97,54 +102,92 @@ pub fn transaction_to_votecode(transaction: Transaction) -> Option<SubmittedVote
     Some(vote)
 }
 
-// Count the votes found in the blockchain
-pub fn count_votes(mut choices: HashMap<VoteCode, ChoiceValue>, serials: HashMap<VoteCode, BallotSerial>, transactions: Vec<Transaction>, pollconf: PollConfiguration, decoys: Vec<usize>) -> Result<()> {
+/// Counts of the votes of each question, and the over-voted questions
+/// of ballots, as (serial, question), which are not counted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoteTally {
+    pub counts: Vec<Vec<u64>>,
+    pub overvotes: Vec<(BallotSerial, usize)>
+}
 
-    let mut vote_for: u64 = 0;
-    let mut vote_against: u64 = 0;
+// Tally the submitted votecodes
+// A votecode counts once however often it is submitted, and a ballot
+// counts at most one option per question: if codes of several options
+// of a question are submitted, none of them is counted.
+// Codes of excluded (audited or decoy) ballots are not counted.
+pub fn tally_votes(choices: &HashMap<VoteCode, (usize, ChoiceValue)>, serials: &HashMap<VoteCode, BallotSerial>, votecodes: &Vec<VoteCode>, num_options: &Vec<usize>, excluded: &Vec<BallotSerial>) -> VoteTally {
+    // The distinct options submitted for each question of each ballot.
+    let mut submitted: BTreeMap<(BallotSerial, usize), BTreeSet<usize>> = BTreeMap::new();
+    votecodes.iter()
+        .for_each(|votecode| {
+            if let (Some(serial), Some((q, choice))) = (serials.get(votecode), choices.get(votecode)) {
+                if !excluded.contains(serial) {
+                    submitted.entry((*serial, *q)).or_insert_with(BTreeSet::new).insert(choice.0);
+                }
+            }
+        });
 
-    let option1 = pollconf.option1;
-    let option2 = pollconf.option2;
-    
-    // Re-construct the audited ballots.
-    let audited_ballots: Vec<BallotSerial> = {
-        pollconf.audited_ballots.clone().unwrap().iter()
-            .map(|serial| usize::from_str_radix(serial, 10).unwrap())
-            .collect()
-    };
-    
-    // Run through all transactions
-    transactions.into_iter()
-        .for_each(|transaction| {     
-           // Get vote from transaction
-            if let Some(vote) = transaction_to_votecode(transaction) {
-                    let votecode = vote.to_votecode().unwrap();
-                    // println!("{:?}", votecode);
-
-                    // Get vote serial number
-                    if let Some(vote_serial) = serials.get(&votecode) {
-                        //println!("{:?}", vote_serial);
-
-                        // If vote is not audit and not decoy, count vote
-                        if !audited_ballots.contains(vote_serial) && !decoys.contains(vote_serial) {
-
-                            // Get ChoiceValue of vote
-                            if let Some(choice) = choices.remove(&votecode) {
-                                
-                                // println!("{:?}: {:?}", vote, choice);
-                                // If both votecodes are submitted, they cancel eachother
-                                // Increment the correct counter
-                                match choice {
-                                    ChoiceValue::For => vote_for += 1,
-                                    ChoiceValue::Against => vote_against += 1,
-                                }
-                            }
-                        }
-                    }
+    // One counter per option of each question.
+    let mut counts: Vec<Vec<u64>> = num_options.iter()
+        .map(|&n| vec![0; n])
+        .collect();
+    let mut overvotes: Vec<(BallotSerial, usize)> = Vec::new();
+    submitted.into_iter()
+        .for_each(|((serial, q), options)| {
+            match options.len() {
+                1 => { options.iter().for_each(|&option| counts[q][option] += 1); },
+                _ => overvotes.push((serial, q))
             }
         });
-    
-    println!("\"{}\": {},  \"{}\": {}", option1, vote_for, option2, vote_against);
+
+    VoteTally {
+        counts: counts,
+        overvotes: overvotes
+    }
+}
+
+// Count the votes found in the blockchain
+pub fn count_votes(choices: HashMap<VoteCode, (usize, ChoiceValue)>, serials: HashMap<VoteCode, BallotSerial>, transactions: Vec<Transaction>, pollconf: PollConfiguration, decoys: Vec<usize>) -> Result<()> {
+
+    // Re-construct the audited ballots, which are not counted with the decoys.
+    let mut excluded: Vec<BallotSerial> = pollconf.audited_ballots.clone()
+        .ok_or("Ballots must be audited before the votes are counted.")?
+        .iter()
+        .map(|serial| usize::from_str_radix(serial, 10))
+        .collect::<std::result::Result<Vec<BallotSerial>, _>>()?;
+    excluded.extend(decoys.iter());
+
+    // Get the votecodes from the transactions
+    let votecodes: Vec<VoteCode> = transactions.into_iter()
+        .filter_map(transaction_to_votecode)
+        .filter_map(|vote| match vote.to_votecode(&pollconf.votecode_format) {
+            Ok(votecode) => Some(votecode),
+            Err(err) => {
+                warn!("Skipping submitted vote: {}", err);
+                None
+            }
+        })
+        .collect();
+
+    let num_options: Vec<usize> = pollconf.questions.iter()
+        .map(|question| question.num_options())
+        .collect();
+    let tally = tally_votes(&choices, &serials, &votecodes, &num_options, &excluded);
+
+    pollconf.questions.iter().zip(tally.counts.iter())
+        .for_each(|(question, counts)| {
+            let results: Vec<String> = question.options.iter().zip(counts.iter())
+                .map(|(option, count)| format!("\"{}\": {}", option, count))
+                .collect();
+            println!("{}", question.question);
+            println!("{}", results.join(",  "));
+        });
+    if !tally.overvotes.is_empty() {
+        let overvotes: Vec<String> = tally.overvotes.iter()
+            .map(|(serial, q)| format!("ballot {} question {}", serial, q + 1))
+            .collect();
+        println!("Warning: Over-voted and not counted: {}", overvotes.join(", "));
+    }
     Ok(())
 }
 
@@ -188,7 +231,7 @@ pub fn get_data(addr: Address, api: String, start_date: &str, end_date: &str) ->
 
 // Audit blockchain for votecodes
 // Count votes
-pub fn audit_votes(ballots: Vec<Ballot>, pollconf: PollConfiguration, xxn_config: &str, decoys: Vec<usize>) -> Result<()> {
+pub fn audit_votes(ballots: Vec<Vec<Ballot>>, pollconf: PollConfiguration, xxn_config: &str, decoys: Vec<usize>) -> Result<()> {
     // Load configuration file
     let config = load_xxn(xxn_config)?;
     
@@ -200,7 +243,7 @@ pub fn audit_votes(ballots: Vec<Ballot>, pollconf: PollConfiguration, xxn_config
     let pub_addr: Address = key.address();
     
     // Map vote codes to choices values
-    let choices: HashMap<VoteCode, ChoiceValue> = map_votes(&ballots)?;
+    let choices: HashMap<VoteCode, (usize, ChoiceValue)> = map_votes(&ballots)?;
     let serials: HashMap<VoteCode, BallotSerial> = map_serials(&ballots)?;
 
     // Get data associated with poll addr -> votes submited via web interface
@@ -274,37 +317,41 @@ pub fn post(xxn: &str, data: CryptoSHA3256Hash) -> Result<()> {
     Ok(())   
 }
 
-pub fn commit (xxn: &str, pollconf: PollConfiguration, planes: Vec<Plane>) -> Result<()> {
-    // Re-construct roster
-    let roster: VoterRoster = {
-        let encoded_roster = pollconf.voter_roster.clone().unwrap();
-        let decoded_roster = base64::decode(&encoded_roster.0).unwrap();
-        let serialized_roster = std::str::from_utf8(&decoded_roster).unwrap();
-        serde_yaml::from_str(serialized_roster).unwrap()
+/// Roster tree of the bound roster, with salted leaves under voter privacy.
+/// The roster is streamed from its sidecar, so build the tree once and
+/// pass it on.
+pub fn roster_tree(pollconf: &PollConfiguration, poll_secrets: &PollSecrets, aead_pmk: &AEADKey) -> Result<MerkleRoot> {
+    let salts = match pollconf.voter_privacy {
+        true => Some(roster_salts(poll_secrets.roster_root)),
+        false => None
     };
+    roster_tree_from_records(bound_roster_records(pollconf, aead_pmk)?, salts)
+}
 
-    // Get voter info
-    let roster = roster.records.into_iter()
-        .map(|voter| {
-            let ser_v = serde_yaml::to_string(&voter).unwrap();
-            ser_v
-        }).collect();
-
+// Build the Merkle tree of the data committed to the blockchain
+pub fn commitment_tree(pollconf: &PollConfiguration, roster_tree: &MerkleRoot, planes: &Vec<Plane>) -> Result<MerkleRoot> {
+    // The roster is committed through the root of its own tree
+    let roster_root = hex::encode(roster_tree.root());
 
     // Re-construct the audited ballots.
     let audited_ballots = pollconf.audited_ballots.to_owned().unwrap();
     
     // Start vec of data for the tree
-    // Push roster
-    let mut data = CryptoHashData::new(roster);
+    // Push roster root
+    let mut data = CryptoHashData::new(vec![roster_root]);
+
+    // Push the roster hash, for rosters bound with one
+    if let Some(roster_hash) = &pollconf.voter_roster_hash {
+        data.push(roster_hash.clone());
+    }
 
     // Push audited ballots
     data.push_vec(audited_ballots);
    
     // Push planes
-    planes.into_iter().for_each(|plane|
+    planes.iter().for_each(|plane|
     {        
-        plane.rows.into_iter().for_each(|row|
+        plane.rows.iter().for_each(|row|
         {
             let ser_row = row.serializable(pollconf.num_ballots);
 
@@ -322,8 +369,17 @@ pub fn commit (xxn: &str, pollconf: PollConfiguration, planes: Vec<Plane>) -> Re
     let merkle_tree = new_tree(data).unwrap();
     debug!("Root: {}", hex::encode(merkle_tree.root()));
 
-    // Store full tree in file, to be later used for proof of inclusions
+    Ok(merkle_tree)
+}
+
+pub fn commit (xxn: &str, pollconf: PollConfiguration, poll_secrets: &PollSecrets, aead_pmk: &AEADKey, planes: Vec<Plane>) -> Result<()> {
+    // Create new tree with the committed data, reading the roster once
+    let roster_tree = roster_tree(&pollconf, poll_secrets, aead_pmk)?;
+    let merkle_tree = commitment_tree(&pollconf, &roster_tree, &planes)?;
+
+    // Store full trees in files, to be later used for proof of inclusions
     store_tree(&merkle_tree, String::from("merkle.yaml"))?;
+    store_tree(&roster_tree, String::from("roster_merkle.yaml"))?;
 
     // Post root to blockchain
     post(xxn, merkle_tree.root())
@@ -1,11 +1,7 @@
 use serde::Deserialize;
-use crate::untagged::VoteCode;
+use crate::ballots::{VoteCode, VoteCodeFormat, validate};
 use crate::Result;
 
-pub fn slice_as_vote(xs: &[u8]) -> &[u8; 20] {
-    slice_as_array!(xs, [u8; 20]).expect("bad votecode length")
-}
-
 #[derive(Debug, Deserialize)]
 pub struct SubmittedVote {
     // ballot: String,
@@ -13,19 +9,9 @@ pub struct SubmittedVote {
 }
 
 impl SubmittedVote {
-    pub fn to_votecode(&self) -> Result<VoteCode> {
-        // Remove '-'
-        let votecode: Vec<&str> = self.votecode.split('-').collect();
-        let votecode: String = votecode.join("");
-        let votecode: Vec<u8> = votecode.chars().map(|digit| {
-            digit.to_digit(10).unwrap() as u8
-        }).collect();
-
-
-        let votecode: &[u8] = &votecode[..];
-        let votecode = slice_as_vote(votecode);
-
-        Ok(*votecode)
+    pub fn to_votecode(&self, format: &VoteCodeFormat) -> Result<VoteCode> {
+        let votecode = validate(&self.votecode, format)?;
+        Ok(votecode)
     }
     /*
     pub fn to_serial(&self) -> std::result::Result<usize, std::num::ParseIntError> {
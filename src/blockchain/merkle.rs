@@ -108,6 +108,15 @@ fn get_hash (a: &mut CryptoSha3Algorithm, v: &String) -> [u8; 32] {
     b
 }
 
+/// Hash of a String of data, as `new_tree` hashes each datum before
+/// making it a leaf. Lets a tree be built from data that is streamed
+/// rather than held in memory.
+pub fn data_hash(v: &str) -> CryptoSHA3256Hash {
+    let mut a = CryptoSha3Algorithm::default();
+    a.write(v.as_bytes());
+    a.hash()
+}
+
 // Search in a tree for leaf index of a given hash
 fn get_leaf_index(t: &MerkleRoot, hash: CryptoSHA3256Hash) -> Result<usize>{
     let leafs = t.leafs();
@@ -121,7 +130,7 @@ fn get_leaf_index(t: &MerkleRoot, hash: CryptoSHA3256Hash) -> Result<usize>{
             return Ok(i)
         }
     }
-    panic!("Data not found in tree");
+    Err(From::from("Data not found in tree."))
 }
 
 // Create new tree from array of data
@@ -130,6 +139,16 @@ pub fn new_tree(hashed: CryptoHashData) -> Result<MerkleRoot> {
     Ok(MerkleTree::from_data(hashed.0)? as MerkleRoot)
 }
 
+/// Create new tree from the data hashes of its leaves, padded as
+/// `CryptoHashData::pad` pads the data. Builds the same tree as
+/// `new_tree` over the data.
+pub fn new_tree_from_hashes(mut hashes: Vec<CryptoSHA3256Hash>) -> Result<MerkleRoot> {
+    let size = hashes.len();
+    let next_size = if size == 1 { 2 } else { size.next_power_of_two() };
+    hashes.resize(next_size, data_hash("\0"));
+    Ok(MerkleTree::new(hashes)? as MerkleRoot)
+}
+
 // Get merkle path for a String of data
 // Returns Proof struct if data in tree
 pub fn get_path(t: MerkleRoot, data: String) -> Result<Proof<CryptoSHA3256Hash>> {
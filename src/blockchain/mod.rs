@@ -7,4 +7,5 @@ pub mod blockchain;
 pub use blockchain::*;
 
 pub mod merkle;
-pub mod etherscan;
\ No newline at end of file
+pub mod etherscan;
+pub mod roster_proof;
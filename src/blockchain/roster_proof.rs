@@ -0,0 +1,103 @@
+//! # Roster Inclusion Proofs
+//!
+//! The voter roster is committed as its own Merkle tree, whose root is a
+//! leaf of the tree posted to the ledger. A `RosterProof` lets a voter
+//! check their record against the roster root without the full roster:
+//! it holds the record, its salt with voter privacy, and the Merkle path
+//! from the record's leaf to the root.
+
+use serde::{Serialize, Deserialize};
+use crate::Result;
+use crate::blockchain::merkle::*;
+use crate::voter_roster::*;
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RosterProof {
+    pub position: usize,
+    pub restricted: RestrictedVoterInfo,
+    pub salt: Option<String>,
+    pub lemma: Vec<String>,
+    pub path: Vec<usize>
+}
+
+impl RosterProof {
+    /// Leaf of the roster tree the proof is about, if the salt is valid.
+    pub fn leaf(self: &Self) -> Option<String> {
+        match &self.salt {
+            Some(salt) => {
+                let salt = RosterSalt::from_hex(salt)?;
+                Some(roster_leaf(self.position, &self.restricted, Some(&salt)))
+            },
+            None => Some(roster_leaf(self.position, &self.restricted, None))
+        }
+    }
+
+    /// Roster root the proof leads to, in hex.
+    pub fn root(self: &Self) -> Option<&String> {
+        self.lemma.last()
+    }
+}
+
+
+/// Build the roster tree from a stream of roster records. With voter
+/// privacy each leaf is salted with the next salt. Each leaf is hashed as
+/// it is read, so only the leaf hashes are held in memory.
+pub fn roster_tree_from_records<R, S>(records: R, mut salts: Option<S>) -> Result<MerkleRoot>
+    where R: Iterator<Item = Result<VoterRosterRecord>>,
+          S: Iterator<Item = RosterSalt> {
+    let mut hashes: Vec<CryptoSHA3256Hash> = Vec::new();
+    for record in records {
+        let record = record?;
+        let restricted = RestrictedVoterInfo::from(record.voter_info);
+        let salt = match salts.as_mut() {
+            Some(salts) => Some(salts.next().ok_or("One salt is needed per roster record.")?),
+            None => None
+        };
+        hashes.push(data_hash(&roster_leaf(record.position, &restricted, salt.as_ref())));
+    }
+    new_tree_from_hashes(hashes)
+}
+
+/// Inclusion proof for the record at `position`, from that record alone:
+/// its restricted voter information and, with voter privacy, its salt.
+pub fn record_proof(tree: MerkleRoot, position: usize, restricted: RestrictedVoterInfo, salt: Option<RosterSalt>) -> Result<RosterProof> {
+    let proof = get_path(tree, roster_leaf(position, &restricted, salt.as_ref()))?;
+    Ok(RosterProof {
+        position: position,
+        restricted: restricted,
+        salt: salt.map(|salt| hex::encode(&salt.0)),
+        lemma: proof.lemma().iter().map(|hash| hex::encode(hash)).collect(),
+        path: proof.path().to_vec()
+    })
+}
+
+/// Check an inclusion proof against the published roster root (hex).
+///
+/// # Examples
+///
+/// ```
+/// use seventh_estate::blockchain::roster_proof::*;
+///
+/// let proof = RosterProof {
+///     position: 0,
+///     restricted: "McKie, Sioux".to_owned(),
+///     salt: None,
+///     lemma: vec!["00".to_owned()],
+///     path: vec![]
+/// };
+/// assert!(!verify_roster_proof(&proof, "00"));
+/// ```
+pub fn verify_roster_proof(proof: &RosterProof, root: &str) -> bool {
+    // A malformed proof is a failed proof.
+    let well_formed = proof.lemma.len() == proof.path.len() + 2
+        && proof.lemma.iter().all(|hash| hex::decode(hash).map_or(false, |bytes| bytes.len() == 32))
+        && proof.path.iter().all(|&side| side < 2);
+    if !well_formed { return false; }
+    let leaf = match proof.leaf() {
+        Some(leaf) => leaf,
+        None => return false
+    };
+    proof.root().map_or(false, |proof_root| proof_root.eq_ignore_ascii_case(root))
+        && validate(proof.lemma.clone(), proof.path.clone(), leaf).unwrap_or(false)
+}
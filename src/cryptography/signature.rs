@@ -11,8 +11,8 @@
 use signatory::ed25519;
 use signatory::encoding::{Encode, Decode, Base64};
 // use signatory::public_key::PublicKey;
-use signatory::signature::{Signer};
-use signatory_sodiumoxide::{Ed25519Signer};
+use signatory::signature::{Signer, Verifier, Signature};
+use signatory_sodiumoxide::{Ed25519Signer, Ed25519Verifier};
 
 use super::{Result, Base64String};
 
@@ -34,3 +34,14 @@ pub fn sign(key: &Base64String, data: Vec<u8>) -> Result<(Vec<u8>, Vec<u8>)> {
     Ok((data, signature))
 }
 
+/// Verify a signature of data with a public key.
+pub fn verify_signature(public_key: &Base64String, data: &[u8], signature: &[u8]) -> Result<()> {
+    let pk = ed25519::PublicKey::decode_from_str(&public_key.0, &Base64::default())
+        .map_err(|_| "Public key is not a Base64 Ed25519 public key.")?;
+    let signature = ed25519::Signature::from_bytes(signature)
+        .map_err(|_| "Signature is not an Ed25519 signature.")?;
+    let verifier = Ed25519Verifier::from(&pk);
+    verifier.verify(data, &signature)
+        .map_err(|_| "Signature does not verify.")?;
+    Ok(())
+}
@@ -3,7 +3,8 @@
 use log::*;
 #[macro_use] extern crate slice_as_array;
 
-const NUMBER_OF_PLANES: usize = 50;
+/// Column planes of a poll that does not set `num_planes`.
+pub const DEFAULT_NUMBER_OF_PLANES: usize = 50;
 
 type Exception = Box<dyn std::error::Error>;
 type Result<T> = std::result::Result<T, Exception>;
@@ -28,10 +29,17 @@ use summands::*;
 
 pub mod voter_selection;
 
+pub mod soundness;
+
+pub mod seeds;
+
 pub mod blockchain;
 
 pub mod ballots;
 use ballots::*;
 
+pub mod mailing;
+use mailing::*;
+
 pub mod subcommands;
 
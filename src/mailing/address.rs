@@ -0,0 +1,63 @@
+//! # Address Labels
+//!
+//! The mailing address of a sampled voter, one row of the address CSV,
+//! with the number of the mail piece it goes on.
+
+use crate::voter_roster::VoterInfo;
+use super::*;
+
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AddressLabel {
+    /// Mail piece the address goes on, and the ballot with it.
+    pub mail_piece: usize,
+    pub last_name: String,
+    pub first_name: String,
+    pub address1: String,
+    pub address2: String,
+    pub city: String,
+    pub state: String,
+    pub zip_code: String,
+    pub country: String,
+    /// Tracking and routing code of the Intelligent Mail barcode, when
+    /// the mailing has one.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub intelligent_mail: String
+}
+
+impl AddressLabel {
+    pub fn from_voter(mail_piece: usize, voter: VoterInfo) -> Self {
+        AddressLabel {
+            mail_piece: mail_piece,
+            last_name: voter.last_name,
+            first_name: voter.first_name,
+            address1: voter.street_address,
+            // The apartment goes first so it stays on the label if address2 is cut.
+            address2: vec![voter.apartment, voter.address2].into_iter()
+                .filter(|field| !field.is_empty())
+                .collect::<Vec<String>>()
+                .join(", "),
+            city: voter.city,
+            state: voter.state,
+            zip_code: voter.zip_code,
+            country: voter.country,
+            intelligent_mail: "".to_owned()
+        }
+    }
+
+    /// The printed address block: name, street lines, city line and
+    /// country, skipping empty lines.
+    pub fn lines(self: &Self) -> Vec<String> {
+        let city_line = format!("{} {} {}", self.city, self.state, self.zip_code);
+        vec![
+            format!("{} {}", self.first_name, self.last_name),
+            self.address1.clone(),
+            self.address2.clone(),
+            city_line,
+            self.country.clone()
+        ].into_iter()
+            .map(|line| line.split_whitespace().collect::<Vec<&str>>().join(" "))
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+}
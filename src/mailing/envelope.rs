@@ -0,0 +1,124 @@
+//! # Window Envelopes
+//!
+//! One address page per voter, with the address where the window of the
+//! envelope shows it once the page is folded and inserted. The window is
+//! given in millimeters from the top left corner of the page, font sizes
+//! in points. The built-in layout is:
+//!
+//! * `no10`: a US letter page folded in three into a #10 window envelope,
+//!   the window 7/8" from the left and 2-1/8" from the top of the page,
+//!   4-1/2" x 1-1/8".
+//!
+//! Other layouts are read from a YAML file of the same fields. Check the
+//! layout against the envelope stock before printing.
+
+use std::fs::File;
+use printpdf::PdfDocument;
+use super::*;
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvelopeWindow {
+    pub paper: PaperSize,
+    /// Top left corner of the window on the page.
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    /// Space between the edge of the window and the address.
+    #[serde(default = "default_padding")]
+    pub padding: f64,
+    /// A PDF built-in font, or a font file relative to the layout file.
+    #[serde(default = "default_font")]
+    pub font: TemplateFont,
+    #[serde(default = "default_font_size")]
+    pub font_size: f64
+}
+
+fn default_padding() -> f64 { 2.0 }
+
+fn default_font_size() -> f64 { 10.0 }
+
+
+impl EnvelopeWindow {
+    /// A built-in layout, `no10`.
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "no10" => Some(EnvelopeWindow {
+                paper: PaperSize::Letter,
+                x: 0.875 * INCH,
+                y: 2.125 * INCH,
+                width: 4.5 * INCH,
+                height: 1.125 * INCH,
+                padding: default_padding(),
+                font: default_font(),
+                font_size: default_font_size()
+            }),
+            _ => None
+        }
+    }
+
+    pub fn from_file(path: &dyn AsRef<Path>) -> Result<Self> {
+        let mut window: EnvelopeWindow = serde_yaml::from_reader(File::open(path)?)?;
+        resolve_font(&mut window.font, path)?;
+        window.check()?;
+        Ok(window)
+    }
+
+    /// A built-in layout by name, else a layout file.
+    pub fn from_name_or_file(name: &str) -> Result<Self> {
+        match EnvelopeWindow::builtin(name) {
+            Some(window) => Ok(window),
+            None => EnvelopeWindow::from_file(&Path::new(name))
+        }
+    }
+
+    /// Check the window is on the page and has room for text.
+    pub fn check(self: &Self) -> Result<()> {
+        let (width, height) = self.paper.dimensions();
+        if self.x < 0.0 || self.y < 0.0 || self.x + self.width > width || self.y + self.height > height {
+            return Err(From::from("The envelope window is not on the page."));
+        }
+        if self.width <= 2.0 * self.padding || self.height <= 2.0 * self.padding {
+            return Err(From::from("Envelope window padding leaves no room for the address."));
+        }
+        Ok(())
+    }
+}
+
+/// Print one page per address, in order, to
+/// `<directory>/envelope_addresses.pdf`. An address too long for the
+/// window is printed smaller, down to 6 points, then cut to the lines that
+/// fit. Returns the path written and the number of addresses cut.
+pub fn print_envelope_pages(addresses: &Vec<AddressLabel>, window: &EnvelopeWindow, directory: &str) -> Result<(String, usize)> {
+    window.check()?;
+    std::fs::create_dir_all(directory)?;
+    let font = address_font("envelope", &window.font, addresses)?;
+    let (width, height) = window.paper.dimensions();
+
+    let (doc, page1, layer1) = PdfDocument::new("Envelope addresses".to_string(), Mm(width), Mm(height), "layer1".to_string());
+    let font_reference = font.add_to(&doc)?;
+    let text_width: f64 = window.width - 2.0 * window.padding;
+    let text_height: f64 = window.height - 2.0 * window.padding;
+    let mut overflows: usize = 0;
+
+    addresses.iter().enumerate()
+        .for_each(|(n, address)| {
+            let layer = match n {
+                0 => doc.get_page(page1).get_layer(layer1),
+                _ => {
+                    let (page, layer) = doc.add_page(Mm(width), Mm(height), "layer1".to_string());
+                    doc.get_page(page).get_layer(layer)
+                }
+            };
+            let fitted = fit_address(address, &font, window.font_size, text_width, text_height);
+            if fitted.truncated { overflows += 1; }
+            let x = window.x + window.padding;
+            let y = window.y + window.padding;
+            write_address(&layer, &fitted, Mm(x), Mm(height - y) - Mm(fitted.font_size / POINTS_PER_MM), &font_reference);
+        });
+
+    let file = Path::new(directory).join("envelope_addresses.pdf");
+    doc.save(&mut std::io::BufWriter::new(File::create(&file)?))?;
+    Ok((file.display().to_string(), overflows))
+}
@@ -0,0 +1,118 @@
+//! # Intelligent Mail Barcode
+//!
+//! The USPS Intelligent Mail barcode (IMb) encodes a 20-digit tracking
+//! code and a routing code:
+//!
+//! ```text
+//! barcode id (2) || service type (3) || mailer id (6 or 9) || serial (9 or 6)
+//!     || delivery point ZIP (0, 5, 9 or 11)
+//! ```
+//!
+//! Each mail piece gets the next serial from `first_serial`. The field is
+//! written as digits, for the print shop's IMb font or encoder to draw.
+
+use std::fs::File;
+use super::*;
+
+const TRACKING_CODE_LENGTH: usize = 20;
+
+
+/// The identifiers of a mailing, given by the USPS.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntelligentMail {
+    pub barcode_id: String,
+    pub service_type: String,
+    /// 6 digits, or 9 digits starting with 9.
+    pub mailer_id: String,
+    /// Serial of the first mail piece.
+    #[serde(default)]
+    pub first_serial: u64
+}
+
+fn is_digits(value: &str) -> bool {
+    value.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Routing code of a ZIP code: its 5, 9 or 11 digits, or none if it has
+/// another number of digits or is not a ZIP code.
+///
+/// # Examples
+///
+/// ```
+/// use seventh_estate::mailing::routing_code;
+///
+/// assert_eq!("123456789", routing_code("12345-6789"));
+/// assert_eq!("12345", routing_code("12345"));
+/// assert_eq!("", routing_code("SW1A 1AA"));
+/// ```
+pub fn routing_code(zip_code: &str) -> String {
+    let digits: String = zip_code.chars().filter(|c| *c != '-' && !c.is_whitespace()).collect();
+    match is_digits(&digits) && [5, 9, 11].contains(&digits.len()) {
+        true => digits,
+        false => "".to_owned()
+    }
+}
+
+impl IntelligentMail {
+    pub fn from_file(path: &dyn AsRef<Path>) -> Result<Self> {
+        let intelligent_mail: IntelligentMail = serde_yaml::from_reader(File::open(path)?)?;
+        intelligent_mail.check(0)?;
+        Ok(intelligent_mail)
+    }
+
+    /// Digits of the serial, after the mailer ID.
+    fn serial_length(self: &Self) -> usize {
+        TRACKING_CODE_LENGTH - 5 - self.mailer_id.len()
+    }
+
+    /// Check the identifiers, and that `pieces` mail pieces get serials
+    /// that fit.
+    pub fn check(self: &Self, pieces: usize) -> Result<()> {
+        if self.barcode_id.len() != 2 || !is_digits(&self.barcode_id) || self.barcode_id.as_bytes()[1] > b'4' {
+            return Err(From::from(format!("IMb barcode ID must be two digits, the second 0 to 4: {}", self.barcode_id)));
+        }
+        if self.service_type.len() != 3 || !is_digits(&self.service_type) {
+            return Err(From::from(format!("IMb service type ID must be three digits: {}", self.service_type)));
+        }
+        let mailer_id_ok = match self.mailer_id.len() {
+            6 => !self.mailer_id.starts_with('9'),
+            9 => self.mailer_id.starts_with('9'),
+            _ => false
+        };
+        if !mailer_id_ok || !is_digits(&self.mailer_id) {
+            return Err(From::from(format!("IMb mailer ID must be 6 digits, or 9 digits starting with 9: {}", self.mailer_id)));
+        }
+        let last_serial = self.first_serial + pieces.saturating_sub(1) as u64;
+        if last_serial.to_string().len() > self.serial_length() {
+            return Err(From::from(format!("IMb serials from {} do not fit {} digits for {} mail pieces.", self.first_serial, self.serial_length(), pieces)));
+        }
+        Ok(())
+    }
+
+    /// Tracking code of the n-th mail piece.
+    pub fn tracking_code(self: &Self, piece: usize) -> String {
+        format!("{}{}{}{:0width$}", self.barcode_id, self.service_type, self.mailer_id,
+            self.first_serial + piece as u64, width = self.serial_length())
+    }
+
+    /// The barcode field of the n-th mail piece: its tracking code and the
+    /// routing code of its ZIP code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seventh_estate::mailing::IntelligentMail;
+    ///
+    /// let intelligent_mail = IntelligentMail {
+    ///     barcode_id: "00".to_owned(),
+    ///     service_type: "270".to_owned(),
+    ///     mailer_id: "123456".to_owned(),
+    ///     first_serial: 100
+    /// };
+    /// assert_eq!("00270123456000000102", intelligent_mail.field(2, "SW1A 1AA"));
+    /// assert_eq!("0027012345600000010098765", intelligent_mail.field(0, "98765"));
+    /// ```
+    pub fn field(self: &Self, piece: usize, zip_code: &str) -> String {
+        self.tracking_code(piece) + &routing_code(zip_code)
+    }
+}
@@ -0,0 +1,147 @@
+//! # Label Sheets
+//!
+//! Address labels printed on sheets of die-cut labels. A sheet format
+//! gives the paper, the grid of labels and where the first one sits;
+//! positions and lengths are in millimeters from the top left corner of
+//! the sheet, font sizes in points. The built-in formats are:
+//!
+//! * `30-up`: US letter, 3 by 10 labels of 1" x 2-5/8" (e.g. Avery 5160),
+//! * `14-up`: US letter, 2 by 7 labels of 1-1/3" x 4" (e.g. Avery 5162).
+//!
+//! Other formats are read from a YAML file of the same fields.
+
+use std::fs::File;
+use printpdf::PdfDocument;
+use super::*;
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelSheetFormat {
+    pub paper: PaperSize,
+    pub columns: usize,
+    pub rows: usize,
+    pub label_width: f64,
+    pub label_height: f64,
+    /// Top left corner of the first label.
+    pub top: f64,
+    pub left: f64,
+    /// Distance between the left edges of neighboring labels.
+    pub horizontal_pitch: f64,
+    /// Distance between the top edges of neighboring labels.
+    pub vertical_pitch: f64,
+    /// Space between the edge of a label and its text.
+    #[serde(default = "default_padding")]
+    pub padding: f64,
+    /// A PDF built-in font, or a font file relative to the format file.
+    #[serde(default = "default_font")]
+    pub font: TemplateFont,
+    #[serde(default = "default_font_size")]
+    pub font_size: f64
+}
+
+fn default_padding() -> f64 { 3.0 }
+
+fn default_font_size() -> f64 { 9.0 }
+
+
+impl LabelSheetFormat {
+    /// A built-in format, `30-up` or `14-up`.
+    pub fn builtin(name: &str) -> Option<Self> {
+        let format = |columns, rows, label_width, label_height, top, left, horizontal_pitch| LabelSheetFormat {
+            paper: PaperSize::Letter,
+            columns: columns,
+            rows: rows,
+            label_width: label_width * INCH,
+            label_height: label_height * INCH,
+            top: top * INCH,
+            left: left * INCH,
+            horizontal_pitch: horizontal_pitch * INCH,
+            vertical_pitch: label_height * INCH,
+            padding: default_padding(),
+            font: default_font(),
+            font_size: default_font_size()
+        };
+        match name {
+            "30-up" => Some(format(3, 10, 2.625, 1.0, 0.5, 0.1875, 2.75)),
+            "14-up" => Some(format(2, 7, 4.0, 4.0 / 3.0, 5.0 / 6.0, 0.15625, 4.1875)),
+            _ => None
+        }
+    }
+
+    pub fn from_file(path: &dyn AsRef<Path>) -> Result<Self> {
+        let mut format: LabelSheetFormat = serde_yaml::from_reader(File::open(path)?)?;
+        resolve_font(&mut format.font, path)?;
+        format.check()?;
+        Ok(format)
+    }
+
+    /// A built-in format by name, else a format file.
+    pub fn from_name_or_file(name: &str) -> Result<Self> {
+        match LabelSheetFormat::builtin(name) {
+            Some(format) => Ok(format),
+            None => LabelSheetFormat::from_file(&Path::new(name))
+        }
+    }
+
+    pub fn labels_per_sheet(self: &Self) -> usize {
+        self.columns * self.rows
+    }
+
+    /// Check the labels fit on the paper and have room for text.
+    pub fn check(self: &Self) -> Result<()> {
+        let (width, height) = self.paper.dimensions();
+        if self.columns == 0 || self.rows == 0 {
+            return Err(From::from("A label sheet needs at least one column and one row."));
+        }
+        let right = self.left + self.horizontal_pitch * (self.columns - 1) as f64 + self.label_width;
+        let bottom = self.top + self.vertical_pitch * (self.rows - 1) as f64 + self.label_height;
+        if self.left < 0.0 || self.top < 0.0 || right > width || bottom > height {
+            return Err(From::from("The labels do not fit on the label sheet."));
+        }
+        if self.label_width <= 2.0 * self.padding || self.label_height <= 2.0 * self.padding {
+            return Err(From::from("Label padding leaves no room for the address."));
+        }
+        Ok(())
+    }
+}
+
+/// Print the addresses on label sheets, in order, to
+/// `<directory>/address_labels.pdf`. An address too long for its label is
+/// printed smaller, down to 6 points, then cut to the lines that fit.
+/// Returns the path written and the number of addresses cut.
+pub fn print_label_sheets(addresses: &Vec<AddressLabel>, format: &LabelSheetFormat, directory: &str) -> Result<(String, usize)> {
+    format.check()?;
+    std::fs::create_dir_all(directory)?;
+    let font = address_font("label", &format.font, addresses)?;
+    let (width, height) = format.paper.dimensions();
+
+    let (doc, page1, layer1) = PdfDocument::new("Address labels".to_string(), Mm(width), Mm(height), "layer1".to_string());
+    let font_reference = font.add_to(&doc)?;
+    let text_width: f64 = format.label_width - 2.0 * format.padding;
+    let text_height: f64 = format.label_height - 2.0 * format.padding;
+    let mut overflows: usize = 0;
+
+    addresses.chunks(format.labels_per_sheet()).enumerate()
+        .for_each(|(n, sheet)| {
+            let layer = match n {
+                0 => doc.get_page(page1).get_layer(layer1),
+                _ => {
+                    let (page, layer) = doc.add_page(Mm(width), Mm(height), "layer1".to_string());
+                    doc.get_page(page).get_layer(layer)
+                }
+            };
+            sheet.iter().enumerate()
+                .for_each(|(slot, address)| {
+                    let (column, row) = (slot % format.columns, slot / format.columns);
+                    let x = format.left + format.horizontal_pitch * column as f64 + format.padding;
+                    let y = format.top + format.vertical_pitch * row as f64 + format.padding;
+                    let fitted = fit_address(address, &font, format.font_size, text_width, text_height);
+                    if fitted.truncated { overflows += 1; }
+                    write_address(&layer, &fitted, Mm(x), Mm(height - y) - Mm(fitted.font_size / POINTS_PER_MM), &font_reference);
+                });
+        });
+
+    let file = Path::new(directory).join("address_labels.pdf");
+    doc.save(&mut std::io::BufWriter::new(File::create(&file)?))?;
+    Ok((file.display().to_string(), overflows))
+}
@@ -0,0 +1,148 @@
+//! # Mailing Module
+//!
+//! `mailing` turns the address labels of the sampled voters into files
+//! ready to print: label sheets, address pages for window envelopes, and
+//! the USPS Intelligent Mail barcode field of each mail piece.
+//!
+//! The mailing files hold addresses only. They never carry a ballot
+//! serial or vote code. Addresses and ballots are paired by mail piece
+//! number through a secret shuffle (see `pieces`), and the files of the
+//! addressing vendor and of the ballot printer can be sealed into
+//! separate encrypted packages (see `package`).
+
+use serde::{Serialize, Deserialize};
+
+pub mod address;
+pub use address::*;
+
+pub mod intelligent_mail;
+pub use intelligent_mail::*;
+
+pub mod labels;
+pub use labels::*;
+
+pub mod envelope;
+pub use envelope::*;
+
+pub mod pieces;
+pub use pieces::*;
+
+pub mod package;
+pub use package::*;
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use printpdf::{PdfLayerReference, IndirectFontRef, Mm};
+use crate::Result;
+use crate::ballots::{PaperSize, TemplateFont, FontData, wrap_text, check_unshaped_text};
+
+/// Default directory of the mailing files.
+pub const MAILING_PATH: &str = "mailing/";
+const POINTS_PER_MM: f64 = 72.0 / 25.4;
+const INCH: f64 = 25.4;
+/// Line height as a multiple of the font size.
+const LINE_SPACING: f64 = 1.2;
+/// Smallest font size, in points, a long address is shrunk to.
+const MIN_FONT_SIZE: f64 = 6.0;
+
+
+/// Which mailing files step 3 writes, besides the address CSV.
+#[derive(Debug, Clone)]
+pub struct MailingOutput {
+    /// Directory of the label sheets and envelope pages.
+    pub directory: String,
+    pub label_sheet: Option<LabelSheetFormat>,
+    pub envelope_window: Option<EnvelopeWindow>,
+    /// Adds the Intelligent Mail barcode field to the addresses.
+    pub intelligent_mail: Option<IntelligentMail>,
+    /// Directory of the sealed vendor packages, if any.
+    pub packages: Option<String>
+}
+
+impl Default for MailingOutput {
+    fn default() -> Self {
+        MailingOutput {
+            directory: MAILING_PATH.to_owned(),
+            label_sheet: None,
+            envelope_window: None,
+            intelligent_mail: None,
+            packages: None
+        }
+    }
+}
+
+/// Resolve a font file relative to the YAML file that names it.
+fn resolve_font(font: &mut TemplateFont, path: &dyn AsRef<Path>) -> Result<()> {
+    if let TemplateFont::File { file, .. } = font {
+        let directory = path.as_ref().parent().unwrap_or(Path::new(""));
+        *file = directory.join(file.as_str()).canonicalize()?.display().to_string();
+    }
+    Ok(())
+}
+
+fn default_font() -> TemplateFont {
+    TemplateFont::Builtin("Helvetica".to_owned())
+}
+
+/// Load the font addresses are printed in, subset to the characters of
+/// the addresses as ballot fonts are. Every address must print without
+/// shaping.
+fn address_font(name: &str, font: &TemplateFont, addresses: &Vec<AddressLabel>) -> Result<FontData> {
+    let mut characters: BTreeSet<char> = BTreeSet::new();
+    for address in addresses.iter() {
+        for line in address.lines().iter() {
+            check_unshaped_text(line)?;
+            characters.extend(line.chars());
+        }
+    }
+    FontData::load(name, font)?.subset(&characters)
+}
+
+/// The lines of an address, each wrapped to `width` millimeters.
+fn wrapped_lines(address: &AddressLabel, font: &FontData, size: f64, width: f64) -> Vec<String> {
+    address.lines().iter()
+        .flat_map(|line| wrap_text(line, width, &|text| font.text_width(text, size)))
+        .collect()
+}
+
+/// An address laid out to fit a box.
+struct FittedAddress {
+    lines: Vec<String>,
+    font_size: f64,
+    /// Lines were left out, the address being too long even at the
+    /// smallest font size.
+    truncated: bool
+}
+
+/// Lay out an address in a box of `width` by `height` millimeters, at
+/// the largest font size up to `size` it fits at. An address that does
+/// not fit at `MIN_FONT_SIZE` is cut to the lines that do.
+fn fit_address(address: &AddressLabel, font: &FontData, size: f64, width: f64, height: f64) -> FittedAddress {
+    let max_lines = |size: f64| (height * POINTS_PER_MM / (size * LINE_SPACING)).floor() as usize;
+    let mut size = size;
+    loop {
+        let mut lines = wrapped_lines(address, font, size, width);
+        if lines.len() <= max_lines(size) {
+            return FittedAddress { lines: lines, font_size: size, truncated: false };
+        }
+        if size <= MIN_FONT_SIZE {
+            lines.truncate(max_lines(size));
+            return FittedAddress { lines: lines, font_size: size, truncated: true };
+        }
+        size = (size - 0.5).max(MIN_FONT_SIZE);
+    }
+}
+
+/// Write the lines of an address, the first one at (`x`, `y`).
+fn write_address(layer: &PdfLayerReference, address: &FittedAddress, x: Mm, y: Mm, font: &IndirectFontRef) {
+    layer.begin_text_section();
+        layer.set_font(font, address.font_size);
+        layer.set_text_cursor(x, y);
+        layer.set_line_height(address.font_size * LINE_SPACING);
+        address.lines.iter()
+            .for_each(|line| {
+                layer.write_text(line.as_str(), font);
+                layer.add_line_break();
+            });
+    layer.end_text_section();
+}
@@ -0,0 +1,140 @@
+//! # Vendor Packages
+//!
+//! Step 3 can seal the files of each vendor into a package of its own,
+//! encrypted with a password for that vendor:
+//!
+//! * `addressing_vendor`: the address CSV, label sheets and envelope
+//!   pages, each address with its mail piece number;
+//! * `ballot_printer`: the ballot PDFs, the ballot CSV of the vote codes
+//!   of each serial and the mail piece number of each serial.
+//!
+//! The key is derived from the password with the poll KDF and the
+//! package is sealed with AEAD, the vendor name and KDF salt bound as
+//! associated data, as the trustee shares are.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::str;
+use crate::cryptography::*;
+use super::*;
+
+pub const ADDRESSING_VENDOR: &str = "addressing_vendor";
+pub const BALLOT_PRINTER: &str = "ballot_printer";
+/// Extension of a sealed package file.
+pub const PACKAGE_EXTENSION: &str = "package";
+
+
+/// The files of one vendor, by file name, base64 encoded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VendorPackage {
+    pub vendor: String,
+    pub files: BTreeMap<String, String>
+}
+
+/// A vendor package encrypted with the vendor password.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealedPackage {
+    pub vendor: String,
+    pub package: AEADString
+}
+
+/// Name of a packaged file: a plain file name, without directories.
+fn package_file_name(path: &Path) -> Result<String> {
+    match path.file_name().and_then(|name| name.to_str()) {
+        Some(name) if !name.is_empty() && name != "." && name != ".." => Ok(name.to_owned()),
+        _ => Err(From::from(format!("Not a file name for a vendor package: {}", path.display())))
+    }
+}
+
+impl VendorPackage {
+    pub fn new(vendor: &str) -> Self {
+        VendorPackage {
+            vendor: vendor.to_owned(),
+            files: BTreeMap::new()
+        }
+    }
+
+    /// Add a file, under its file name.
+    pub fn add_file(self: &mut Self, path: &dyn AsRef<Path>) -> Result<()> {
+        let name = package_file_name(path.as_ref())?;
+        if self.files.contains_key(&name) {
+            return Err(From::from(format!("Vendor package {} already holds a file named {}.", self.vendor, name)));
+        }
+        let data = std::fs::read(path)?;
+        self.files.insert(name, base64::encode(&data));
+        Ok(())
+    }
+
+    /// Seal the package with the vendor password.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seventh_estate::mailing::VendorPackage;
+    ///
+    /// let package = VendorPackage::new("ballot_printer");
+    /// let sealed = package.seal("password").unwrap();
+    /// assert_eq!(package, sealed.open("password").unwrap());
+    /// assert!(sealed.open("another password").is_err());
+    /// ```
+    pub fn seal(self: &Self, password: &str) -> Result<SealedPackage> {
+        let (key, params) = kdf(password)?;
+        let aad = format!("{}${}", self.vendor, base64::encode(&params.salt));
+        let serialized_package = serde_yaml::to_string(self)?;
+        Ok(SealedPackage {
+            vendor: self.vendor.clone(),
+            package: AEADString::from_values(
+                aead_encrypt(&AEADKey::from(key),
+                             aad.as_bytes().to_vec(),
+                             serialized_package.as_bytes().to_vec())?)
+        })
+    }
+
+    /// Write the files to a directory. Returns the paths written.
+    pub fn write_files(self: &Self, directory: &str) -> Result<Vec<String>> {
+        std::fs::create_dir_all(directory)?;
+        self.files.iter()
+            .map(|(name, data)| -> Result<String> {
+                let file = Path::new(directory).join(package_file_name(Path::new(name))?);
+                std::fs::write(&file, base64::decode(data)?)?;
+                Ok(file.display().to_string())
+            })
+            .collect()
+    }
+}
+
+impl SealedPackage {
+    pub fn from_file(path: &dyn AsRef<Path>) -> Result<Self> {
+        Ok(serde_yaml::from_reader(File::open(path)?)?)
+    }
+
+    /// Write the package to `<directory>/<vendor>.package`. Returns the
+    /// path written.
+    pub fn to_file(self: &Self, directory: &str) -> Result<String> {
+        std::fs::create_dir_all(directory)?;
+        let file = Path::new(directory).join(&self.vendor).with_extension(PACKAGE_EXTENSION);
+        serde_yaml::to_writer(File::create(&file)?, self)?;
+        Ok(file.display().to_string())
+    }
+
+    /// Decrypt the package with the vendor password.
+    pub fn open(self: &Self, password: &str) -> Result<VendorPackage> {
+        let values: AEADValues = self.package.values()?;
+        let aad: &str = str::from_utf8(&values.aad)?;
+        let (vendor, salt64) = match aad.rfind('$') {
+            Some(split) => (&aad[..split], &aad[split + 1..]),
+            None => return Err(From::from("Vendor package is missing its KDF parameters."))
+        };
+        if vendor != self.vendor {
+            return Err(From::from(format!("Detected vendor package tampering. Package for {} is sealed for {}.", self.vendor, vendor)));
+        }
+        let params = KDFValues { salt: base64::decode(salt64)? };
+        let key = kdf_with_params(password, &params)?;
+        let serialized_package = aead_decrypt(&AEADKey::from(key), &values)?;
+        let package: VendorPackage = serde_yaml::from_slice(&serialized_package)?;
+        if package.vendor != self.vendor {
+            return Err(From::from(format!("Detected vendor package tampering. Package for {} holds the files of {}.", self.vendor, package.vendor)));
+        }
+        Ok(package)
+    }
+}
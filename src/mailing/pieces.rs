@@ -0,0 +1,68 @@
+//! # Mail Pieces
+//!
+//! Which ballot goes to which address is a secret of the poll. Step 3
+//! numbers the mail pieces and derives two independent shuffles from the
+//! mailing root of the poll secrets: one deals the sampled voters over
+//! the mail pieces, the other the ballot serials. The addressing vendor
+//! gets the mail piece number of each address, the ballot printer the
+//! mail piece number of each ballot, and the envelopes are stuffed by
+//! mail piece number.
+//!
+//! Neither list links a voter to a serial, and neither follows the
+//! sampled order or the serial order; only both lists together pair them.
+
+use rand::seq::SliceRandom;
+use crate::cryptography::{CSPRNG, CSPRNGExt, CSPRNGSeed};
+use crate::ballots::BallotSerial;
+
+/// File of the ballot printer listing the mail piece of each serial.
+pub const BALLOT_ORDER_FILE: &str = "ballot_order.csv";
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MailPieces {
+    /// Sampled voter of each mail piece, as an index in sampled order.
+    pub voters: Vec<usize>,
+    /// Ballot serial of each mail piece.
+    pub serials: Vec<BallotSerial>
+}
+
+impl MailPieces {
+    /// Deal `num_pieces` voters and ballot serials over as many mail
+    /// pieces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seventh_estate::cryptography::CSPRNGSeed;
+    /// use seventh_estate::mailing::MailPieces;
+    ///
+    /// let pieces = MailPieces::derive(CSPRNGSeed::from_vec(&vec![7; 32]), 100);
+    /// assert_eq!(pieces, MailPieces::derive(CSPRNGSeed::from_vec(&vec![7; 32]), 100));
+    /// let mut serials = pieces.serials.clone();
+    /// serials.sort();
+    /// assert_eq!((0..100).collect::<Vec<usize>>(), serials);
+    /// ```
+    pub fn derive(mailing_root: CSPRNGSeed, num_pieces: usize) -> Self {
+        let mut mailingrng = CSPRNG::from_csprng_seed(mailing_root);
+        let mut votersrng = CSPRNG::from_csprng_seed(CSPRNGSeed::next_seed(&mut mailingrng));
+        let mut serialsrng = CSPRNG::from_csprng_seed(CSPRNGSeed::next_seed(&mut mailingrng));
+        let mut voters: Vec<usize> = (0..num_pieces).collect();
+        voters.shuffle(&mut votersrng);
+        let mut serials: Vec<BallotSerial> = (0..num_pieces).collect();
+        serials.shuffle(&mut serialsrng);
+        MailPieces {
+            voters: voters,
+            serials: serials
+        }
+    }
+
+    pub fn len(self: &Self) -> usize {
+        self.serials.len()
+    }
+
+    /// Mail piece number as printed, counting from 1.
+    pub fn number(piece: usize) -> usize {
+        piece + 1
+    }
+}
@@ -11,13 +11,17 @@
 //! * Address Labels (CSV)
 //! * Ballot Information (CSV)
 use clap::{Arg, App, SubCommand};
+use seventh_estate::DEFAULT_NUMBER_OF_PLANES;
 use seventh_estate::subcommands::*;
+use seventh_estate::ballots::{BallotPdfOutput, Imposition, ScratchOffLayers, BALLOTS_PATH, DECOYS_PATH};
+use seventh_estate::mailing::{MailingOutput, LabelSheetFormat, EnvelopeWindow, IntelligentMail, MAILING_PATH};
 use tokio;
 
 type Exception = Box<dyn std::error::Error + 'static>;
 
 #[tokio::main]
 async fn main() -> Result<(), Exception> {
+    let default_num_planes = DEFAULT_NUMBER_OF_PLANES.to_string();
     let matches = App::new("Seventh-Estate")
         .about("Seventh-Estate Poll Manager")
         .version("1.0")
@@ -41,8 +45,23 @@ async fn main() -> Result<(), Exception> {
                 .short("r")
                 .long("roster")
                 .value_name("FILE")
-                .help("Voter roster CSV file.")
+                .help("Voter roster file.")
                 .required(true))
+            .arg(Arg::with_name("roster_profile")
+                .short("p")
+                .long("profile")
+                .value_name("FILE")
+                .help("Roster import profile YAML file.")
+                .required(false))
+            .arg(Arg::with_name("check_roster")
+                .long("check")
+                .help("Normalize and check the roster, and refuse to bind it if it has errors.")
+                .required(false))
+            .arg(Arg::with_name("report")
+                .long("report")
+                .value_name("FILE")
+                .help("Roster check report YAML file, with --check. Keep it private: it lists voters.")
+                .default_value("roster_report.yaml"))
             .arg(Arg::with_name("disable_voter_privacy")
                 .long("disable-voter-privacy")
                 .help("Commit roster with full voter name and address information.")
@@ -52,6 +71,26 @@ async fn main() -> Result<(), Exception> {
                 .long("force")
                 .help("Force a re-commit of the voter roster.")
                 .required(false)))
+        .subcommand(SubCommand::with_name("check-roster")
+            .about("Check a voter roster for missing fields and duplicate voters.")
+            .arg(Arg::with_name("roster_file")
+                .short("r")
+                .long("roster")
+                .value_name("FILE")
+                .help("Voter roster file.")
+                .required(true))
+            .arg(Arg::with_name("roster_profile")
+                .short("p")
+                .long("profile")
+                .value_name("FILE")
+                .help("Roster import profile YAML file.")
+                .required(false))
+            .arg(Arg::with_name("report")
+                .short("o")
+                .long("report")
+                .value_name("FILE")
+                .help("Roster check report YAML file.")
+                .default_value("roster_report.yaml")))
         .subcommand(SubCommand::with_name("step1")
             .about("Step 1: Generate initial commitments.")
             .arg(Arg::with_name("poll_configuration")
@@ -78,11 +117,20 @@ async fn main() -> Result<(), Exception> {
                 .long("seed")
                 .value_name("HEX")
                 .help("Seed value as hexadecimal string of bytes.")
-                .required(true))
+                .required_unless("seed_transcript"))
+            .arg(Arg::with_name("seed_transcript")
+                .long("seed-transcript")
+                .value_name("FILE")
+                .help("Seed transcript YAML file (Given by seed-derive subcommand).")
+                .required(false))
             .arg(Arg::with_name("force")
                 .short("f")
                 .long("force")
                 .help("Force a re-generation of the drawn summands.")
+                .required(false))
+            .arg(Arg::with_name("dry_run")
+                .long("dry-run")
+                .help("Rehearse the step: write outputs to a scratch directory and leave the poll configuration untouched.")
                 .required(false)))
         .subcommand(SubCommand::with_name("step3")
             .about("Step 3: Generate address labels and ballot information.")
@@ -103,6 +151,85 @@ async fn main() -> Result<(), Exception> {
                 .long("ballots")
                 .value_name("FILE")
                 .help("Ballot information CSV file.")
+                .required(true))
+            .arg(Arg::with_name("pdf_directory")
+                .long("pdf-dir")
+                .value_name("DIR")
+                .help("Directory of the ballot PDFs.")
+                .default_value(BALLOTS_PATH))
+            .arg(Arg::with_name("decoy_directory")
+                .long("decoy-dir")
+                .value_name("DIR")
+                .help("Directory of the decoy stickers and decoy serials, kept apart from the ballot PDFs.")
+                .default_value(DECOYS_PATH))
+            .arg(Arg::with_name("batch")
+                .long("batch")
+                .help("Write the ballots to one PDF, in mail piece order, instead of one PDF per serial.")
+                .required(false))
+            .arg(Arg::with_name("imposition")
+                .long("imposition")
+                .value_name("N-UP")
+                .help("Ballots per sheet of a batched PDF, with crop marks: 1-up, 2-up or 4-up.")
+                .possible_values(&["1-up", "2-up", "4-up"])
+                .default_value("1-up")
+                .requires("batch"))
+            .arg(Arg::with_name("sheets_per_file")
+                .long("sheets-per-file")
+                .value_name("N")
+                .help("Split the batched PDF into chunks of N sheets.")
+                .requires("batch"))
+            .arg(Arg::with_name("scratch_off")
+                .long("scratch-off")
+                .value_name("LAYERS")
+                .help("Scratch-off areas: combined with the text, on a scratch mask layer, or in a separate mask PDF, with registration marks.")
+                .possible_values(&["combined", "layers", "separate"])
+                .default_value("combined"))
+            .arg(Arg::with_name("mailing_directory")
+                .long("mailing-dir")
+                .value_name("DIR")
+                .help("Directory of the label sheets and envelope address pages.")
+                .default_value(MAILING_PATH))
+            .arg(Arg::with_name("label_sheet")
+                .long("label-sheet")
+                .value_name("FORMAT")
+                .help("Print the addresses on label sheets: 30-up, 14-up or a label sheet YAML file.")
+                .required(false))
+            .arg(Arg::with_name("envelope_window")
+                .long("envelope-window")
+                .value_name("LAYOUT")
+                .help("Print one address page per voter for window envelopes: no10 or an envelope window YAML file.")
+                .required(false))
+            .arg(Arg::with_name("intelligent_mail")
+                .long("imb")
+                .value_name("FILE")
+                .help("Intelligent Mail barcode YAML file, adds the IMb field to the address labels.")
+                .required(false))
+            .arg(Arg::with_name("packages")
+                .long("packages")
+                .value_name("DIR")
+                .help("Seal the files of the addressing vendor and of the ballot printer into packages encrypted with a password for each, leaving no plain copy.")
+                .required(false)))
+        .subcommand(SubCommand::with_name("decoy-request")
+            .about("Hand a decoy ballot to a voter who asked for one.")
+            .arg(Arg::with_name("poll_configuration")
+                .short("c")
+                .long("config")
+                .value_name("FILE")
+                .help("Poll configuration YAML file.")
+                .required(true))
+            .arg(Arg::with_name("voter")
+                .short("v")
+                .long("voter")
+                .value_name("IDENTIFIER")
+                .help("Identifier of the voter asking for a decoy.")
+                .required(true)))
+        .subcommand(SubCommand::with_name("decoy-status")
+            .about("Report how many decoy ballots were handed out.")
+            .arg(Arg::with_name("poll_configuration")
+                .short("c")
+                .long("config")
+                .value_name("FILE")
+                .help("Poll configuration YAML file.")
                 .required(true)))
         .subcommand(SubCommand::with_name("step4")
             .about("Step 4: Record audited (spoiled) ballots.")
@@ -122,7 +249,11 @@ async fn main() -> Result<(), Exception> {
                 .long("xxn")
                 .value_name("FILE")
                 .help("XX Network configuration file")
-                .required(true)))
+                .required(true))
+            .arg(Arg::with_name("dry_run")
+                .long("dry-run")
+                .help("Rehearse the step: write outputs to a scratch directory and leave the poll configuration untouched.")
+                .required(false)))
         .subcommand(SubCommand::with_name("step5")
             .about("Step 5: --VOTE-- (This command does nothing.)"))
         .subcommand(SubCommand::with_name("step6")
@@ -143,6 +274,10 @@ async fn main() -> Result<(), Exception> {
                 .short("f")
                 .long("force")
                 .help("Force an overwrite of the recorded votes.")
+                .required(false))
+            .arg(Arg::with_name("dry_run")
+                .long("dry-run")
+                .help("Rehearse the step: write outputs to a scratch directory and leave the poll configuration untouched.")
                 .required(false)))
         .subcommand(SubCommand::with_name("step7")
             .about("Step 7: Generate audited plane columns.")
@@ -157,11 +292,20 @@ async fn main() -> Result<(), Exception> {
                 .long("seed")
                 .value_name("HEX")
                 .help("Seed value as hexadecimal string of bytes.")
-                .required(true))
+                .required_unless("seed_transcript"))
+            .arg(Arg::with_name("seed_transcript")
+                .long("seed-transcript")
+                .value_name("FILE")
+                .help("Seed transcript YAML file (Given by seed-derive subcommand).")
+                .required(false))
             .arg(Arg::with_name("force")
                 .short("f")
                 .long("force")
                 .help("Force a re-generation of the audited planes columns.")
+                .required(false))
+            .arg(Arg::with_name("dry_run")
+                .long("dry-run")
+                .help("Rehearse the step: write outputs to a scratch directory and leave the poll configuration untouched.")
                 .required(false)))
         .subcommand(SubCommand::with_name("step8")
             .about("Step 8: Generated decrypted plane columns.")
@@ -175,7 +319,85 @@ async fn main() -> Result<(), Exception> {
                 .short("f")
                 .long("force")
                 .help("Force a re-decrypt of the plane columns.")
+                .required(false))
+            .arg(Arg::with_name("dry_run")
+                .long("dry-run")
+                .help("Rehearse the step: write outputs to a scratch directory and leave the poll configuration untouched.")
+                .required(false)))
+        .subcommand(SubCommand::with_name("verify-roster")
+            .about("Verify the revealed voters against the roster commitment.")
+            .arg(Arg::with_name("committed_roster")
+                .short("r")
+                .long("roster")
+                .value_name("FILE")
+                .help("Committed roster CSV file.")
+                .default_value("committed_roster.csv"))
+            .arg(Arg::with_name("revealed_roster")
+                .short("v")
+                .long("revealed")
+                .value_name("FILE")
+                .help("Revealed roster CSV file.")
+                .default_value("revealed_roster.csv")))
+        .subcommand(SubCommand::with_name("verify-selection")
+            .about("Re-check the voters selected in each stratum from the published summands.")
+            .arg(Arg::with_name("strata")
+                .short("s")
+                .long("strata")
+                .value_name("FILE")
+                .help("Sampling strata YAML file.")
+                .default_value("strata.yaml"))
+            .arg(Arg::with_name("committed_summands")
+                .short("c")
+                .long("committed")
+                .value_name("FILE")
+                .help("Revealed committed summands CSV file.")
+                .default_value("committed_summands_revealed.csv"))
+            .arg(Arg::with_name("drawn_summands")
+                .short("d")
+                .long("drawn")
+                .value_name("FILE")
+                .help("Drawn summands YAML file.")
+                .default_value("drawn_summands.yaml"))
+            .arg(Arg::with_name("revealed_roster")
+                .short("v")
+                .long("revealed")
+                .value_name("FILE")
+                .help("Revealed roster CSV file.")
                 .required(false)))
+        .subcommand(SubCommand::with_name("roster-proof")
+            .about("Generate the inclusion proof of a roster record.")
+            .arg(Arg::with_name("poll_configuration")
+                .short("c")
+                .long("config")
+                .value_name("FILE")
+                .help("Poll configuration YAML file.")
+                .required(true))
+            .arg(Arg::with_name("position")
+                .short("p")
+                .long("position")
+                .value_name("NUMBER")
+                .help("Roster position of the voter.")
+                .required(true))
+            .arg(Arg::with_name("proof")
+                .short("o")
+                .long("output")
+                .value_name("FILE")
+                .help("Roster proof YAML file.")
+                .default_value("roster_proof.yaml")))
+        .subcommand(SubCommand::with_name("verify-roster-proof")
+            .about("Verify a roster inclusion proof against the published roster root.")
+            .arg(Arg::with_name("proof")
+                .short("p")
+                .long("proof")
+                .value_name("FILE")
+                .help("Roster proof YAML file (Given by roster-proof subcommand).")
+                .required(true))
+            .arg(Arg::with_name("root")
+                .short("r")
+                .long("root")
+                .value_name("HEX")
+                .help("Published roster root as hexadecimal string.")
+                .required(true)))
         .subcommand(SubCommand::with_name("sign")
             .about("Generate signature for a file to publish.")
             .arg(Arg::with_name("poll_configuration")
@@ -212,6 +434,174 @@ async fn main() -> Result<(), Exception> {
                 .value_name("FILE")
                 .help("Proof of inclusion in YAML format (Given by gen subcommand).")
                 .required(true)))
+        .subcommand(SubCommand::with_name("seed-derive")
+            .about("Derive a seed from public inputs and write its transcript.")
+            .arg(Arg::with_name("purpose")
+                .short("p")
+                .long("purpose")
+                .value_name("PURPOSE")
+                .help("drawn_summands (step 2) or audited_columns (step 7).")
+                .required(true))
+            .arg(Arg::with_name("input")
+                .short("i")
+                .long("input")
+                .value_name("KIND=FILE")
+                .help("Public input file, with kind beacon, lottery, index or block_hash.")
+                .multiple(true)
+                .number_of_values(1)
+                .required(true))
+            .arg(Arg::with_name("transcript")
+                .short("o")
+                .long("output")
+                .value_name("FILE")
+                .help("Seed transcript YAML file.")
+                .default_value("seed_transcript.yaml")))
+        .subcommand(SubCommand::with_name("seed-verify")
+            .about("Re-derive the seed of a seed transcript.")
+            .arg(Arg::with_name("transcript")
+                .short("t")
+                .long("transcript")
+                .value_name("FILE")
+                .help("Seed transcript YAML file.")
+                .required(true)))
+        .subcommand(SubCommand::with_name("seed-keygen")
+            .about("Generate the key pair a participant signs seed commitments with.")
+            .arg(Arg::with_name("participant")
+                .short("n")
+                .long("participant")
+                .value_name("NAME")
+                .help("Name of the trustee or observer.")
+                .required(true))
+            .arg(Arg::with_name("key")
+                .short("o")
+                .long("output")
+                .value_name("FILE")
+                .help("YAML file with the signing key to keep private.")
+                .default_value("participant_key.yaml")))
+        .subcommand(SubCommand::with_name("seed-commit")
+            .about("Commit to a secret contribution to a seed.")
+            .arg(Arg::with_name("key")
+                .short("k")
+                .long("key")
+                .value_name("FILE")
+                .help("Participant key YAML file (Given by seed-keygen subcommand).")
+                .required(true))
+            .arg(Arg::with_name("purpose")
+                .short("p")
+                .long("purpose")
+                .value_name("PURPOSE")
+                .help("drawn_summands (step 2) or audited_columns (step 7).")
+                .required(true))
+            .arg(Arg::with_name("commitment")
+                .short("o")
+                .long("output")
+                .value_name("FILE")
+                .help("Signed commitment YAML file to publish.")
+                .default_value("seed_commitment.yaml"))
+            .arg(Arg::with_name("secret")
+                .short("s")
+                .long("secret")
+                .value_name("FILE")
+                .help("YAML file with the contribution to keep private until the reveal.")
+                .default_value("seed_secret.yaml")))
+        .subcommand(SubCommand::with_name("seed-reveal")
+            .about("Reveal a committed contribution to a seed.")
+            .arg(Arg::with_name("secret")
+                .short("s")
+                .long("secret")
+                .value_name("FILE")
+                .help("Secret YAML file (Given by seed-commit subcommand).")
+                .required(true))
+            .arg(Arg::with_name("reveal")
+                .short("o")
+                .long("output")
+                .value_name("FILE")
+                .help("Reveal YAML file to publish.")
+                .default_value("seed_reveal.yaml")))
+        .subcommand(SubCommand::with_name("seed-combine")
+            .about("Check the commitments against the participant keys, the reveals against their commitments and combine them into a seed transcript.")
+            .arg(Arg::with_name("purpose")
+                .short("p")
+                .long("purpose")
+                .value_name("PURPOSE")
+                .help("drawn_summands (step 2) or audited_columns (step 7).")
+                .required(true))
+            .arg(Arg::with_name("participant_key")
+                .short("k")
+                .long("participant-key")
+                .value_name("NAME=PUBLIC_KEY")
+                .help("Public key of a participant (Given by seed-keygen subcommand).")
+                .multiple(true)
+                .number_of_values(1)
+                .required(true))
+            .arg(Arg::with_name("commitment")
+                .short("c")
+                .long("commitment")
+                .value_name("FILE")
+                .help("Commitment YAML file (Given by seed-commit subcommand).")
+                .multiple(true)
+                .number_of_values(1)
+                .required(true))
+            .arg(Arg::with_name("reveal")
+                .short("r")
+                .long("reveal")
+                .value_name("FILE")
+                .help("Reveal YAML file (Given by seed-reveal subcommand).")
+                .multiple(true)
+                .number_of_values(1)
+                .required(true))
+            .arg(Arg::with_name("transcript")
+                .short("o")
+                .long("output")
+                .value_name("FILE")
+                .help("Seed transcript YAML file.")
+                .default_value("seed_transcript.yaml")))
+        .subcommand(SubCommand::with_name("decode-ballot-url")
+            .about("Print the ballot serial of a URL printed as a QR code on a ballot.")
+            .arg(Arg::with_name("url")
+                .short("u")
+                .long("url")
+                .value_name("URL")
+                .help("Scanned ballot URL.")
+                .required(true))
+            .arg(Arg::with_name("voting_url")
+                .long("voting-url")
+                .value_name("URL")
+                .help("Voting URL of the poll; the ballot URL must be one of its URLs.")))
+        .subcommand(SubCommand::with_name("open-package")
+            .about("Decrypt a vendor package sealed by step 3 and write out its files.")
+            .arg(Arg::with_name("package")
+                .short("p")
+                .long("package")
+                .value_name("FILE")
+                .help("Vendor package file (Given by step3 subcommand).")
+                .required(true))
+            .arg(Arg::with_name("output_directory")
+                .short("o")
+                .long("output")
+                .value_name("DIR")
+                .help("Directory to write the files of the package to.")
+                .required(true)))
+        .subcommand(SubCommand::with_name("plan")
+            .about("Report how likely tampering survives the column audit.")
+            .arg(Arg::with_name("num_planes")
+                .short("p")
+                .long("planes")
+                .value_name("COUNT")
+                .help("Number of column planes.")
+                .default_value(&default_num_planes))
+            .arg(Arg::with_name("num_rows")
+                .short("k")
+                .long("rows")
+                .value_name("COUNT")
+                .help("Number of tampered rows.")
+                .default_value("1"))
+            .arg(Arg::with_name("target")
+                .short("t")
+                .long("target")
+                .value_name("PROBABILITY")
+                .help("Highest acceptable survival probability (e.g. 1e-9).")
+                .required(false)))
         .subcommand(SubCommand::with_name("audit")
             .about("Blockchain audit, count votes in blockchain")
             .arg(Arg::with_name("poll_configuration")
@@ -238,8 +628,23 @@ async fn main() -> Result<(), Exception> {
                 .short("r")
                 .long("roster")
                 .value_name("FILE")
-                .help("Voter roster CSV file.")
+                .help("Voter roster file.")
                 .required(true))
+            .arg(Arg::with_name("roster_profile")
+                .short("p")
+                .long("profile")
+                .value_name("FILE")
+                .help("Roster import profile YAML file.")
+                .required(false))
+            .arg(Arg::with_name("check_roster")
+                .long("check")
+                .help("Normalize and check the roster, and refuse to bind it if it has errors.")
+                .required(false))
+            .arg(Arg::with_name("report")
+                .long("report")
+                .value_name("FILE")
+                .help("Roster check report YAML file, with --check. Keep it private: it lists voters.")
+                .default_value("roster_report.yaml"))
             .arg(Arg::with_name("disable_voter_privacy")
                 .long("disable-voter-privacy")
                 .help("Commit roster with full voter name and address information.")
@@ -249,7 +654,12 @@ async fn main() -> Result<(), Exception> {
                 .long("seed")
                 .value_name("HEX")
                 .help("Seed value as hexadecimal string of bytes.")
-                .required(true))
+                .required_unless("seed_transcript"))
+            .arg(Arg::with_name("seed_transcript")
+                .long("seed-transcript")
+                .value_name("FILE")
+                .help("Seed transcript YAML file (Given by seed-derive subcommand).")
+                .required(false))
             .arg(Arg::with_name("address_label")
                 .short("a")
                 .long("addresses")
@@ -311,9 +721,18 @@ async fn main() -> Result<(), Exception> {
             bind_roster(
                 arguments.value_of("poll_configuration").unwrap(),
                 arguments.value_of("roster_file").unwrap(),
+                arguments.value_of("roster_profile"),
+                0 < arguments.occurrences_of("check_roster"),
+                arguments.value_of("report").unwrap(),
                 0 < arguments.occurrences_of("disable_voter_privacy"),
                 0 < arguments.occurrences_of("force"))?;
         },
+        ("check-roster", Some(arguments)) => {
+            check_roster_file(
+                arguments.value_of("roster_file").unwrap(),
+                arguments.value_of("roster_profile"),
+                arguments.value_of("report").unwrap())?;
+        },
         ("step1", Some(arguments)) => {
             generate_poll_commitments(
                 arguments.value_of("poll_configuration").unwrap(),
@@ -322,37 +741,108 @@ async fn main() -> Result<(), Exception> {
         ("step2", Some(arguments)) => {
             generate_drawn_summands(
                 arguments.value_of("poll_configuration").unwrap(),
-                arguments.value_of("drawn_summands_seed").unwrap(),
-                0 < arguments.occurrences_of("force"))?;
+                arguments.value_of("drawn_summands_seed"),
+                arguments.value_of("seed_transcript"),
+                0 < arguments.occurrences_of("force"),
+                0 < arguments.occurrences_of("dry_run"))?;
         },
         ("step3", Some(arguments)) => {
+            let pdf_output = BallotPdfOutput {
+                directory: arguments.value_of("pdf_directory").unwrap().to_owned(),
+                batched: arguments.is_present("batch"),
+                imposition: Imposition::from_name(arguments.value_of("imposition").unwrap()).unwrap(),
+                sheets_per_file: match arguments.value_of("sheets_per_file") {
+                    Some(sheets) => match sheets.parse::<usize>()? {
+                        0 => return Err(From::from("A batched PDF must hold at least one sheet (--sheets-per-file).")),
+                        sheets => Some(sheets)
+                    },
+                    None => None
+                },
+                scratch_off: ScratchOffLayers::from_name(arguments.value_of("scratch_off").unwrap()).unwrap(),
+                decoy_directory: arguments.value_of("decoy_directory").unwrap().to_owned()
+            };
+            let mailing = MailingOutput {
+                directory: arguments.value_of("mailing_directory").unwrap().to_owned(),
+                label_sheet: match arguments.value_of("label_sheet") {
+                    Some(format) => Some(LabelSheetFormat::from_name_or_file(format)?),
+                    None => None
+                },
+                envelope_window: match arguments.value_of("envelope_window") {
+                    Some(layout) => Some(EnvelopeWindow::from_name_or_file(layout)?),
+                    None => None
+                },
+                intelligent_mail: match arguments.value_of("intelligent_mail") {
+                    Some(file) => Some(IntelligentMail::from_file(&file)?),
+                    None => None
+                },
+                packages: arguments.value_of("packages").map(|directory| directory.to_owned())
+            };
             generate_print_files(
                 arguments.value_of("poll_configuration").unwrap(),
                 arguments.value_of("address_label").unwrap(),
-                arguments.value_of("ballot_information").unwrap())?;
+                arguments.value_of("ballot_information").unwrap(),
+                &pdf_output,
+                &mailing)?;
+        },
+        ("decoy-request", Some(arguments)) => {
+            record_decoy_request(
+                arguments.value_of("poll_configuration").unwrap(),
+                arguments.value_of("voter").unwrap())?;
+        },
+        ("decoy-status", Some(arguments)) => {
+            decoy_status(
+                arguments.value_of("poll_configuration").unwrap())?;
         },
         ("step4", Some(arguments)) => {
             record_audited_ballots(
                 arguments.value_of("poll_configuration").unwrap(),
                 arguments.value_of("audited_ballots").unwrap(),
                 0 < arguments.occurrences_of("force"),
-            arguments.value_of("xxn_config").unwrap())?;
+                arguments.value_of("xxn_config").unwrap(),
+                0 < arguments.occurrences_of("dry_run"))?;
         },
         ("step6", Some(arguments)) => {
             record_votes(
                 arguments.value_of("poll_configuration").unwrap(),
                 arguments.value_of("votes_file").unwrap(),
-                0 < arguments.occurrences_of("force"))?;
+                0 < arguments.occurrences_of("force"),
+                0 < arguments.occurrences_of("dry_run"))?;
         },
         ("step7", Some(arguments)) => {
             generate_tally_audit(
                 arguments.value_of("poll_configuration").unwrap(),
-                arguments.value_of("tally_audit_seed").unwrap())?;
+                arguments.value_of("tally_audit_seed"),
+                arguments.value_of("seed_transcript"),
+                0 < arguments.occurrences_of("dry_run"))?;
         },
         ("step8", Some(arguments)) => {
             generate_poll_revelations(
                 arguments.value_of("poll_configuration").unwrap(),
-                0 < arguments.occurrences_of("force"))?;
+                0 < arguments.occurrences_of("force"),
+                0 < arguments.occurrences_of("dry_run"))?;
+        },
+        ("verify-roster", Some(arguments)) => {
+            verify_roster(
+                arguments.value_of("committed_roster").unwrap(),
+                arguments.value_of("revealed_roster").unwrap())?;
+        },
+        ("verify-selection", Some(arguments)) => {
+            verify_selection(
+                arguments.value_of("strata").unwrap(),
+                arguments.value_of("committed_summands").unwrap(),
+                arguments.value_of("drawn_summands").unwrap(),
+                arguments.value_of("revealed_roster"))?;
+        },
+        ("roster-proof", Some(arguments)) => {
+            generate_roster_proof(
+                arguments.value_of("poll_configuration").unwrap(),
+                arguments.value_of("position").unwrap().parse::<usize>()?,
+                arguments.value_of("proof").unwrap())?;
+        },
+        ("verify-roster-proof", Some(arguments)) => {
+            verify_roster_proof_file(
+                arguments.value_of("proof").unwrap(),
+                arguments.value_of("root").unwrap())?;
         },
         ("sign", Some(arguments)) => {
             sign_document(
@@ -370,6 +860,57 @@ async fn main() -> Result<(), Exception> {
                 arguments.value_of("inclusion_proof").unwrap())?;
 
         },
+        ("seed-derive", Some(arguments)) => {
+            seed_derive(
+                arguments.value_of("purpose").unwrap(),
+                arguments.values_of("input").unwrap().collect(),
+                arguments.value_of("transcript").unwrap())?;
+        },
+        ("seed-verify", Some(arguments)) => {
+            seed_verify(
+                arguments.value_of("transcript").unwrap())?;
+        },
+        ("seed-keygen", Some(arguments)) => {
+            seed_keygen(
+                arguments.value_of("participant").unwrap(),
+                arguments.value_of("key").unwrap())?;
+        },
+        ("seed-commit", Some(arguments)) => {
+            seed_commit(
+                arguments.value_of("key").unwrap(),
+                arguments.value_of("purpose").unwrap(),
+                arguments.value_of("commitment").unwrap(),
+                arguments.value_of("secret").unwrap())?;
+        },
+        ("seed-reveal", Some(arguments)) => {
+            seed_reveal(
+                arguments.value_of("secret").unwrap(),
+                arguments.value_of("reveal").unwrap())?;
+        },
+        ("seed-combine", Some(arguments)) => {
+            seed_combine(
+                arguments.value_of("purpose").unwrap(),
+                arguments.values_of("participant_key").unwrap().collect(),
+                arguments.values_of("commitment").unwrap().collect(),
+                arguments.values_of("reveal").unwrap().collect(),
+                arguments.value_of("transcript").unwrap())?;
+        },
+        ("decode-ballot-url", Some(arguments)) => {
+            ballot_url_serial(
+                arguments.value_of("url").unwrap(),
+                arguments.value_of("voting_url"))?;
+        },
+        ("open-package", Some(arguments)) => {
+            open_package(
+                arguments.value_of("package").unwrap(),
+                arguments.value_of("output_directory").unwrap())?;
+        },
+        ("plan", Some(arguments)) => {
+            plan(
+                arguments.value_of("num_planes").unwrap().parse::<usize>()?,
+                arguments.value_of("num_rows").unwrap().parse::<usize>()?,
+                arguments.value_of("target").map(|target| target.parse::<f64>()).transpose()?)?;
+        },
         ("audit", Some(arguments)) => {
             blockchain_audit(
                 arguments.value_of("poll_configuration").unwrap(),
@@ -379,6 +920,9 @@ async fn main() -> Result<(), Exception> {
             start(
                 arguments.value_of("poll_configuration").unwrap(),
                 arguments.value_of("roster_file").unwrap(),
+                arguments.value_of("roster_profile"),
+                0 < arguments.occurrences_of("check_roster"),
+                arguments.value_of("report").unwrap(),
                 0 < arguments.occurrences_of("disable_voter_privacy"),
                 arguments.value_of("drawn_summands_seed").unwrap(),
                 arguments.value_of("address_label").unwrap(),
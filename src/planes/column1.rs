@@ -1,8 +1,18 @@
 //! # Column 1 Entries
+//!
+//! Column 1 links a plane row to its ballot serial and vote code. The
+//! entry is encrypted in its canonical encoding
+//! `v1:<serial>:<vote code>`, where the serial is padded to the width of
+//! the largest serial so all entries of a poll encrypt to the same length,
+//! and the vote code is in its canonical encoding. Everything reading
+//! column 1 goes through `SerialVoteCode::from_canonical`.
 
 use super::*;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+const SERIAL_VOTE_CODE_VERSION: &str = "v1";
+const SERIAL_VOTE_CODE_SEPARATOR: char = ':';
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SerialVoteCode {
     pub serial: BallotSerial,
     pub votecode: VoteCode
@@ -10,34 +20,73 @@ pub struct SerialVoteCode {
 
 #[derive(Debug, Clone, Serialize)]
 pub enum Column1Entry {
-    //Entry(SerialVoteCode),
-    Entry(String),
+    Entry(SerialVoteCode),
     Encrypted(AEADString)
 }
 
+impl SerialVoteCode {
+    /// Canonical encoding of the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seventh_estate::planes::SerialVoteCode;
+    ///
+    /// let entry = SerialVoteCode { serial: 7, votecode: vec![1, 2, 3, 4] };
+    /// assert_eq!("v1:007:1234", entry.to_canonical(250));
+    /// assert_eq!(entry, SerialVoteCode::from_canonical("v1:007:1234").unwrap());
+    /// ```
+    pub fn to_canonical(self: &Self, num_ballots: usize) -> String {
+        format!("{}{}{}{}{}",
+            SERIAL_VOTE_CODE_VERSION,
+            SERIAL_VOTE_CODE_SEPARATOR,
+            string_from_ballotserial(&self.serial, num_ballots),
+            SERIAL_VOTE_CODE_SEPARATOR,
+            canonical_string_from_votecode(&self.votecode))
+    }
+
+    /// Parse the canonical encoding of an entry.
+    pub fn from_canonical(value: &str) -> Result<Self> {
+        let fields: Vec<&str> = value.split(SERIAL_VOTE_CODE_SEPARATOR).collect();
+        if fields.len() != 3 {
+            return Err(From::from(format!("Malformed column 1 entry: {}", value)));
+        }
+        if fields[0] != SERIAL_VOTE_CODE_VERSION {
+            return Err(From::from(format!("Unsupported column 1 entry version: {}", fields[0])));
+        }
+        let serial: BallotSerial = fields[1].parse::<BallotSerial>()?;
+        let votecode: VoteCode = votecode_from_canonical_string(fields[2])
+            .ok_or(format!("Malformed vote code in column 1 entry: {}", value))?;
+        Ok(SerialVoteCode {
+            serial: serial,
+            votecode: votecode
+        })
+    }
+}
+
 impl Column1Entry {
-    pub fn decrypt(self: &Self, filter: &PlaneFilterEntry) -> Self {
+    pub fn decrypt(self: &Self, filter: &PlaneFilterEntry) -> Result<Self> {
         match self {
             Self::Encrypted(aestr) => {
                 match filter.decrypt {
                     true => {
-                        let aevalues = aestr.values().unwrap();
-                        let serialized_bytes = aead_decrypt(&filter.key, &aevalues).unwrap();
-                        let serialized = String::from_utf8(serialized_bytes).unwrap();
-                        let entry = serialized;
-                        Self::Entry(entry)
+                        let aevalues = aestr.values()?;
+                        let serialized_bytes = aead_decrypt(&filter.key, &aevalues)?;
+                        let serialized = String::from_utf8(serialized_bytes)?;
+                        let entry = SerialVoteCode::from_canonical(&serialized)?;
+                        Ok(Self::Entry(entry))
                     },
-                    false => (*self).clone()
+                    false => Ok((*self).clone())
                 }
             },
-            _ => (*self).clone()
+            _ => Ok((*self).clone())
         }
     }
 
-    pub fn encrypt(self: &Self, key: &AEADKey, nonce: &AEADNonce) -> Option<Self> {
+    pub fn encrypt(self: &Self, key: &AEADKey, nonce: &AEADNonce, num_ballots: usize) -> Option<Self> {
         match self {
             Self::Entry(value) => {
-                let serialized = value.as_bytes().to_vec();
+                let serialized = value.to_canonical(num_ballots).as_bytes().to_vec();
                 let aad = base64::encode(&nonce.0)
                     .as_bytes().to_vec();
                 let aead_values = aead_encrypt_ex(&key, &nonce, aad, serialized).unwrap();
@@ -47,4 +96,3 @@ impl Column1Entry {
         }
     }
 }
-
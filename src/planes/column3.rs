@@ -9,21 +9,21 @@ pub enum Column3Entry {
 }
 
 impl Column3Entry {
-    pub fn decrypt(self: &Self, filter: &PlaneFilterEntry) -> Self {
+    pub fn decrypt(self: &Self, filter: &PlaneFilterEntry) -> Result<Self> {
         match self {
             Self::Encrypted(aestr) => {
                 match filter.decrypt {
                     true => {
-                        let aevalues = aestr.values().unwrap();
-                        let serialized_bytes = aead_decrypt(&filter.key, &aevalues).unwrap();
-                        let serialized = String::from_utf8(serialized_bytes).unwrap();
-                        let entry = serde_yaml::from_str(&serialized).unwrap();
-                        Self::Entry(entry)
+                        let aevalues = aestr.values()?;
+                        let serialized_bytes = aead_decrypt(&filter.key, &aevalues)?;
+                        let serialized = String::from_utf8(serialized_bytes)?;
+                        let entry = serde_yaml::from_str(&serialized)?;
+                        Ok(Self::Entry(entry))
                     },
-                    false => (*self).clone()
+                    false => Ok((*self).clone())
                 }
             },
-            _ => (*self).clone()
+            _ => Ok((*self).clone())
         }
     }
 
@@ -44,11 +44,11 @@ impl PlaneFilter {
         }
     }
 
-    pub fn decrypt_serials(self: &Self, serials: &Vec<BallotSerial>) -> Self {
+    pub fn decrypt_serials(self: &Self, serials: &Vec<BallotSerial>, num_options: usize) -> Self {
         Self {
             rows: self.rows.iter().enumerate()
                 .map(|(n, row)| {
-                    let serial = n/2;
+                    let serial = n / num_options;
                     if serials.contains(&serial) {
                         PlaneFilterRecord {
                             col1: PlaneFilterEntry { key: row.col1.key.clone(), decrypt: true },
@@ -3,15 +3,13 @@
 
 use serde::{Serialize, Deserialize};
 use crate::ballots::{BallotSerial, VoteCode};
-/*
 use crate::ballots::{
     string_from_ballotserial,
-    string_from_votecode,
-    string_from_taggedchoicevalue,
-    string_from_taggedchoicevalue_padded
+    canonical_string_from_votecode,
+    votecode_from_canonical_string
 };
-*/
 use crate::cryptography::*;
+use crate::Result;
 
 pub mod record;
 pub use record::*;
@@ -51,14 +49,14 @@ impl Plane {
         }
     }
 
-    pub fn decrypt(self: &Self, filter: &PlaneFilter) -> Self {
+    pub fn decrypt(self: &Self, filter: &PlaneFilter) -> Result<Self> {
         assert!(self.len() == filter.len(),
             "Plane and Filter must have the same number of rows.");
-        Plane {
+        Ok(Plane {
             rows: self.rows.iter().zip(filter.rows.iter())
                 .map(|(prec, frec)| { prec.decrypt(frec) })
-                .collect::<Vec<PlaneRecord>>()
-        }
+                .collect::<Result<Vec<PlaneRecord>>>()?
+        })
     }
 
     pub fn len(self: &Self) -> usize { self.rows.len() }
@@ -33,26 +33,18 @@ impl PlaneRecord {
         }
     }
 
-    pub fn decrypt(self: &Self, filter: &PlaneFilterRecord) -> Self {
-        PlaneRecord {
-            col1: self.col1.decrypt(&filter.col1),
+    pub fn decrypt(self: &Self, filter: &PlaneFilterRecord) -> Result<Self> {
+        Ok(PlaneRecord {
+            col1: self.col1.decrypt(&filter.col1)?,
             col2: self.col2.clone(),
-            col3: self.col3.decrypt(&filter.col3)
-        }
+            col3: self.col3.decrypt(&filter.col3)?
+        })
     }
 
     pub fn serializable(self: &Self, num_ballots: usize) -> PlaneRecordFileRow {
-        let _num_ballots = num_ballots;
         PlaneRecordFileRow {
             col1: match &self.col1 {
-                Column1Entry::Entry(svc) => svc.clone(),
-                /*
-                {
-                    format!("{}: {}",
-                        string_from_ballotserial(&svc.serial, num_ballots),
-                        string_from_votecode(&svc.votecode))
-                },
-                */
+                Column1Entry::Entry(svc) => svc.to_canonical(num_ballots),
                 Column1Entry::Encrypted(v) => v.0.clone()
             },
             col2: match self.col2 {
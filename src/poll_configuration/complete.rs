@@ -21,20 +21,75 @@ pub struct PollConfiguration {
     pub signing_key: Base64String,
     pub num_ballots: usize,
     pub num_decoys: usize,
+    #[serde(default = "default_num_planes")]
+    pub num_planes: usize,
     pub voter_roster: Option<Base64String>,
+    #[serde(default)]
+    pub voter_roster_sidecar: Option<RosterSidecarReference>,
     pub voter_roster_size: usize,
+    #[serde(default)]
+    pub voter_roster_hash: Option<String>,
     pub voter_privacy: bool,
+    #[serde(default)]
+    pub stratification: Option<Stratification>,
+    #[serde(default)]
+    pub strata: Vec<Stratum>,
     pub drawn_summands_seed: Option<String>,
     pub audited_columns_seed: Option<String>,
     pub audited_ballots: Option<Vec<String>>,
     pub votes: Option<Vec<VoteCode>>,
-    pub question: String,
-    pub option1: String,
-    pub option2: String,
+    #[serde(default)]
+    pub decoy_requests: Vec<DecoyRequest>,
+    pub questions: Vec<PollQuestion>,
+    #[serde(default)]
+    pub votecode_format: VoteCodeFormat,
+    #[serde(default)]
+    pub ballot_template: BallotTemplate,
+    #[serde(default)]
+    pub languages: BallotLanguages,
+    #[serde(default)]
+    pub voting_url: Option<VotingUrl>,
     pub start_date: String,
     pub end_date: String
 }
 
+impl PollConfiguration {
+    /// Number of questions asked on each ballot.
+    pub fn num_questions(self: &Self) -> usize {
+        self.questions.len()
+    }
+
+    /// Number of options offered by a question.
+    pub fn num_options(self: &Self, question: usize) -> usize {
+        self.questions[question].num_options()
+    }
+
+    /// Strata the ballots are sampled from: the strata bound with the
+    /// roster, or the whole roster as a single stratum.
+    pub fn sampling_strata(self: &Self) -> Vec<Stratum> {
+        match self.strata.is_empty() {
+            true => vec![Stratum {
+                key: "".to_owned(),
+                ballots: self.num_ballots,
+                voters: self.voter_roster_size
+            }],
+            false => self.strata.clone()
+        }
+    }
+
+    /// Number of summands and their modulus, for each sampling stratum.
+    pub fn summand_strata(self: &Self) -> Vec<(usize, usize)> {
+        self.sampling_strata().iter()
+            .map(|stratum| (stratum.ballots, stratum.voters))
+            .collect()
+    }
+
+    /// Number of rows in each column plane of a question: one per option per ballot.
+    pub fn num_rows(self: &Self, question: usize) -> usize {
+        self.num_options(question) * self.num_ballots
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PollState {
     pub announced: bool,
@@ -0,0 +1,44 @@
+//! # Decoy Requests
+//!
+//! Voters may ask for a decoy ballot to hand over to whoever pressures
+//! them. Each request binds the voter to the decoy serial handed out.
+//! Requests are kept in the encrypted poll configuration only, so the
+//! serials of the decoys never appear in anything posted.
+
+use super::*;
+use crate::Result;
+use crate::ballots::{BallotSerial, string_from_ballotserial};
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecoyRequest {
+    pub voter: String,
+    pub serial: String,
+    pub requested: String
+}
+
+
+/// Hand the first decoy serial not yet handed out to a voter, and record
+/// the request. Returns the serial, as in the list of decoy serials.
+pub fn hand_out_decoy(requests: &mut Vec<DecoyRequest>, decoys: &Vec<BallotSerial>, num_ballots: usize, voter: &str, requested: String) -> Result<String> {
+    if requests.iter().any(|request| request.voter == voter) {
+        return Err(From::from(format!("Voter {} already received a decoy.", voter)));
+    }
+    let serial: String = decoys.iter()
+        .map(|serial| string_from_ballotserial(serial, num_ballots))
+        .find(|serial| !requests.iter().any(|request| &request.serial == serial))
+        .ok_or("All decoys have been handed out.")?;
+    requests.push(DecoyRequest {
+        voter: voter.to_owned(),
+        serial: serial.clone(),
+        requested: requested
+    });
+    Ok(serial)
+}
+
+/// Lines reporting the decoys handed out, without any decoy serial.
+pub fn decoy_status_lines(requests: &Vec<DecoyRequest>, num_decoys: usize) -> Vec<String> {
+    std::iter::once(format!("Decoys handed out: {} of {}", requests.len(), num_decoys))
+        .chain(requests.iter().map(|request| format!("  {}: {}", request.requested, request.voter)))
+        .collect()
+}
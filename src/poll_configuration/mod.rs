@@ -2,7 +2,17 @@
 
 use serde::{Serialize, Deserialize};
 use crate::cryptography::{Base64String, AEADString};
-use crate::ballots::VoteCode;
+use crate::ballots::{VoteCode, VoteCodeFormat, BallotTemplate, BallotLanguages, VotingUrl};
+use crate::voter_roster::RosterSidecarReference;
+
+pub mod question;
+pub use question::*;
+
+pub mod decoy_request;
+pub use decoy_request::*;
+
+pub mod stratification;
+pub use stratification::*;
 
 pub mod complete;
 pub use complete::*;
@@ -12,3 +22,5 @@ pub use secured::*;
 
 pub mod new;
 pub use new::*;
+
+fn default_num_planes() -> usize { crate::DEFAULT_NUMBER_OF_PLANES }
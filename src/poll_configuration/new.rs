@@ -14,9 +14,25 @@ pub struct NewPollConfiguration {
     pub poll_trustees: Vec<NewPollConfigurationTrustee>,
     pub num_ballots: usize,
     pub num_decoys: usize,
-    pub question: String,
-    pub option1: String,
-    pub option2: String,
+    #[serde(default = "default_num_planes")]
+    pub num_planes: usize,
+    pub questions: Vec<PollQuestion>,
+    #[serde(default)]
+    pub votecode_format: VoteCodeFormat,
+    #[serde(default)]
+    pub stratification: Option<Stratification>,
+    /// Ballot template YAML file, relative to this file. The default
+    /// layout is used if not given.
+    #[serde(default)]
+    pub ballot_template: Option<String>,
+    /// Default language of the ballots and whether translated ballots
+    /// are bilingual.
+    #[serde(default)]
+    pub languages: BallotLanguages,
+    /// Voting page whose URL, with the ballot serial, is printed as a QR
+    /// code where the ballot template places it.
+    #[serde(default)]
+    pub voting_url: Option<VotingUrl>,
     pub start_date: String,
     pub end_date: String
 }
\ No newline at end of file
@@ -0,0 +1,48 @@
+//! # Poll Questions
+//!
+//! A poll asks one or more questions on every mailed ballot. Each
+//! question has its own options, vote codes and column planes, but all
+//! questions share the sampled voters and the ballot serial.
+//!
+//! A question may be translated, keyed by language (e.g. `es`, `zh`,
+//! `vi`). A translation only changes what is printed: the options keep
+//! their order, so the vote codes and planes are the same in every
+//! language.
+
+use std::collections::BTreeMap;
+use super::*;
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollQuestion {
+    pub question: String,
+    pub options: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub translations: BTreeMap<String, QuestionTranslation>
+}
+
+/// The question and its options, in the order of the poll options, in
+/// another language.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuestionTranslation {
+    pub question: String,
+    pub options: Vec<String>
+}
+
+impl PollQuestion {
+    /// Number of options the question offers.
+    pub fn num_options(self: &Self) -> usize {
+        self.options.len()
+    }
+
+    /// Translation of the question in a language, if there is one.
+    pub fn translation(self: &Self, language: &str) -> Option<&QuestionTranslation> {
+        self.translations.get(language)
+    }
+
+    /// Whether every translation gives one label per option.
+    pub fn translations_complete(self: &Self) -> bool {
+        self.translations.values()
+            .all(|translation| translation.options.len() == self.num_options())
+    }
+}
@@ -0,0 +1,101 @@
+//! # Stratification
+//!
+//! Voters may be sampled per stratum instead of over the whole roster, so
+//! that every region gets its share of the ballots. Strata are defined by
+//! a roster field, optionally cut to a prefix (e.g. the first 3 digits of
+//! the ZIP code), and each stratum is allocated a number of ballots:
+//!
+//! ```yaml
+//! stratification:
+//!   field: state             # state, zip_code, city or country
+//!   prefix: 3                # optional
+//!   allocation: explicit     # proportional (default) or explicit
+//!   ballots:                 # explicit allocation only
+//!     TX: 60
+//!     OK: 40
+//! ```
+
+use std::collections::BTreeMap;
+use super::*;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StratumField {
+    #[serde(rename = "state")]
+    State,
+    #[serde(rename = "zip_code")]
+    ZipCode,
+    #[serde(rename = "city")]
+    City,
+    #[serde(rename = "country")]
+    Country
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StratumAllocation {
+    #[serde(rename = "proportional")]
+    Proportional,
+    #[serde(rename = "explicit")]
+    Explicit
+}
+
+impl Default for StratumAllocation {
+    fn default() -> Self { StratumAllocation::Proportional }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stratification {
+    pub field: StratumField,
+    #[serde(default)]
+    pub prefix: Option<usize>,
+    #[serde(default)]
+    pub allocation: StratumAllocation,
+    #[serde(default)]
+    pub ballots: BTreeMap<String, usize>
+}
+
+/// A stratum of the roster: its key, the ballots allocated to it and its
+/// number of voters. The roster positions of its voters are not stored;
+/// they are found by streaming the roster when needed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "StoredStratum")]
+pub struct Stratum {
+    pub key: String,
+    pub ballots: usize,
+    pub voters: usize
+}
+
+/// Stratum as stored, also read from configurations that listed the
+/// roster positions of each stratum.
+#[derive(Deserialize)]
+struct StoredStratum {
+    key: String,
+    ballots: usize,
+    #[serde(default)]
+    voters: Option<usize>,
+    #[serde(default)]
+    positions: Option<Vec<usize>>
+}
+
+impl From<StoredStratum> for Stratum {
+    fn from(stored: StoredStratum) -> Self {
+        Stratum {
+            key: stored.key,
+            ballots: stored.ballots,
+            voters: stored.voters.unwrap_or_else(|| stored.positions.map_or(0, |positions| positions.len()))
+        }
+    }
+}
+
+/// Sampling strata published in step 1. With stratification `members`
+/// holds, per stratum, the salted commitment of the roster position of
+/// each of its voters, in roster order (see `member_commitment`); step 8
+/// reveals the salts of the selected voters. Without it the only stratum
+/// is the whole roster and `members` is empty.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrataFile {
+    pub stratification: Option<Stratification>,
+    pub strata: Vec<Stratum>,
+    #[serde(default)]
+    pub members: Vec<Vec<String>>
+}
@@ -2,7 +2,6 @@
 
 use rand::seq::SliceRandom;
 use super::*;
-use crate::NUMBER_OF_PLANES;
 
 #[derive(Debug, Clone)]
 pub struct DerivedPlaneSecrets {
@@ -26,21 +25,31 @@ pub struct PlaneSecrets {
     pub col3_nonce_root: CSPRNGSeed,
 }
 
+#[derive(Debug, Clone)]
+pub struct QuestionSecrets {
+    pub votecode_root: CSPRNGSeed,
+    pub planes_root: CSPRNGSeed,
+    // Plane-level Secrets
+    pub plane_secrets: Vec<PlaneSecrets>
+}
+
 #[derive(Debug, Clone)]
 pub struct PollSecrets {
     // Top-level Secrets
-    pub votecode_root: CSPRNGSeed,
     pub decoy_root: CSPRNGSeed,
     pub summands_root: CSPRNGSeed,
-    pub planes_root: CSPRNGSeed,
+    pub questions_root: CSPRNGSeed,
+    pub roster_root: CSPRNGSeed,
+    /// Root of the shuffles pairing addresses and ballots.
+    pub mailing_root: CSPRNGSeed,
     pub summands_key: AEADKey,
-    // Plane-level Secrets
-    pub plane_secrets: Vec<PlaneSecrets>
+    // Question-level Secrets
+    pub question_secrets: Vec<QuestionSecrets>
 }
 
 
 impl PollSecrets {
-    pub fn derive(pmk: &PollMasterKey) -> Self {
+    pub fn derive(pmk: &PollMasterKey, num_questions: usize, num_planes: usize) -> Self {
         assert!(pmk.0.len() == CSPRNGSeed::SIZE,
             "Poll Master Key not a valid seed length.");
         let pmk_seed = CSPRNGSeed::from_vec(&pmk.0);
@@ -49,27 +58,53 @@ impl PollSecrets {
         let mut secrets = PollSecrets::new();
         let mut pmkrng = CSPRNG::from_csprng_seed(pmk_seed);
         // Top-level Secrets
-        secrets.votecode_root = CSPRNGSeed::next_seed(&mut pmkrng);
+        let votecode_root = CSPRNGSeed::next_seed(&mut pmkrng);
         secrets.decoy_root = CSPRNGSeed::next_seed(&mut pmkrng);
         secrets.summands_root = CSPRNGSeed::next_seed(&mut pmkrng);
-        secrets.planes_root = CSPRNGSeed::next_seed(&mut pmkrng);
+        let planes_root = CSPRNGSeed::next_seed(&mut pmkrng);
         pmkrng.fill_bytes(&mut secrets.summands_key.0);
-        // Plane-level Secrets
-        let mut planesrng = CSPRNG::from_csprng_seed(secrets.planes_root);
-        secrets.plane_secrets = (0..NUMBER_OF_PLANES).into_iter()
-            .map(|_| PlaneSecrets::derive(CSPRNGSeed::next_seed(&mut planesrng)))
-            .collect();
+        secrets.questions_root = CSPRNGSeed::next_seed(&mut pmkrng);
+        secrets.roster_root = CSPRNGSeed::next_seed(&mut pmkrng);
+        secrets.mailing_root = CSPRNGSeed::next_seed(&mut pmkrng);
+        // Question-level Secrets
+        // The first question keeps the roots of a single-question poll.
+        let mut questionsrng = CSPRNG::from_csprng_seed(secrets.questions_root);
+        secrets.question_secrets = (0..num_questions).into_iter()
+            .map(|n| {
+                if n == 0 { QuestionSecrets::derive(votecode_root, planes_root, num_planes) }
+                else {
+                    let votecode_root = CSPRNGSeed::next_seed(&mut questionsrng);
+                    let planes_root = CSPRNGSeed::next_seed(&mut questionsrng);
+                    QuestionSecrets::derive(votecode_root, planes_root, num_planes)
+                }
+            }).collect();
         secrets
     }
 
     fn new() -> Self {
         PollSecrets {
-            votecode_root: CSPRNGSeed::DEFAULT,
             decoy_root: CSPRNGSeed::DEFAULT,
             summands_root: CSPRNGSeed::DEFAULT,
-            planes_root: CSPRNGSeed::DEFAULT,
+            questions_root: CSPRNGSeed::DEFAULT,
+            roster_root: CSPRNGSeed::DEFAULT,
+            mailing_root: CSPRNGSeed::DEFAULT,
             summands_key: AEADKey(Default::default()),
-            plane_secrets: Vec::new()
+            question_secrets: Vec::new()
+        }
+    }
+}
+
+impl QuestionSecrets {
+    pub fn derive(votecode_root: CSPRNGSeed, planes_root: CSPRNGSeed, num_planes: usize) -> Self {
+        // Plane-level Secrets
+        // Planes are drawn in order, so adding planes keeps the existing ones.
+        let mut planesrng = CSPRNG::from_csprng_seed(planes_root);
+        QuestionSecrets {
+            votecode_root: votecode_root,
+            planes_root: planes_root,
+            plane_secrets: (0..num_planes).into_iter()
+                .map(|_| PlaneSecrets::derive(CSPRNGSeed::next_seed(&mut planesrng)))
+                .collect()
         }
     }
 }
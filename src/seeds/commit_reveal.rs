@@ -0,0 +1,241 @@
+//! # Commit-Reveal Seeds
+//!
+//! Each participant (a trustee or an observer) draws a secret 32-byte
+//! contribution and publishes a commitment to it, signed with their
+//! long-lived Ed25519 participant key (see `seed-keygen`):
+//!
+//! ```text
+//! commitment = SHA-256( "seventh-estate-seed-v1" || 0x00 || purpose || 0x00
+//!                       || participant || 0x00 || contribution )
+//! ```
+//!
+//! Once every commitment is published, each participant reveals the
+//! contribution. The revealed contributions, in participant order, are the
+//! inputs of the seed transcript, which also keeps the commitments. The
+//! seed is unpredictable as long as one participant kept their
+//! contribution secret until all commitments were published.
+//!
+//! The signature covers the construction, purpose, participant and
+//! commitment. A commitment carries its public key, which only shows the
+//! file was not altered; `combine_reveals` checks every key against the
+//! participant keys, published before the ceremony, and fails on a
+//! commitment from a participant who is not listed or is listed with
+//! another key.
+
+use std::collections::{BTreeMap, BTreeSet};
+use sha2::{Sha256, Digest};
+use crate::cryptography::*;
+use super::*;
+
+/// Size in bytes of a contribution.
+pub const CONTRIBUTION_SIZE: usize = 32;
+
+
+/// A published, signed commitment to a contribution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeedCommitment {
+    pub participant: String,
+    pub purpose: String,
+    pub commitment: String,
+    pub public_key: Base64String,
+    pub signature: String
+}
+
+/// The Ed25519 key pair of a participant, kept private.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParticipantKey {
+    pub participant: String,
+    pub signing_key: Base64String,
+    pub public_key: Base64String
+}
+
+/// What a participant keeps private until the reveal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeedCommitmentSecret {
+    pub participant: String,
+    pub purpose: String,
+    pub contribution: String
+}
+
+/// A revealed contribution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeedReveal {
+    pub participant: String,
+    pub purpose: String,
+    pub contribution: String
+}
+
+
+/// Commitment, in hex, to a contribution.
+pub fn contribution_commitment(purpose: &str, participant: &str, contribution: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.input(SEED_CONSTRUCTION.as_bytes());
+    hasher.input(&[0u8]);
+    hasher.input(purpose.as_bytes());
+    hasher.input(&[0u8]);
+    hasher.input(participant.as_bytes());
+    hasher.input(&[0u8]);
+    hasher.input(contribution);
+    hex::encode(hasher.result())
+}
+
+impl ParticipantKey {
+    /// Draw a key pair for a participant.
+    pub fn new(participant: &str) -> Result<Self> {
+        let (signing_key, public_key) = new_signing_key()?;
+        Ok(ParticipantKey {
+            participant: participant.to_owned(),
+            signing_key: signing_key,
+            public_key: public_key
+        })
+    }
+}
+
+impl SeedCommitmentSecret {
+    /// Draw a contribution and sign the commitment to it with the key of
+    /// the participant.
+    pub fn new(key: &ParticipantKey, purpose: &str) -> Result<(Self, SeedCommitment)> {
+        let participant = key.participant.as_str();
+        let mut contribution = [0u8; CONTRIBUTION_SIZE];
+        getrandom::getrandom(&mut contribution)?;
+        let mut commitment = SeedCommitment {
+            participant: participant.to_owned(),
+            purpose: purpose.to_owned(),
+            commitment: contribution_commitment(purpose, participant, &contribution),
+            public_key: key.public_key.clone(),
+            signature: String::new()
+        };
+        let (_, signature) = sign(&key.signing_key, commitment.message())?;
+        commitment.signature = base64::encode(&signature);
+        commitment.check_signature()?;
+        let secret = SeedCommitmentSecret {
+            participant: participant.to_owned(),
+            purpose: purpose.to_owned(),
+            contribution: hex::encode(&contribution)
+        };
+        Ok((secret, commitment))
+    }
+
+    pub fn reveal(self: &Self) -> SeedReveal {
+        SeedReveal {
+            participant: self.participant.clone(),
+            purpose: self.purpose.clone(),
+            contribution: self.contribution.clone()
+        }
+    }
+}
+
+impl SeedCommitment {
+    /// The signed message.
+    fn message(self: &Self) -> Vec<u8> {
+        format!("{}\0{}\0{}\0{}", SEED_CONSTRUCTION, self.purpose, self.participant, self.commitment).into_bytes()
+    }
+
+    /// Check the signature against the public key of the commitment.
+    pub fn check_signature(self: &Self) -> Result<()> {
+        let signature = base64::decode(&self.signature)
+            .map_err(|_| format!("Signature of the commitment of {} is not Base64.", self.participant))?;
+        verify_signature(&self.public_key, &self.message(), &signature)
+            .map_err(|error| format!("Commitment of {}: {}", self.participant, error))?;
+        Ok(())
+    }
+
+    /// Check that a contribution, in hex, is the committed one.
+    pub fn check_contribution(self: &Self, contribution: &str) -> Result<()> {
+        let contribution = hex::decode(contribution)?;
+        if contribution.len() != CONTRIBUTION_SIZE {
+            return Err(From::from(format!("Contribution of {} must be {} bytes long.", self.participant, CONTRIBUTION_SIZE)));
+        }
+        if contribution_commitment(&self.purpose, &self.participant, &contribution) != self.commitment {
+            return Err(From::from(format!("Contribution of {} does not match its commitment.", self.participant)));
+        }
+        Ok(())
+    }
+}
+
+
+/// Check that the signed commitments come from exactly the listed
+/// participants, each signed with the listed public key.
+pub fn check_participant_keys(commitments: &Vec<SeedCommitment>, participant_keys: &BTreeMap<String, Base64String>) -> Result<()> {
+    for commitment in commitments.iter() {
+        match participant_keys.get(&commitment.participant) {
+            Some(public_key) if public_key.0 == commitment.public_key.0 => commitment.check_signature()?,
+            Some(_) => return Err(From::from(format!("Commitment of {} is not signed with their listed key.", commitment.participant))),
+            None => return Err(From::from(format!("{} is not a listed participant.", commitment.participant)))
+        }
+    }
+    if let Some(participant) = participant_keys.keys()
+        .find(|participant| !commitments.iter().any(|commitment| &commitment.participant == *participant)) {
+        return Err(From::from(format!("No commitment from {}.", participant)));
+    }
+    Ok(())
+}
+
+/// Check the signed commitments of a purpose, one per participant, and
+/// that the contribution inputs are exactly their revealed contributions:
+/// one per committed participant, in participant order as
+/// `combine_reveals` orders them.
+pub fn verify_contributions(purpose: &str, inputs: &Vec<SeedInput>, commitments: &Vec<SeedCommitment>) -> Result<()> {
+    let mut participants = BTreeSet::new();
+    for commitment in commitments.iter() {
+        if commitment.purpose != purpose {
+            return Err(From::from(format!("Commitment of {} is for {}, not {}.", commitment.participant, commitment.purpose, purpose)));
+        }
+        if !participants.insert(commitment.participant.as_str()) {
+            return Err(From::from(format!("More than one commitment from {}.", commitment.participant)));
+        }
+        commitment.check_signature()?;
+    }
+
+    let contributions: Vec<&SeedInput> = inputs.iter()
+        .filter(|input| input.kind == SeedInputKind::Contribution)
+        .collect();
+    let sources: Vec<&str> = contributions.iter().map(|input| input.source.as_str()).collect();
+    let participants: Vec<&str> = participants.into_iter().collect();
+    if sources != participants {
+        return Err(From::from(format!("Contributions from {} do not match the commitments from {}, one each in participant order.",
+            sources.join(", "), participants.join(", "))));
+    }
+    for contribution in contributions.iter() {
+        let commitment = commitments.iter()
+            .find(|commitment| commitment.participant == contribution.source)
+            .ok_or_else(|| format!("No commitment from {}.", contribution.source))?;
+        commitment.check_contribution(&contribution.content)?;
+    }
+    Ok(())
+}
+
+/// Check every commitment against the participant keys and every reveal
+/// against its commitment, and derive the seed transcript. Every listed
+/// participant must have committed and revealed.
+pub fn combine_reveals(purpose: &str, commitments: Vec<SeedCommitment>, reveals: Vec<SeedReveal>, participant_keys: &BTreeMap<String, Base64String>) -> Result<SeedTranscript> {
+    if commitments.is_empty() {
+        return Err(From::from("At least one commitment is needed to combine a seed."));
+    }
+    check_participant_keys(&commitments, participant_keys)?;
+    if let Some(reveal) = reveals.iter().find(|reveal| reveal.purpose != purpose) {
+        return Err(From::from(format!("Reveal of {} is for {}, not {}.", reveal.participant, reveal.purpose, purpose)));
+    }
+    for commitment in commitments.iter() {
+        let count = reveals.iter()
+            .filter(|reveal| reveal.participant == commitment.participant)
+            .count();
+        if count != 1 {
+            return Err(From::from(format!("{} reveals from {}, expected one.", count, commitment.participant)));
+        }
+    }
+
+    let mut inputs = reveals.into_iter()
+        .map(|reveal| SeedInput {
+            kind: SeedInputKind::Contribution,
+            source: reveal.participant,
+            content: reveal.contribution
+        })
+        .collect::<Vec<SeedInput>>();
+    inputs.sort_by(|a, b| a.source.cmp(&b.source));
+    verify_contributions(purpose, &inputs, &commitments)?;
+
+    let mut transcript = SeedTranscript::derive(purpose, inputs);
+    transcript.commitments = commitments;
+    Ok(transcript)
+}
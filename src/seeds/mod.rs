@@ -0,0 +1,17 @@
+//! # Public Seeds
+//!
+//! The seeds of step 2 (drawn summands) and step 7 (audited columns) must
+//! come from randomness nobody controls. `seeds` derives them from public
+//! inputs (randomness beacon pulses, lottery numbers, closing index
+//! values or block hashes) and keeps a transcript from which anyone can
+//! re-derive the seed. Trustees and observers can also contribute to a
+//! seed by commit-reveal, so no single party chooses it.
+
+use serde::{Serialize, Deserialize};
+use crate::Result;
+
+pub mod transcript;
+pub use transcript::*;
+
+pub mod commit_reveal;
+pub use commit_reveal::*;
@@ -0,0 +1,182 @@
+//! # Seed Transcript
+//!
+//! A seed is derived with a fixed, versioned construction:
+//!
+//! ```text
+//! SHA-256( "seventh-estate-seed-v1" || 0x00 || purpose || 0x00 || u64(n)
+//!          || kind_1 || 0x00 || u64(len_1) || content_1
+//!          || ...
+//!          || kind_n || 0x00 || u64(len_n) || content_n )
+//! ```
+//!
+//! where `u64` is big-endian and each content is the input file, byte for
+//! byte. The transcript records the construction, the purpose, every
+//! input with its content and the seed, so the seed can be re-derived
+//! from the transcript alone.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use sha2::{Sha256, Digest};
+use super::*;
+
+pub const SEED_CONSTRUCTION: &str = "seventh-estate-seed-v1";
+
+/// Purpose of the seed of step 2.
+pub const DRAWN_SUMMANDS_PURPOSE: &str = "drawn_summands";
+/// Purpose of the seed of step 7.
+pub const AUDITED_COLUMNS_PURPOSE: &str = "audited_columns";
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SeedInputKind {
+    #[serde(rename = "beacon")]
+    Beacon,
+    #[serde(rename = "lottery")]
+    Lottery,
+    #[serde(rename = "index")]
+    Index,
+    #[serde(rename = "block_hash")]
+    BlockHash,
+    #[serde(rename = "contribution")]
+    Contribution
+}
+
+/// A public input: its kind, where it was taken from and its content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeedInput {
+    pub kind: SeedInputKind,
+    pub source: String,
+    pub content: String
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeedTranscript {
+    pub construction: String,
+    pub purpose: String,
+    pub inputs: Vec<SeedInput>,
+    pub seed: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commitments: Vec<SeedCommitment>
+}
+
+
+impl SeedInputKind {
+    pub fn name(self: &Self) -> &'static str {
+        match self {
+            SeedInputKind::Beacon => "beacon",
+            SeedInputKind::Lottery => "lottery",
+            SeedInputKind::Index => "index",
+            SeedInputKind::BlockHash => "block_hash",
+            SeedInputKind::Contribution => "contribution"
+        }
+    }
+
+    /// Kind of a public input file. Contributions only come from reveals.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "beacon" => Some(SeedInputKind::Beacon),
+            "lottery" => Some(SeedInputKind::Lottery),
+            "index" => Some(SeedInputKind::Index),
+            "block_hash" => Some(SeedInputKind::BlockHash),
+            _ => None
+        }
+    }
+}
+
+impl SeedInput {
+    /// Read an input from a file. The content is kept byte for byte.
+    pub fn from_file(kind: SeedInputKind, path: &str) -> Result<Self> {
+        let mut content = String::new();
+        File::open(Path::new(path))?.read_to_string(&mut content)?;
+        Ok(SeedInput {
+            kind: kind,
+            source: path.to_owned(),
+            content: content
+        })
+    }
+
+    /// Parse a `kind=FILE` argument and read the file.
+    pub fn from_argument(argument: &str) -> Result<Self> {
+        let mut parts = argument.splitn(2, '=');
+        let kind = parts.next().and_then(SeedInputKind::from_name);
+        match (kind, parts.next()) {
+            (Some(kind), Some(path)) => Self::from_file(kind, path),
+            _ => Err(From::from(format!("Seed input \"{}\" is not of the form kind=FILE, with kind one of beacon, lottery, index or block_hash.", argument)))
+        }
+    }
+}
+
+/// Derive a seed, in hex, from public inputs.
+///
+/// # Examples
+///
+/// ```
+/// use seventh_estate::seeds::*;
+///
+/// let inputs = vec![SeedInput {
+///     kind: SeedInputKind::Lottery,
+///     source: "lottery.txt".to_owned(),
+///     content: "4 8 15 16 23 42\n".to_owned()
+/// }];
+/// let seed = derive_seed(DRAWN_SUMMANDS_PURPOSE, &inputs);
+/// assert_eq!(64, seed.len());
+/// assert_ne!(seed, derive_seed(AUDITED_COLUMNS_PURPOSE, &inputs));
+/// ```
+pub fn derive_seed(purpose: &str, inputs: &Vec<SeedInput>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.input(SEED_CONSTRUCTION.as_bytes());
+    hasher.input(&[0u8]);
+    hasher.input(purpose.as_bytes());
+    hasher.input(&[0u8]);
+    hasher.input(&(inputs.len() as u64).to_be_bytes());
+    inputs.iter()
+        .for_each(|input| {
+            hasher.input(input.kind.name().as_bytes());
+            hasher.input(&[0u8]);
+            hasher.input(&(input.content.len() as u64).to_be_bytes());
+            hasher.input(input.content.as_bytes());
+        });
+    hex::encode(hasher.result())
+}
+
+impl SeedTranscript {
+    pub fn derive(purpose: &str, inputs: Vec<SeedInput>) -> Self {
+        assert!(!inputs.is_empty(),
+            "At least one public input is needed to derive a seed.");
+        SeedTranscript {
+            construction: SEED_CONSTRUCTION.to_owned(),
+            purpose: purpose.to_owned(),
+            seed: derive_seed(purpose, &inputs),
+            inputs: inputs,
+            commitments: vec![]
+        }
+    }
+
+    pub fn from_file(path: &dyn AsRef<Path>) -> Result<Self> {
+        let transcript: SeedTranscript = serde_yaml::from_reader(File::open(path)?)?;
+        Ok(transcript)
+    }
+
+    pub fn to_file(self: &Self, path: &dyn AsRef<Path>) -> Result<()> {
+        serde_yaml::to_writer(File::create(path)?, self)?;
+        Ok(())
+    }
+
+    /// Re-derive the seed from the recorded inputs, and check any revealed
+    /// contributions against their signed commitments.
+    pub fn verify(self: &Self) -> Result<()> {
+        if self.construction != SEED_CONSTRUCTION {
+            return Err(From::from(format!("Unknown seed construction \"{}\".", self.construction)));
+        }
+        if derive_seed(&self.purpose, &self.inputs) != self.seed {
+            return Err(From::from("Seed does not match the inputs of its transcript."));
+        }
+        let has_contributions = self.inputs.iter()
+            .any(|input| input.kind == SeedInputKind::Contribution);
+        if has_contributions || !self.commitments.is_empty() {
+            verify_contributions(&self.purpose, &self.inputs, &self.commitments)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+//! # Column Audit Soundness
+//!
+//! `soundness` estimates how likely tampering with the column planes
+//! survives the random column audit of step 7.
+//!
+//! The audit flips a fair coin for each plane and opens either column 1
+//! or column 3. Altering a row breaks its link to exactly one of these
+//! columns, so the audit catches the altered row if the coin opens that
+//! column. A changed vote must be carried by every plane for the planes to
+//! agree on the tally, so each plane faces its own coin.
+//!
+//! * A careful adversary breaks the same column for all altered rows of a
+//!   plane, and survives each plane with probability 1/2.
+//! * An adversary that cannot choose (e.g. a faulty or corrupted printer)
+//!   breaks a random column for each row, and survives each plane only if
+//!   all `k` altered rows break the column that stays closed, with
+//!   probability 1/2^k.
+
+use crate::Result;
+
+/// Probability that a careful adversary altering any number of rows
+/// survives the audit of `num_planes` planes.
+///
+/// # Examples
+///
+/// ```
+/// use seventh_estate::soundness::*;
+///
+/// assert_eq!(0.125, worst_case_survival(3));
+/// ```
+pub fn worst_case_survival(num_planes: usize) -> f64 {
+    0.5f64.powi(num_planes as i32)
+}
+
+/// Probability that `num_rows` rows altered at random columns survive
+/// the audit of `num_planes` planes.
+pub fn random_tampering_survival(num_planes: usize, num_rows: usize) -> f64 {
+    0.5f64.powf((num_planes * num_rows) as f64)
+}
+
+/// Smallest number of planes bringing the worst case survival
+/// probability down to `target`.
+///
+/// # Examples
+///
+/// ```
+/// use seventh_estate::soundness::*;
+///
+/// assert_eq!(20, planes_for_target(1e-6).unwrap());
+/// assert!(planes_for_target(2.0).is_err());
+/// ```
+pub fn planes_for_target(target: f64) -> Result<usize> {
+    if !(0.0 < target && target < 1.0) {
+        return Err(From::from("Target probability must be between 0 and 1."));
+    }
+    let planes = (-target.log2()).ceil() as usize;
+    // Guard against rounding of the logarithm.
+    Ok(if worst_case_survival(planes) > target { planes + 1 } else { planes })
+}
@@ -21,16 +21,18 @@ pub fn blockchain_audit(pollconf_filename: &str, xxn_filename: &str) -> Result <
         "Summands must be drawn to generate voters and print content for public audit.");
 
     // Derive the poll secrets.
-    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key);
+    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key, pollconf.num_questions(), pollconf.num_planes);
 
-    // Generate the Ballots.
+    // Regenerate the ballots of each question.
     let serials: Vec<BallotSerial> = (0..pollconf.num_ballots).collect();
-    let votecodes: Vec<VoteCode> = generate_votecodes(
-        poll_secrets.votecode_root,
-        2 * pollconf.num_ballots);
-
-    // Regenerate ballots
-    let ballots = generate_ballots(&serials, &votecodes);
+    let ballots: Vec<Vec<Ballot>> = (0..pollconf.num_questions()).into_iter()
+        .map(|q| {
+            let votecodes: Vec<VoteCode> = generate_votecodes(
+                poll_secrets.question_secrets[q].votecode_root,
+                pollconf.num_rows(q),
+                &pollconf.votecode_format);
+            generate_ballots(&serials, &votecodes, pollconf.num_options(q))
+        }).collect();
     let decoys = get_decoys(&pollconf, poll_master_key)?;
     audit_votes(ballots, pollconf, xxn_filename, decoys)
 }
@@ -46,16 +48,18 @@ pub fn blockchain_audit_run (xxn_filename: &str, secured_poll_configuration: Sec
         "Summands must be drawn to generate voters and print content for public audit.");
 
     // Derive the poll secrets.
-    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key);
+    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key, pollconf.num_questions(), pollconf.num_planes);
 
-    // Generate the Ballots.
+    // Regenerate the ballots of each question.
     let serials: Vec<BallotSerial> = (0..pollconf.num_ballots).collect();
-    let votecodes: Vec<VoteCode> = generate_votecodes(
-        poll_secrets.votecode_root,
-        2 * pollconf.num_ballots);
-
-    // Regenerate ballots
-    let ballots = generate_ballots(&serials, &votecodes);
+    let ballots: Vec<Vec<Ballot>> = (0..pollconf.num_questions()).into_iter()
+        .map(|q| {
+            let votecodes: Vec<VoteCode> = generate_votecodes(
+                poll_secrets.question_secrets[q].votecode_root,
+                pollconf.num_rows(q),
+                &pollconf.votecode_format);
+            generate_ballots(&serials, &votecodes, pollconf.num_options(q))
+        }).collect();
     let decoys = get_decoys(&pollconf, poll_master_key)?;
     audit_votes(ballots, pollconf, xxn_filename, decoys)
 }
@@ -63,14 +67,20 @@ pub fn blockchain_audit_run (xxn_filename: &str, secured_poll_configuration: Sec
 
 pub fn get_decoys(pollconf: &PollConfiguration, poll_master_key: PollMasterKey) -> Result<Vec<usize>>{
     // Derive the poll secrets.
-    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key);
+    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key, pollconf.num_questions(), pollconf.num_planes);
+
+    // The decoys are shared by all questions, so the first question is enough.
+    let q = 0;
 
     // Post the Fully Audited Column Planes.
     let column_planes: Vec<Plane> = generate_column_planes(
         &poll_secrets,
-        NUMBER_OF_PLANES,
-        2 * pollconf.num_ballots,
-        pollconf.num_decoys)?;
+        q,
+        pollconf.num_planes,
+        pollconf.num_ballots,
+        pollconf.num_options(q),
+        pollconf.num_decoys,
+        &pollconf.votecode_format)?;
 
     // Re-construct the audited ballots.
     let audited_ballots: Vec<BallotSerial> = {
@@ -83,8 +93,9 @@ pub fn get_decoys(pollconf: &PollConfiguration, poll_master_key: PollMasterKey)
     let votes: Vec<VoteCode> = pollconf.votes.clone().unwrap();
     let marked_rows: Vec<usize> = {
         let votecodes: Vec<VoteCode> = generate_votecodes(
-            poll_secrets.votecode_root,
-            2 * pollconf.num_ballots);
+            poll_secrets.question_secrets[q].votecode_root,
+            pollconf.num_rows(q),
+            &pollconf.votecode_format);
         votecodes.iter().enumerate()
             .filter_map(|(n, vc)| {
                 debug!("{:?}", vc);
@@ -97,15 +108,15 @@ pub fn get_decoys(pollconf: &PollConfiguration, poll_master_key: PollMasterKey)
     let n = 0;
     let decoys: Vec<usize> = match column_planes.get(n) {
         Some(plane) => {
-            // Decrypt col1 (serial) and col3 (For|Against|Decoy)
-            let psecrets = poll_secrets.plane_secrets[n].resolve(plane.len());
+            // Decrypt col1 (serial) and col3 (Option N|Decoy)
+            let psecrets = poll_secrets.question_secrets[q].plane_secrets[n].resolve(plane.len());
             let filter = PlaneFilter::from(&psecrets.col1_keys, &psecrets.col3_keys)
-                .decrypt_serials(&audited_ballots)
+                .decrypt_serials(&audited_ballots, pollconf.num_options(q))
                 .decrypt_column(1)
                 .decrypt_column(3);
             
             // Get only Decoys
-            let permuted_plane = plane.mark_rows(&marked_rows).decrypt(&filter).permute(&psecrets.permutation);
+            let permuted_plane = plane.mark_rows(&marked_rows).decrypt(&filter)?.permute(&psecrets.permutation);
             let decoys: Vec<usize> = permuted_plane.rows.iter().filter_map (| row | match &row.col3 {
                 Column3Entry::Entry(opt) => 
                     if opt == "Decoy" { Some(row.col1.clone()) }
@@ -113,10 +124,8 @@ pub fn get_decoys(pollconf: &PollConfiguration, poll_master_key: PollMasterKey)
                 _ => None,
             }) // Get Serial codes
             .filter_map (| row | match row {
-                Column1Entry::Entry(entry) => { Some(entry) },
+                Column1Entry::Entry(entry) => { Some(entry.serial) },
                 _ => None
-            }).map(| serial | {
-                *&serial[..2].parse::<usize>().unwrap()
             }).collect();
             decoys
         },
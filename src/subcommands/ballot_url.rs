@@ -0,0 +1,16 @@
+//! # Command: Decode Ballot URL
+//!
+//! `ballot_url_serial` reads the ballot serial of a URL printed as a QR
+//! code on a ballot, as the vote server does when a voter scans it.
+
+use super::*;
+
+
+pub fn ballot_url_serial(url: &str, voting_url: Option<&str>) -> Result<()> {
+    let serial = match voting_url {
+        Some(voting_url) => VotingUrl { url: voting_url.to_owned(), short_url: None }.decode(url)?,
+        None => decode_ballot_url(url)?
+    };
+    println!("{}", serial);
+    Ok(())
+}
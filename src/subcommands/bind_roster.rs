@@ -3,11 +3,24 @@
 //! `bind_roster` binds a roster file to a secured poll configuration.
 //! The purpose of this binding is to make the voter roster file
 //! immutable without corrupting the entire poll.
+//!
+//! The roster file is read as described by an optional import profile
+//! (see `voter_roster::import_profile`), or as a CSV with the `VoterInfo`
+//! field names as headers. With `check`, the roster is normalized and
+//! checked first (see `voter_roster::check`); the report is written to the
+//! given report file, kept out of the published poll data directory as it
+//! lists voters, and a roster with errors is not bound.
+//!
+//! With a stratified poll, the ballots are allocated between the strata
+//! of the roster when it is bound.
+//!
+//! The roster is written to an encrypted sidecar file in the poll data
+//! directory (see `voter_roster::sidecar`), referenced from the poll
+//! configuration by its hash, so later steps can stream it.
 
 use super::*;
 
-
-pub fn bind_roster(pollconf_filename: &str, roster_filename: &str, disable_privacy: bool, force: bool) -> Result<()> {
+pub fn bind_roster(pollconf_filename: &str, roster_filename: &str, profile_filename: Option<&str>, check: bool, report_filename: &str, disable_privacy: bool, force: bool) -> Result<()> {
     let pollconf_path = Path::new(pollconf_filename);
     let roster_path = Path::new(roster_filename);
 
@@ -30,12 +43,34 @@ pub fn bind_roster(pollconf_filename: &str, roster_filename: &str, disable_priva
         "Voter roster already bound. To re-bind, pass --force.");
 
     // Read roster file.
-    let roster = VoterRoster::from_file(&roster_path)?;
-    let serialized_roster = serde_yaml::to_string(&roster)?;
-    let roster64 = base64::encode(&serialized_roster);
-    // Bind the roster.
-    pollconf.voter_roster = Some(Base64String(roster64));
+    let profile: RosterImportProfile = match profile_filename {
+        Some(filename) => RosterImportProfile::from_file(&Path::new(filename))?,
+        None => RosterImportProfile::default()
+    };
+    let roster = VoterRoster::from_file_with_profile(&roster_path, &profile)?;
+    let roster = match check {
+        true => checked_roster(&roster, report_filename)?,
+        false => roster
+    };
+    // Bind the roster as an encrypted sidecar in the poll data directory.
+    let datadir_path = ensure_poll_data_directory_exists(&secured_poll_configuration, &aead_pmk)?;
+    let sidecar_path = {
+        let mut pathbuf = PathBuf::new();
+        pathbuf.push(&datadir_path);
+        pathbuf.push("voter_roster");
+        pathbuf.set_extension("sidecar");
+        pathbuf.into_boxed_path()
+    };
+    pollconf.voter_roster = None;
+    pollconf.voter_roster_sidecar = Some(write_roster_sidecar(&roster, &aead_pmk, &sidecar_path.display().to_string())?);
+    assert!(pollconf.num_ballots <= roster.len(),
+        "Voter roster must have at least one voter per ballot.");
     pollconf.voter_roster_size = roster.len();
+    pollconf.strata = match &pollconf.stratification {
+        Some(stratification) => crate::voter_selection::stratify(&roster, stratification, pollconf.num_ballots)?,
+        None => Vec::new()
+    };
+    pollconf.voter_roster_hash = Some(roster.hash());
     pollconf.voter_privacy = !disable_privacy;
     pollconf.poll_state.roster_committed = true;
     // Re-encrypt the poll configuration.
@@ -53,7 +88,7 @@ pub fn bind_roster(pollconf_filename: &str, roster_filename: &str, disable_priva
     Ok(())
 }
 
-pub fn bind_roster_run(pollconf_filename: &str, mut secured_poll_configuration: SecuredPollConfiguration, aead_pmk: AEADKey, roster_filename: &str, disable_privacy: bool) -> Result<SecuredPollConfiguration> {
+pub fn bind_roster_run(pollconf_filename: &str, mut secured_poll_configuration: SecuredPollConfiguration, aead_pmk: AEADKey, roster_filename: &str, profile_filename: Option<&str>, check: bool, report_filename: &str, disable_privacy: bool) -> Result<SecuredPollConfiguration> {
     let pollconf_path = Path::new(pollconf_filename);
     let roster_path = Path::new(roster_filename);
 
@@ -71,12 +106,34 @@ pub fn bind_roster_run(pollconf_filename: &str, mut secured_poll_configuration:
         "Voter roster already bound.");
 
     // Read roster file.
-    let roster = VoterRoster::from_file(&roster_path)?;
-    let serialized_roster = serde_yaml::to_string(&roster)?;
-    let roster64 = base64::encode(&serialized_roster);
-    // Bind the roster.
-    pollconf.voter_roster = Some(Base64String(roster64));
+    let profile: RosterImportProfile = match profile_filename {
+        Some(filename) => RosterImportProfile::from_file(&Path::new(filename))?,
+        None => RosterImportProfile::default()
+    };
+    let roster = VoterRoster::from_file_with_profile(&roster_path, &profile)?;
+    let roster = match check {
+        true => checked_roster(&roster, report_filename)?,
+        false => roster
+    };
+    // Bind the roster as an encrypted sidecar in the poll data directory.
+    let datadir_path = ensure_poll_data_directory_exists(&secured_poll_configuration, &aead_pmk)?;
+    let sidecar_path = {
+        let mut pathbuf = PathBuf::new();
+        pathbuf.push(&datadir_path);
+        pathbuf.push("voter_roster");
+        pathbuf.set_extension("sidecar");
+        pathbuf.into_boxed_path()
+    };
+    pollconf.voter_roster = None;
+    pollconf.voter_roster_sidecar = Some(write_roster_sidecar(&roster, &aead_pmk, &sidecar_path.display().to_string())?);
+    assert!(pollconf.num_ballots <= roster.len(),
+        "Voter roster must have at least one voter per ballot.");
     pollconf.voter_roster_size = roster.len();
+    pollconf.strata = match &pollconf.stratification {
+        Some(stratification) => crate::voter_selection::stratify(&roster, stratification, pollconf.num_ballots)?,
+        None => Vec::new()
+    };
+    pollconf.voter_roster_hash = Some(roster.hash());
     pollconf.voter_privacy = !disable_privacy;
     pollconf.poll_state.roster_committed = true;
     // Re-encrypt the poll configuration.
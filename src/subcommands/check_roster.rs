@@ -0,0 +1,51 @@
+//! # Command: Check Roster
+//!
+//! `check_roster_file` normalizes a voter roster file, checks it for
+//! missing fields, malformed ZIP codes and duplicate voters, and writes
+//! the report to a YAML file. `bind-roster --check` runs the same check
+//! and refuses to bind a roster with errors.
+
+use super::*;
+
+
+pub fn check_roster_file(roster_filename: &str, profile_filename: Option<&str>, report_filename: &str) -> Result<()> {
+    let roster_path = Path::new(roster_filename);
+
+    // Read roster file.
+    let profile: RosterImportProfile = match profile_filename {
+        Some(filename) => RosterImportProfile::from_file(&Path::new(filename))?,
+        None => RosterImportProfile::default()
+    };
+    let roster = VoterRoster::from_file_with_profile(&roster_path, &profile)?;
+
+    // Check the roster.
+    checked_roster(&roster, report_filename)?;
+    Ok(())
+}
+
+
+/// Normalize and check a roster, write the report to a file and return
+/// the normalized roster, unless it has errors.
+pub fn checked_roster(roster: &VoterRoster, report_filename: &str) -> Result<VoterRoster> {
+    let (normalized, report) = check_roster(roster);
+    report.to_file(&Path::new(report_filename))?;
+    print_roster_report(&report);
+
+    if report.has_errors() {
+        return Err(From::from(format!("Voter roster failed the check. See {}.", report_filename)));
+    }
+    Ok(normalized)
+}
+
+
+/// Print the statistics of a roster check.
+pub fn print_roster_report(report: &RosterCheckReport) -> () {
+    println!("Voters: {}", report.num_records);
+    println!("Normalized records: {}", report.num_normalized);
+    println!("Missing fields: {}", report.missing_fields.len());
+    println!("Malformed ZIP codes: {}", report.malformed_zip_codes.len());
+    println!("Exact duplicates: {}", report.exact_duplicates.len());
+    println!("Possible duplicates: {}", report.fuzzy_duplicates.len());
+    println!("Households with several voters: {}", report.num_households);
+    println!("Roster hash: {}", report.roster_hash);
+}
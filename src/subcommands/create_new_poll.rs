@@ -16,6 +16,18 @@ pub fn create_new_poll(pollconf_filename: &str) -> Result<()> {
         let pollconf_file = File::open(pollconf_path)?;
         serde_yaml::from_reader(pollconf_file)?
     };
+    assert!(!new_poll_configuration.questions.is_empty(),
+        "A poll must ask at least one question.");
+    assert!(new_poll_configuration.questions.iter().all(|question| question.num_options() >= 2),
+        "Each poll question must offer at least two options.");
+    assert!(new_poll_configuration.questions.iter().all(|question| question.translations_complete()),
+        "Each translation of a question must label every option.");
+    assert!(new_poll_configuration.votecode_format.num_groups > 0 && new_poll_configuration.votecode_format.group_size > 0,
+        "Vote codes must have at least one group of one symbol.");
+    assert!(new_poll_configuration.votecode_format.num_votecodes().is_some(),
+        "Vote code format has too many symbols.");
+    assert!(new_poll_configuration.num_planes > 0,
+        "A poll must have at least one column plane.");
 
     // Generate Master Key and Shares.
     let num_trustees: usize = new_poll_configuration.poll_trustees.len();
@@ -38,6 +50,14 @@ pub fn create_new_poll(pollconf_filename: &str) -> Result<()> {
         })
         .collect::<Vec<PollConfigurationTrustee>>();
 
+    // Read the ballot template.
+    let ballot_template = read_ballot_template(pollconf_path, &new_poll_configuration)?;
+    if let Some(voting_url) = &new_poll_configuration.voting_url {
+        voting_url.check()?;
+        assert!(ballot_template.qr_code.is_some(),
+            "A voting URL needs a qr_code position in the ballot template.");
+    }
+
     // Generate signing key/certificate.
     let (private_key, public_key): (Base64String, Base64String) = new_signing_key()?;
 
@@ -47,18 +67,26 @@ pub fn create_new_poll(pollconf_filename: &str) -> Result<()> {
         signing_key: private_key,
         num_ballots: new_poll_configuration.num_ballots,
         num_decoys: new_poll_configuration.num_decoys,
-        question: new_poll_configuration.question,
-        option1: new_poll_configuration.option1,
-        option2: new_poll_configuration.option2,
+        num_planes: new_poll_configuration.num_planes,
+        questions: new_poll_configuration.questions,
+        votecode_format: new_poll_configuration.votecode_format,
+        ballot_template: ballot_template,
+        languages: new_poll_configuration.languages,
+        voting_url: new_poll_configuration.voting_url,
         start_date: new_poll_configuration.start_date,
         end_date: new_poll_configuration.end_date,
         voter_roster: None,
+        voter_roster_sidecar: None,
         voter_roster_size: 0,
+        voter_roster_hash: None,
         voter_privacy: true,
+        stratification: new_poll_configuration.stratification,
+        strata: Vec::new(),
         drawn_summands_seed: None,
         audited_columns_seed: None,
         audited_ballots: None,
-        votes: None
+        votes: None,
+        decoy_requests: Vec::new()
     };
     let serialized_pollconf = serde_yaml::to_string(&pollconf)?;
     //debug!("{}\n", serialized_pollconf);
@@ -100,6 +128,18 @@ pub fn create_new_poll_run(pollconf_filename: &str) -> Result<(SecuredPollConfig
         let pollconf_file = File::open(pollconf_path)?;
         serde_yaml::from_reader(pollconf_file)?
     };
+    assert!(!new_poll_configuration.questions.is_empty(),
+        "A poll must ask at least one question.");
+    assert!(new_poll_configuration.questions.iter().all(|question| question.num_options() >= 2),
+        "Each poll question must offer at least two options.");
+    assert!(new_poll_configuration.questions.iter().all(|question| question.translations_complete()),
+        "Each translation of a question must label every option.");
+    assert!(new_poll_configuration.votecode_format.num_groups > 0 && new_poll_configuration.votecode_format.group_size > 0,
+        "Vote codes must have at least one group of one symbol.");
+    assert!(new_poll_configuration.votecode_format.num_votecodes().is_some(),
+        "Vote code format has too many symbols.");
+    assert!(new_poll_configuration.num_planes > 0,
+        "A poll must have at least one column plane.");
 
     // Generate Master Key and Shares.
     let num_trustees: usize = new_poll_configuration.poll_trustees.len();
@@ -122,6 +162,14 @@ pub fn create_new_poll_run(pollconf_filename: &str) -> Result<(SecuredPollConfig
         })
         .collect::<Vec<PollConfigurationTrustee>>();
 
+    // Read the ballot template.
+    let ballot_template = read_ballot_template(pollconf_path, &new_poll_configuration)?;
+    if let Some(voting_url) = &new_poll_configuration.voting_url {
+        voting_url.check()?;
+        assert!(ballot_template.qr_code.is_some(),
+            "A voting URL needs a qr_code position in the ballot template.");
+    }
+
     // Generate signing key/certificate.
     let (private_key, public_key): (Base64String, Base64String) = new_signing_key()?;
 
@@ -131,18 +179,26 @@ pub fn create_new_poll_run(pollconf_filename: &str) -> Result<(SecuredPollConfig
         signing_key: private_key,
         num_ballots: new_poll_configuration.num_ballots,
         num_decoys: new_poll_configuration.num_decoys,
-        question: new_poll_configuration.question,
-        option1: new_poll_configuration.option1,
-        option2: new_poll_configuration.option2,
+        num_planes: new_poll_configuration.num_planes,
+        questions: new_poll_configuration.questions,
+        votecode_format: new_poll_configuration.votecode_format,
+        ballot_template: ballot_template,
+        languages: new_poll_configuration.languages,
+        voting_url: new_poll_configuration.voting_url,
         start_date: new_poll_configuration.start_date,
         end_date: new_poll_configuration.end_date,
         voter_roster: None,
+        voter_roster_sidecar: None,
         voter_roster_size: 0,
+        voter_roster_hash: None,
         voter_privacy: true,
+        stratification: new_poll_configuration.stratification,
+        strata: Vec::new(),
         drawn_summands_seed: None,
         audited_columns_seed: None,
         audited_ballots: None,
-        votes: None
+        votes: None,
+        decoy_requests: Vec::new()
     };
     let serialized_pollconf = serde_yaml::to_string(&pollconf)?;
     //debug!("{}\n", serialized_pollconf);
@@ -0,0 +1,76 @@
+//! # Command: Decoy Requests
+//!
+//! `record_decoy_request` hands the next unused decoy ballot to a voter
+//! who asked for one and records the request in the secured poll
+//! configuration. `decoy_status` reports how many decoys are left
+//! without printing any decoy serial.
+
+use super::*;
+use chrono::Utc;
+
+
+pub fn record_decoy_request(pollconf_filename: &str, voter: &str) -> Result<()> {
+    let pollconf_path = Path::new(pollconf_filename);
+
+    // Read poll configuration file.
+    let mut secured_poll_configuration = read_poll_configuration_file(pollconf_filename)?;
+
+    // Reconstruct the Poll Master Key from the trustee passwords.
+    let (poll_master_key, aead_pmk) = read_poll_master_key(&secured_poll_configuration);
+
+    // Decrypt poll configuration state.
+    let pollconf_aead_values = secured_poll_configuration.encrypted_poll_configuration.values()?;
+    let serialized_pollconf = aead_decrypt(&aead_pmk, &pollconf_aead_values)?;
+    let mut pollconf: PollConfiguration = serde_yaml::from_slice(&serialized_pollconf).unwrap();
+
+    if !pollconf.poll_state.summands_drawn {
+        return Err(From::from("Decoys cannot be handed out before the ballots are printed."));
+    }
+    if pollconf.poll_state.votes_committed {
+        return Err(From::from("Decoys cannot be handed out after the votes are committed."));
+    }
+
+    // Derive the poll secrets.
+    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key, pollconf.num_questions(), pollconf.num_planes);
+
+    // Pick the next decoy not yet handed out.
+    let decoys: Vec<BallotSerial> = generate_decoy_serials(
+        poll_secrets.decoy_root,
+        pollconf.num_decoys,
+        pollconf.num_ballots);
+    let serial = hand_out_decoy(&mut pollconf.decoy_requests, &decoys, pollconf.num_ballots, voter, Utc::now().to_rfc3339())?;
+    println!("Hand decoy ballot {} to {}.", serial, voter);
+
+    // Re-encrypt the poll configuration.
+    let serialized_pollconf = serde_yaml::to_string(&pollconf)?;
+    let secure_serialized_pollconf = AEADString::from_values(
+        aead_encrypt(&aead_pmk,
+                     Vec::new(),
+                     serialized_pollconf.as_bytes().to_vec())?);
+    // Save the poll configuration.
+    secured_poll_configuration.encrypted_poll_configuration = secure_serialized_pollconf;
+    serde_yaml::to_writer(
+        File::create(pollconf_path)?,
+        &secured_poll_configuration)?;
+
+    Ok(())
+}
+
+
+pub fn decoy_status(pollconf_filename: &str) -> Result<()> {
+    // Read poll configuration file.
+    let secured_poll_configuration = read_poll_configuration_file(pollconf_filename)?;
+
+    // Reconstruct the Poll Master Key from the trustee passwords.
+    let (_poll_master_key, aead_pmk) = read_poll_master_key(&secured_poll_configuration);
+
+    // Decrypt poll configuration state.
+    let pollconf_aead_values = secured_poll_configuration.encrypted_poll_configuration.values()?;
+    let serialized_pollconf = aead_decrypt(&aead_pmk, &pollconf_aead_values)?;
+    let pollconf: PollConfiguration = serde_yaml::from_slice(&serialized_pollconf).unwrap();
+
+    decoy_status_lines(&pollconf.decoy_requests, pollconf.num_decoys).iter()
+        .for_each(|line| println!("{}", line));
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+//! # Dry Run Report
+//!
+//! `dry_run` records what a state-changing step would have done so the
+//! trustees can rehearse a public ceremony. During a dry run the step
+//! writes its outputs to a scratch directory, leaves the secured poll
+//! configuration untouched and does not post anything to the ledger.
+
+use super::*;
+
+
+pub struct DryRunReport {
+    step: String,
+    scratch_directory: String,
+    state_transitions: Vec<String>,
+    files: Vec<String>,
+    ledger_posts: Vec<String>
+}
+
+impl DryRunReport {
+    pub fn new(step: &str, scratch_directory: &str) -> Self {
+        DryRunReport {
+            step: step.to_owned(),
+            scratch_directory: scratch_directory.to_owned(),
+            state_transitions: Vec::new(),
+            files: Vec::new(),
+            ledger_posts: Vec::new()
+        }
+    }
+
+    pub fn state_transition(self: &mut Self, property: &str, from: &dyn std::fmt::Debug, to: &dyn std::fmt::Debug) {
+        self.state_transitions.push(format!("{}: {:?} -> {:?}", property, from, to));
+    }
+
+    pub fn file(self: &mut Self, path: &dyn AsRef<Path>) {
+        self.files.push(path.as_ref().display().to_string());
+    }
+
+    pub fn ledger_post(self: &mut Self, description: String) {
+        self.ledger_posts.push(description);
+    }
+
+    pub fn print(self: &Self) {
+        fn print_section(title: &str, entries: &Vec<String>) {
+            println!("{}:", title);
+            if entries.is_empty() { println!("  (none)"); }
+            entries.iter().for_each(|entry| println!("  {}", entry));
+        }
+
+        println!("DRY RUN: {}", self.step);
+        println!("Outputs were written to the scratch directory \"{}\".", self.scratch_directory);
+        println!("The poll configuration was not modified.");
+        print_section("State transitions", &self.state_transitions);
+        print_section("Files", &self.files);
+        print_section("Ledger posts", &self.ledger_posts);
+    }
+}
@@ -8,7 +8,10 @@
 use super::*;
 
 
-pub fn generate_drawn_summands(pollconf_filename: &str, seed: &str, force: bool) -> Result<()> {
+pub fn generate_drawn_summands(pollconf_filename: &str, seed: Option<&str>, seed_transcript: Option<&str>, force: bool, dry_run: bool) -> Result<()> {
+    let (seed, transcript) = resolve_seed(seed, seed_transcript, DRAWN_SUMMANDS_PURPOSE)?;
+    let seed: &str = &seed;
+
     let pollconf_path = Path::new(pollconf_filename);
 
     // Read poll configuration file.
@@ -19,6 +22,8 @@ pub fn generate_drawn_summands(pollconf_filename: &str, seed: &str, force: bool)
 
     // Ensure the data directory exists.
     let datadir_path = ensure_poll_data_directory_exists(&secured_poll_configuration, &aead_pmk)?;
+    let datadir_path = if dry_run { ensure_dry_run_directory_exists(&datadir_path)? } else { datadir_path };
+    let mut report = DryRunReport::new("Step 2: Generate drawn summands", &datadir_path);
 
     // Decrypt poll configuration state.
     let pollconf_aead_values = secured_poll_configuration.encrypted_poll_configuration.values()?;
@@ -36,7 +41,20 @@ pub fn generate_drawn_summands(pollconf_filename: &str, seed: &str, force: bool)
     let drawn_summands_seed: Vec<u8> = hex::decode(seed)?;
     assert!(drawn_summands_seed.len() == CSPRNGSeed::SIZE,
         "Seed for Drawn Summands must be {} bytes long.", CSPRNGSeed::SIZE);
+    report.state_transition("drawn_summands_seed", &pollconf.drawn_summands_seed, &Some(seed));
     pollconf.drawn_summands_seed = Some(seed.to_owned());
+    // Keep the seed transcript, so anyone can re-derive the seed.
+    if let Some(transcript) = transcript {
+        let transcript_path = {
+            let mut pathbuf = PathBuf::new();
+            pathbuf.push(&datadir_path);
+            pathbuf.push("drawn_summands_seed_transcript");
+            pathbuf.set_extension("yaml");
+            pathbuf.into_boxed_path()
+        };
+        transcript.to_file(&transcript_path)?;
+        report.file(&transcript_path);
+    }
 
     // Draw the Summands.
     let drawn_summands_path = {
@@ -46,17 +64,22 @@ pub fn generate_drawn_summands(pollconf_filename: &str, seed: &str, force: bool)
         pathbuf.set_extension("yaml");
         pathbuf.into_boxed_path()
     };
-    let drawn_summands = DrawnSummands::from_csprng(
+    let drawn_summands = DrawnSummands::from_csprng_strata(
         CSPRNGSeed::from_vec(&drawn_summands_seed),
-        pollconf.num_ballots,
-        pollconf.voter_roster_size);
+        &pollconf.summand_strata());
     debug!("{:#?}", drawn_summands);
     serde_yaml::to_writer(
-        File::create(drawn_summands_path)?,
+        File::create(&drawn_summands_path)?,
         &drawn_summands)?;
+    report.file(&drawn_summands_path);
 
     // Update the poll state.
+    report.state_transition("summands_drawn", &pollconf.poll_state.summands_drawn, &true);
     pollconf.poll_state.summands_drawn = true;
+    if dry_run {
+        report.print();
+        return Ok(());
+    }
     // Re-encrypt the poll configuration.
     let serialized_pollconf = serde_yaml::to_string(&pollconf)?;
     let secure_serialized_pollconf = AEADString::from_values(
@@ -104,10 +127,9 @@ pub fn generate_drawn_summands_run (pollconf_filename: &str, mut secured_poll_co
         pathbuf.set_extension("yaml");
         pathbuf.into_boxed_path()
     };
-    let drawn_summands = DrawnSummands::from_csprng(
+    let drawn_summands = DrawnSummands::from_csprng_strata(
         CSPRNGSeed::from_vec(&drawn_summands_seed),
-        pollconf.num_ballots,
-        pollconf.voter_roster_size);
+        &pollconf.summand_strata());
     debug!("{:#?}", drawn_summands);
     serde_yaml::to_writer(
         File::create(drawn_summands_path)?,
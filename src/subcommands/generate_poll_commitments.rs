@@ -2,25 +2,40 @@
 //!
 //! `generate_poll_commitments` reads the secrets from a secured
 //! poll configuration and generates commitment files for the
-//! committed summands and the column planes.
+//! voter roster, the committed summands and the column planes.
+//! The sampling strata are published alongside, so the selection of
+//! each stratum can be re-checked once the summands are revealed. The
+//! voters of each stratum are published only as salted commitments of
+//! their roster positions, opened for the selected voters in step 8.
 
 use super::*;
 
 pub fn generate_poll_commitments(pollconf_filename: &str, force: bool) -> Result<()> {
-    let pollconf_path = Path::new(pollconf_filename);
-
     // Read poll configuration file.
-    let mut secured_poll_configuration = read_poll_configuration_file(pollconf_filename)?;
+    let secured_poll_configuration = read_poll_configuration_file(pollconf_filename)?;
 
     // Reconstruct the Poll Master Key from the trustee passwords.
     let (poll_master_key, aead_pmk) = read_poll_master_key(&secured_poll_configuration);
 
+    write_poll_commitments(pollconf_filename, secured_poll_configuration, &poll_master_key, &aead_pmk, force)?;
+    Ok(())
+}
+
+pub fn generate_poll_commitments_run (pollconf_filename: &str, secured_poll_configuration: SecuredPollConfiguration, poll_master_key: PollMasterKey, aead_pmk: AEADKey) -> Result<SecuredPollConfiguration> {
+    write_poll_commitments(pollconf_filename, secured_poll_configuration, &poll_master_key, &aead_pmk, false)
+}
+
+/// Step 1 proper: commit the roster, summands and column planes, publish
+/// the sampling strata and save the updated poll configuration.
+fn write_poll_commitments(pollconf_filename: &str, mut secured_poll_configuration: SecuredPollConfiguration, poll_master_key: &PollMasterKey, aead_pmk: &AEADKey, force: bool) -> Result<SecuredPollConfiguration> {
+    let pollconf_path = Path::new(pollconf_filename);
+
     // Ensure the data directory exists.
-    let datadir_path = ensure_poll_data_directory_exists(&secured_poll_configuration, &aead_pmk)?;
+    let datadir_path = ensure_poll_data_directory_exists(&secured_poll_configuration, aead_pmk)?;
 
     // Decrypt poll configuration state.
     let pollconf_aead_values = secured_poll_configuration.encrypted_poll_configuration.values()?;
-    let serialized_pollconf = aead_decrypt(&aead_pmk, &pollconf_aead_values)?;
+    let serialized_pollconf = aead_decrypt(aead_pmk, &pollconf_aead_values)?;
     let mut pollconf: PollConfiguration = serde_yaml::from_slice(&serialized_pollconf).unwrap();
 
     assert!(pollconf.poll_state.roster_committed,
@@ -31,7 +46,7 @@ pub fn generate_poll_commitments(pollconf_filename: &str, force: bool) -> Result
         "Columns already committed. To re-commit, pass --force.");
 
     // Derive the poll secrets.
-    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key);
+    let poll_secrets: PollSecrets = PollSecrets::derive(poll_master_key, pollconf.num_questions(), pollconf.num_planes);
     debug!("{:?}", poll_secrets);
 
     // Commit the Roster.
@@ -42,126 +57,47 @@ pub fn generate_poll_commitments(pollconf_filename: &str, force: bool) -> Result
         pathbuf.set_extension("csv");
         pathbuf.into_boxed_path()
     };
-    let committed_roster = {
-        let full_roster: VoterRoster = {
-            let encoded_roster = pollconf.voter_roster.clone().unwrap();
-            let decoded_roster = base64::decode(&encoded_roster.0)?;
-            let serialized_roster = str::from_utf8(&decoded_roster)?;
-            serde_yaml::from_str(serialized_roster)?
-        };
-
-        // TODO: Implement voter privacy.
-        if pollconf.voter_privacy {
-            full_roster.restricted()
-        } else {
-            full_roster.restricted()
+    // With voter privacy only a salted commitment of each record is published.
+    // The roster is streamed, one record at a time.
+    {
+        let mut csvwriter = csv::Writer::from_path(&committed_roster_path)?;
+        let mut salts = roster_salts(poll_secrets.roster_root);
+        for record in bound_roster_records(&pollconf, aead_pmk)? {
+            let record = record?;
+            let restricted = RestrictedVoterInfo::from(record.voter_info);
+            let salt = salts.next().unwrap();
+            csvwriter.serialize(RestrictedVoterRosterFileRow {
+                position: record.position,
+                restricted: match pollconf.voter_privacy {
+                    true => salted_commitment(&restricted, &salt),
+                    false => restricted
+                }
+            })?;
         }
-    };
-    committed_roster.to_file(&committed_roster_path)?;
+    }
 
-    // Commit the Summands.
-    let committed_summands_path = {
+    // Publish the Sampling Strata.
+    let strata_path = {
         let mut pathbuf = PathBuf::new();
         pathbuf.push(&datadir_path);
-        pathbuf.push("committed_summands");
+        pathbuf.push("strata");
         pathbuf.set_extension("yaml");
         pathbuf.into_boxed_path()
     };
-    let committed_summands = CommittedSummands::from_csprng(
-        poll_secrets.summands_root,
-        pollconf.num_ballots,
-        pollconf.voter_roster_size);
-    let summands_commitment: SecuredFile = committed_summands.aead_commit(&poll_secrets.summands_key)?;
     serde_yaml::to_writer(
-        File::create(committed_summands_path)?,
-        &summands_commitment)?;
-
-    // Commit the Column Planes.
-    let column_planes: Vec<Plane> = generate_column_planes(
-        &poll_secrets,
-        NUMBER_OF_PLANES,
-        2 * pollconf.num_ballots,
-        pollconf.num_decoys)?;
-    column_planes.iter().enumerate()
-        .for_each(|(n, plane)| {
-            let committed_planes_path = {
-                let mut pathbuf = PathBuf::new();
-                pathbuf.push(&datadir_path);
-                pathbuf.push(format!("committed_plane_{:02}", n+1));
-                pathbuf.set_extension("csv");
-                pathbuf.into_boxed_path()
-            };
-            let psecrets = poll_secrets.plane_secrets[n].resolve(plane.len());
-            let permuted_plane = plane.permute(&psecrets.permutation);
-            let mut csvwriter = csv::Writer::from_path(committed_planes_path).unwrap();
-            permuted_plane.rows.iter()
-                .for_each(|rec| { csvwriter.serialize(rec).unwrap(); });
-        });
-
-    // Update the poll state.
-    pollconf.poll_state.summands_committed = true;
-    pollconf.poll_state.columns_committed = true;
-    // Re-encrypt the poll configuration.
-    let serialized_pollconf = serde_yaml::to_string(&pollconf)?;
-    let secure_serialized_pollconf = AEADString::from_values(
-        aead_encrypt(&aead_pmk,
-                     Vec::new(),
-                     serialized_pollconf.as_bytes().to_vec())?);
-    // Save the poll configuration.
-    secured_poll_configuration.encrypted_poll_configuration = secure_serialized_pollconf;
-    serde_yaml::to_writer(
-        File::create(pollconf_path)?,
-        &secured_poll_configuration)?;
-
-    Ok(())
-}
-
-pub fn generate_poll_commitments_run (pollconf_filename: &str, mut secured_poll_configuration: SecuredPollConfiguration, poll_master_key: PollMasterKey, aead_pmk: AEADKey) -> Result<SecuredPollConfiguration> {
-    let pollconf_path = Path::new(pollconf_filename);
-
-    // Ensure the data directory exists.
-    let datadir_path = ensure_poll_data_directory_exists(&secured_poll_configuration, &aead_pmk)?;
-    
-    // Decrypt poll configuration state.
-    let pollconf_aead_values = secured_poll_configuration.encrypted_poll_configuration.values()?;
-    let serialized_pollconf = aead_decrypt(&aead_pmk, &pollconf_aead_values)?;
-    let mut pollconf: PollConfiguration = serde_yaml::from_slice(&serialized_pollconf).unwrap();
-
-    assert!(pollconf.poll_state.roster_committed,
-        "Voter roster must be bound to generate poll commitments.");
-    assert!(!pollconf.poll_state.summands_committed,
-        "Summands already committed.");
-    assert!(!pollconf.poll_state.columns_committed,
-        "Columns already committed.");
-
-    // Derive the poll secrets.
-    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key);
-    debug!("{:?}", poll_secrets);
-
-    // Commit the Roster.
-    let committed_roster_path = {
-        let mut pathbuf = PathBuf::new();
-        pathbuf.push(&datadir_path);
-        pathbuf.push("committed_roster");
-        pathbuf.set_extension("csv");
-        pathbuf.into_boxed_path()
-    };
-    let committed_roster = {
-        let full_roster: VoterRoster = {
-            let encoded_roster = pollconf.voter_roster.clone().unwrap();
-            let decoded_roster = base64::decode(&encoded_roster.0)?;
-            let serialized_roster = str::from_utf8(&decoded_roster)?;
-            serde_yaml::from_str(serialized_roster)?
-        };
-
-        // TODO: Implement voter privacy.
-        if pollconf.voter_privacy {
-            full_roster.restricted()
-        } else {
-            full_roster.restricted()
-        }
-    };
-    committed_roster.to_file(&committed_roster_path)?;
+        File::create(strata_path)?,
+        &StrataFile {
+            stratification: pollconf.stratification.clone(),
+            strata: pollconf.sampling_strata(),
+            members: match &pollconf.stratification {
+                Some(stratification) => crate::voter_selection::stratum_member_commitments(
+                    bound_roster_records(&pollconf, aead_pmk)?,
+                    roster_salts(poll_secrets.roster_root),
+                    stratification,
+                    &pollconf.strata)?,
+                None => Vec::new()
+            }
+        })?;
 
     // Commit the Summands.
     let committed_summands_path = {
@@ -171,36 +107,40 @@ pub fn generate_poll_commitments_run (pollconf_filename: &str, mut secured_poll_
         pathbuf.set_extension("yaml");
         pathbuf.into_boxed_path()
     };
-    let committed_summands = CommittedSummands::from_csprng(
+    let committed_summands = CommittedSummands::from_csprng_strata(
         poll_secrets.summands_root,
-        pollconf.num_ballots,
-        pollconf.voter_roster_size);
+        &pollconf.summand_strata());
     let summands_commitment: SecuredFile = committed_summands.aead_commit(&poll_secrets.summands_key)?;
     serde_yaml::to_writer(
         File::create(committed_summands_path)?,
         &summands_commitment)?;
 
-    // Commit the Column Planes.
-    let column_planes: Vec<Plane> = generate_column_planes(
-        &poll_secrets,
-        NUMBER_OF_PLANES,
-        2 * pollconf.num_ballots,
-        pollconf.num_decoys)?;
-    column_planes.iter().enumerate()
-        .for_each(|(n, plane)| {
-            let committed_planes_path = {
-                let mut pathbuf = PathBuf::new();
-                pathbuf.push(&datadir_path);
-                pathbuf.push(format!("committed_plane_{:02}", n+1));
-                pathbuf.set_extension("csv");
-                pathbuf.into_boxed_path()
-            };
-            let psecrets = poll_secrets.plane_secrets[n].resolve(plane.len());
-            let permuted_plane = plane.permute(&psecrets.permutation);
-            let mut csvwriter = csv::Writer::from_path(committed_planes_path).unwrap();
-            permuted_plane.rows.iter()
-                .for_each(|rec| { csvwriter.serialize(rec).unwrap(); });
-        });
+    // Commit the Column Planes of each question.
+    for q in 0..pollconf.num_questions() {
+        let column_planes: Vec<Plane> = generate_column_planes(
+            &poll_secrets,
+            q,
+            pollconf.num_planes,
+            pollconf.num_ballots,
+            pollconf.num_options(q),
+            pollconf.num_decoys,
+            &pollconf.votecode_format)?;
+        column_planes.iter().enumerate()
+            .for_each(|(n, plane)| {
+                let committed_planes_path = {
+                    let mut pathbuf = PathBuf::new();
+                    pathbuf.push(&datadir_path);
+                    pathbuf.push(plane_file_name("committed", q, n));
+                    pathbuf.set_extension("csv");
+                    pathbuf.into_boxed_path()
+                };
+                let psecrets = poll_secrets.question_secrets[q].plane_secrets[n].resolve(plane.len());
+                let permuted_plane = plane.permute(&psecrets.permutation);
+                let mut csvwriter = csv::Writer::from_path(committed_planes_path).unwrap();
+                permuted_plane.rows.iter()
+                    .for_each(|rec| { csvwriter.serialize(rec).unwrap(); });
+            });
+    }
 
     // Update the poll state.
     pollconf.poll_state.summands_committed = true;
@@ -208,16 +148,14 @@ pub fn generate_poll_commitments_run (pollconf_filename: &str, mut secured_poll_
     // Re-encrypt the poll configuration.
     let serialized_pollconf = serde_yaml::to_string(&pollconf)?;
     let secure_serialized_pollconf = AEADString::from_values(
-        aead_encrypt(&aead_pmk,
-                    Vec::new(),
-                    serialized_pollconf.as_bytes().to_vec())?);
+        aead_encrypt(aead_pmk,
+                     Vec::new(),
+                     serialized_pollconf.as_bytes().to_vec())?);
     // Save the poll configuration.
     secured_poll_configuration.encrypted_poll_configuration = secure_serialized_pollconf;
     serde_yaml::to_writer(
-    File::create(pollconf_path)?,
-    &secured_poll_configuration)?;
-
+        File::create(pollconf_path)?,
+        &secured_poll_configuration)?;
 
     Ok(secured_poll_configuration)
 }
-
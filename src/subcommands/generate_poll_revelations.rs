@@ -1,11 +1,14 @@
 //! # Command: Generate Poll Revelations
 //!
+//! `generate_poll_revelations` reveals the committed summands, the
+//! fully audited column planes and, with voter privacy, the roster
+//! records and salts of the selected voters.
 
 use std::io::Write;
 use super::*;
 
 
-pub fn generate_poll_revelations(pollconf_filename: &str, force: bool) -> Result<()> {
+pub fn generate_poll_revelations(pollconf_filename: &str, force: bool, dry_run: bool) -> Result<()> {
     let pollconf_path = Path::new(pollconf_filename);
 
     // Read poll configuration file.
@@ -16,6 +19,8 @@ pub fn generate_poll_revelations(pollconf_filename: &str, force: bool) -> Result
 
     // Ensure the data directory exists.
     let datadir_path = ensure_poll_data_directory_exists(&secured_poll_configuration, &aead_pmk)?;
+    let datadir_path = if dry_run { ensure_dry_run_directory_exists(&datadir_path)? } else { datadir_path };
+    let mut report = DryRunReport::new("Step 8: Generate decrypted plane columns", &datadir_path);
 
     // Decrypt poll configuration state.
     let pollconf_aead_values = secured_poll_configuration.encrypted_poll_configuration.values()?;
@@ -32,7 +37,7 @@ pub fn generate_poll_revelations(pollconf_filename: &str, force: bool) -> Result
         "Voter roster already revealed. To re-commit, pass --force.");
 
     // Derive the poll secrets.
-    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key);
+    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key, pollconf.num_questions(), pollconf.num_planes);
 
     // Re-construct the audited ballots.
     let audited_ballots: Vec<BallotSerial> = {
@@ -43,17 +48,6 @@ pub fn generate_poll_revelations(pollconf_filename: &str, force: bool) -> Result
 
     // Re-construct the marked votes.
     let votes: Vec<VoteCode> = pollconf.votes.clone().unwrap();
-    let marked_rows: Vec<usize> = {
-        let votecodes: Vec<VoteCode> = generate_votecodes(
-            poll_secrets.votecode_root,
-            2 * pollconf.num_ballots);
-        votecodes.iter().enumerate()
-            .filter_map(|(n, vc)| {
-                debug!("{:?}", vc);
-                if votes.contains(vc) { Some(n) }
-                else { None }
-            }).collect()
-    };
 
     // Reveal Committed Summands
     let committed_summands_revealed_path = {
@@ -63,14 +57,14 @@ pub fn generate_poll_revelations(pollconf_filename: &str, force: bool) -> Result
         pathbuf.set_extension("csv");
         pathbuf.into_boxed_path()
     };
-    let committed_summands = CommittedSummands::from_csprng(
+    let committed_summands = CommittedSummands::from_csprng_strata(
         poll_secrets.summands_root,
-        pollconf.num_ballots,
-        pollconf.voter_roster_size);
-    let mut csvwriter = csv::Writer::from_path(committed_summands_revealed_path)?;
+        &pollconf.summand_strata());
+    let mut csvwriter = csv::Writer::from_path(&committed_summands_revealed_path)?;
     Summands::from(committed_summands).records.iter().for_each(|summand| {
         csvwriter.serialize(summand).unwrap();
     });
+    report.file(&committed_summands_revealed_path);
     // Reveal Committed Summands Key
     let committed_summands_key_path = {
         let mut pathbuf = PathBuf::new();
@@ -79,8 +73,22 @@ pub fn generate_poll_revelations(pollconf_filename: &str, force: bool) -> Result
         pathbuf.set_extension("key");
         pathbuf.into_boxed_path()
     };
-    File::create(committed_summands_key_path)?
+    File::create(&committed_summands_key_path)?
         .write(base64::encode(&poll_secrets.summands_key.0).as_bytes())?;
+    report.file(&committed_summands_key_path);
+
+    // Reveal the Selected Voters
+    if pollconf.voter_privacy {
+        let revealed_roster_path = {
+            let mut pathbuf = PathBuf::new();
+            pathbuf.push(&datadir_path);
+            pathbuf.push("revealed_roster");
+            pathbuf.set_extension("csv");
+            pathbuf.into_boxed_path()
+        };
+        reveal_selected_voters(&pollconf, &poll_secrets, &aead_pmk, &revealed_roster_path)?;
+        report.file(&revealed_roster_path);
+    }
 
     // Reveal Audited Columns
     let audited_columns_seed: Vec<u8> = {
@@ -90,56 +98,83 @@ pub fn generate_poll_revelations(pollconf_filename: &str, force: bool) -> Result
     let audited_columns: Vec<usize> = {
         let seed = CSPRNGSeed::from_vec(&audited_columns_seed);
         let mut prng = CSPRNG::from_csprng_seed(seed);
-        (0..NUMBER_OF_PLANES).into_iter().map(|_| prng.gen_range(0, 2)).collect()
+        (0..pollconf.num_planes).into_iter().map(|_| prng.gen_range(0, 2)).collect()
     };
 
-    // Post the Fully Audited Column Planes.
-    let column_planes: Vec<Plane> = generate_column_planes(
-        &poll_secrets,
-        NUMBER_OF_PLANES,
-        2 * pollconf.num_ballots,
-        pollconf.num_decoys)?;
-    // Filter planes.
-    column_planes.iter().enumerate()
-        .for_each(|(n, plane)| {
-            let posted_planes_path = {
-                let mut pathbuf = PathBuf::new();
-                pathbuf.push(&datadir_path);
-                pathbuf.push(format!("final_plane_{:02}", n+1));
-                pathbuf.set_extension("csv");
-                pathbuf.into_boxed_path()
-            };
-            let posted_keys_path = {
-                let mut pathbuf = PathBuf::new();
-                pathbuf.push(&datadir_path);
-                pathbuf.push(format!("final_plane_{:02}_keys", n+1));
-                pathbuf.set_extension("csv");
-                pathbuf.into_boxed_path()
-            };
-            let psecrets = poll_secrets.plane_secrets[n].resolve(plane.len());
-            let filter = PlaneFilter::from(&psecrets.col1_keys, &psecrets.col3_keys)
-                .decrypt_serials(&audited_ballots)
-                .decrypt_column(if audited_columns[n] == 0 {1} else {3});
-
-            let permuted_plane = plane.mark_rows(&marked_rows).decrypt(&filter).permute(&psecrets.permutation);
-            let mut csvwriter = csv::Writer::from_path(posted_planes_path).unwrap();
-            permuted_plane.rows.iter()
-                .for_each(|rec| {
-                    csvwriter.serialize(rec.serializable(pollconf.num_ballots)).unwrap();
-                });
+    // Post the Fully Audited Column Planes of each question.
+    for q in 0..pollconf.num_questions() {
+        let marked_rows: Vec<usize> = {
+            let votecodes: Vec<VoteCode> = generate_votecodes(
+                poll_secrets.question_secrets[q].votecode_root,
+                pollconf.num_rows(q),
+                &pollconf.votecode_format);
+            votecodes.iter().enumerate()
+                .filter_map(|(n, vc)| {
+                    debug!("{:?}", vc);
+                    if votes.contains(vc) { Some(n) }
+                    else { None }
+                }).collect()
+        };
+        let column_planes: Vec<Plane> = generate_column_planes(
+            &poll_secrets,
+            q,
+            pollconf.num_planes,
+            pollconf.num_ballots,
+            pollconf.num_options(q),
+            pollconf.num_decoys,
+            &pollconf.votecode_format)?;
+        // Filter planes.
+        column_planes.iter().enumerate()
+            .map(|(n, plane)| -> Result<()> {
+                let posted_planes_path = {
+                    let mut pathbuf = PathBuf::new();
+                    pathbuf.push(&datadir_path);
+                    pathbuf.push(plane_file_name("final", q, n));
+                    pathbuf.set_extension("csv");
+                    pathbuf.into_boxed_path()
+                };
+                let posted_keys_path = {
+                    let mut pathbuf = PathBuf::new();
+                    pathbuf.push(&datadir_path);
+                    pathbuf.push(format!("{}_keys", plane_file_name("final", q, n)));
+                    pathbuf.set_extension("csv");
+                    pathbuf.into_boxed_path()
+                };
+                let psecrets = poll_secrets.question_secrets[q].plane_secrets[n].resolve(plane.len());
+                let filter = PlaneFilter::from(&psecrets.col1_keys, &psecrets.col3_keys)
+                    .decrypt_serials(&audited_ballots, pollconf.num_options(q))
+                    .decrypt_column(if audited_columns[n] == 0 {1} else {3});
+
+                let permuted_plane = plane.mark_rows(&marked_rows).decrypt(&filter)?.permute(&psecrets.permutation);
+                let mut csvwriter = csv::Writer::from_path(&posted_planes_path).unwrap();
+                permuted_plane.rows.iter()
+                    .for_each(|rec| {
+                        csvwriter.serialize(rec.serializable(pollconf.num_ballots)).unwrap();
+                    });
+                report.file(&posted_planes_path);
             
-            let permuted_filter = filter.permute(&psecrets.permutation);
-            let mut csvwriter = csv::Writer::from_path(posted_keys_path).unwrap();
-            permuted_filter.serializable().iter()
-                .for_each(|rec| {
-                    csvwriter.serialize(rec).unwrap();
-                });
-        });
+                let permuted_filter = filter.permute(&psecrets.permutation);
+                let mut csvwriter = csv::Writer::from_path(&posted_keys_path).unwrap();
+                permuted_filter.serializable().iter()
+                    .for_each(|rec| {
+                        csvwriter.serialize(rec).unwrap();
+                    });
+                report.file(&posted_keys_path);
+                Ok(())
+            }).collect::<Result<()>>()?;
+    }
 
     // Update the poll state.
+    report.state_transition("roster_revealed", &pollconf.poll_state.roster_revealed, &true);
+    report.state_transition("summands_revealed", &pollconf.poll_state.summands_revealed, &true);
+    report.state_transition("columns_revealed", &pollconf.poll_state.columns_revealed, &true);
     pollconf.poll_state.roster_revealed = true;
     pollconf.poll_state.summands_revealed = true;
     pollconf.poll_state.columns_revealed = true;
+    if dry_run {
+        report.print();
+        return Ok(());
+    }
     // Re-encrypt the poll configuration.
     let serialized_pollconf = serde_yaml::to_string(&pollconf)?;
     let secure_serialized_pollconf = AEADString::from_values(
@@ -177,7 +212,7 @@ pub fn generate_poll_revelations_run(pollconf_filename: &str, mut secured_poll_c
         "Voter roster already revealed. To re-commit.");
 
     // Derive the poll secrets.
-    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key);
+    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key, pollconf.num_questions(), pollconf.num_planes);
 
     // Re-construct the audited ballots.
     let audited_ballots: Vec<BallotSerial> = {
@@ -188,17 +223,6 @@ pub fn generate_poll_revelations_run(pollconf_filename: &str, mut secured_poll_c
 
     // Re-construct the marked votes.
     let votes: Vec<VoteCode> = pollconf.votes.clone().unwrap();
-    let marked_rows: Vec<usize> = {
-        let votecodes: Vec<VoteCode> = generate_votecodes(
-            poll_secrets.votecode_root,
-            2 * pollconf.num_ballots);
-        votecodes.iter().enumerate()
-            .filter_map(|(n, vc)| {
-                debug!("{:?}", vc);
-                if votes.contains(vc) { Some(n) }
-                else { None }
-            }).collect()
-    };
 
     // Reveal Committed Summands
     let committed_summands_revealed_path = {
@@ -208,10 +232,9 @@ pub fn generate_poll_revelations_run(pollconf_filename: &str, mut secured_poll_c
         pathbuf.set_extension("csv");
         pathbuf.into_boxed_path()
     };
-    let committed_summands = CommittedSummands::from_csprng(
+    let committed_summands = CommittedSummands::from_csprng_strata(
         poll_secrets.summands_root,
-        pollconf.num_ballots,
-        pollconf.voter_roster_size);
+        &pollconf.summand_strata());
     let mut csvwriter = csv::Writer::from_path(committed_summands_revealed_path)?;
     Summands::from(committed_summands).records.iter().for_each(|summand| {
         csvwriter.serialize(summand).unwrap();
@@ -227,6 +250,18 @@ pub fn generate_poll_revelations_run(pollconf_filename: &str, mut secured_poll_c
     File::create(committed_summands_key_path)?
         .write(base64::encode(&poll_secrets.summands_key.0).as_bytes())?;
 
+    // Reveal the Selected Voters
+    if pollconf.voter_privacy {
+        let revealed_roster_path = {
+            let mut pathbuf = PathBuf::new();
+            pathbuf.push(&datadir_path);
+            pathbuf.push("revealed_roster");
+            pathbuf.set_extension("csv");
+            pathbuf.into_boxed_path()
+        };
+        reveal_selected_voters(&pollconf, &poll_secrets, &aead_pmk, &revealed_roster_path)?;
+    }
+
     // Reveal Audited Columns
     let audited_columns_seed: Vec<u8> = {
         let seed = pollconf.audited_columns_seed.clone();
@@ -235,51 +270,69 @@ pub fn generate_poll_revelations_run(pollconf_filename: &str, mut secured_poll_c
     let audited_columns: Vec<usize> = {
         let seed = CSPRNGSeed::from_vec(&audited_columns_seed);
         let mut prng = CSPRNG::from_csprng_seed(seed);
-        (0..NUMBER_OF_PLANES).into_iter().map(|_| prng.gen_range(0, 2)).collect()
+        (0..pollconf.num_planes).into_iter().map(|_| prng.gen_range(0, 2)).collect()
     };
 
-    // Post the Fully Audited Column Planes.
-    let column_planes: Vec<Plane> = generate_column_planes(
-        &poll_secrets,
-        NUMBER_OF_PLANES,
-        2 * pollconf.num_ballots,
-        pollconf.num_decoys)?;
-    // Filter planes.
-    column_planes.iter().enumerate()
-        .for_each(|(n, plane)| {
-            let posted_planes_path = {
-                let mut pathbuf = PathBuf::new();
-                pathbuf.push(&datadir_path);
-                pathbuf.push(format!("final_plane_{:02}", n+1));
-                pathbuf.set_extension("csv");
-                pathbuf.into_boxed_path()
-            };
-            let posted_keys_path = {
-                let mut pathbuf = PathBuf::new();
-                pathbuf.push(&datadir_path);
-                pathbuf.push(format!("final_plane_{:02}_keys", n+1));
-                pathbuf.set_extension("csv");
-                pathbuf.into_boxed_path()
-            };
-            let psecrets = poll_secrets.plane_secrets[n].resolve(plane.len());
-            let filter = PlaneFilter::from(&psecrets.col1_keys, &psecrets.col3_keys)
-                .decrypt_serials(&audited_ballots)
-                .decrypt_column(if audited_columns[n] == 0 {1} else {3});
-
-            let permuted_plane = plane.mark_rows(&marked_rows).decrypt(&filter).permute(&psecrets.permutation);
-            let mut csvwriter = csv::Writer::from_path(posted_planes_path).unwrap();
-            permuted_plane.rows.iter()
-                .for_each(|rec| {
-                    csvwriter.serialize(rec.serializable(pollconf.num_ballots)).unwrap();
-                });
+    // Post the Fully Audited Column Planes of each question.
+    for q in 0..pollconf.num_questions() {
+        let marked_rows: Vec<usize> = {
+            let votecodes: Vec<VoteCode> = generate_votecodes(
+                poll_secrets.question_secrets[q].votecode_root,
+                pollconf.num_rows(q),
+                &pollconf.votecode_format);
+            votecodes.iter().enumerate()
+                .filter_map(|(n, vc)| {
+                    debug!("{:?}", vc);
+                    if votes.contains(vc) { Some(n) }
+                    else { None }
+                }).collect()
+        };
+        let column_planes: Vec<Plane> = generate_column_planes(
+            &poll_secrets,
+            q,
+            pollconf.num_planes,
+            pollconf.num_ballots,
+            pollconf.num_options(q),
+            pollconf.num_decoys,
+            &pollconf.votecode_format)?;
+        // Filter planes.
+        column_planes.iter().enumerate()
+            .map(|(n, plane)| -> Result<()> {
+                let posted_planes_path = {
+                    let mut pathbuf = PathBuf::new();
+                    pathbuf.push(&datadir_path);
+                    pathbuf.push(plane_file_name("final", q, n));
+                    pathbuf.set_extension("csv");
+                    pathbuf.into_boxed_path()
+                };
+                let posted_keys_path = {
+                    let mut pathbuf = PathBuf::new();
+                    pathbuf.push(&datadir_path);
+                    pathbuf.push(format!("{}_keys", plane_file_name("final", q, n)));
+                    pathbuf.set_extension("csv");
+                    pathbuf.into_boxed_path()
+                };
+                let psecrets = poll_secrets.question_secrets[q].plane_secrets[n].resolve(plane.len());
+                let filter = PlaneFilter::from(&psecrets.col1_keys, &psecrets.col3_keys)
+                    .decrypt_serials(&audited_ballots, pollconf.num_options(q))
+                    .decrypt_column(if audited_columns[n] == 0 {1} else {3});
+
+                let permuted_plane = plane.mark_rows(&marked_rows).decrypt(&filter)?.permute(&psecrets.permutation);
+                let mut csvwriter = csv::Writer::from_path(posted_planes_path).unwrap();
+                permuted_plane.rows.iter()
+                    .for_each(|rec| {
+                        csvwriter.serialize(rec.serializable(pollconf.num_ballots)).unwrap();
+                    });
             
-            let permuted_filter = filter.permute(&psecrets.permutation);
-            let mut csvwriter = csv::Writer::from_path(posted_keys_path).unwrap();
-            permuted_filter.serializable().iter()
-                .for_each(|rec| {
-                    csvwriter.serialize(rec).unwrap();
-                });
-        });
+                let permuted_filter = filter.permute(&psecrets.permutation);
+                let mut csvwriter = csv::Writer::from_path(posted_keys_path).unwrap();
+                permuted_filter.serializable().iter()
+                    .for_each(|rec| {
+                        csvwriter.serialize(rec).unwrap();
+                    });
+                Ok(())
+            }).collect::<Result<()>>()?;
+    }
 
     // Update the poll state.
     pollconf.poll_state.roster_revealed = true;
@@ -3,220 +3,335 @@
 //! `generate_print_files` reads from the voter roster and poll secrets
 //! from the secured poll configuration and writes out CSV files for the
 //! address labels and ballot information. These files are intended
-//! for mass printing.
+//! for mass printing. The ballot PDFs are written one per serial, or
+//! batched and imposed for the print shop.
+//!
+//! The decoy packet, the decoy indicator stickers and the list of decoy
+//! serials, is written to its own directory so it can be handed to
+//! whoever attaches the stickers rather than to the print shop.
+//!
+//! A secret shuffle derived from the poll deals the sampled voters and
+//! the ballot serials over numbered mail pieces (see `mailing::pieces`).
+//! The address CSV lists each address with its mail piece, the ballot
+//! printer gets the ballot CSV of the vote codes of each serial and
+//! `ballot_order.csv` with the mail piece of each serial, and
+//! a batch of ballots is printed in mail piece order. Each ballot is
+//! printed in the language of its voter (see `ballots::locale`). The
+//! label sheets, window envelope pages, Intelligent Mail barcode field
+//! and sealed vendor packages are optional (see `mailing`).
+//!
+//! When packages are sealed, the files of the vendors are written to a
+//! staging directory in the packages directory and removed with it once
+//! sealed, so no plain copy of the addresses or ballots is left. Without
+//! packages they are plain files, and step 3 warns so.
 
+use std::collections::BTreeMap;
 use super::*;
-use crate::voter_selection::select_voters;
+use crate::voter_selection::{select_members_counting_collisions, member_positions};
 use crate::ballots::print;
+use crate::mailing::*;
+
+/// Directory of the staged vendor files, in the packages directory.
+const STAGING_DIRECTORY: &str = ".staging";
+
+/// A directory removed, with its files, when dropped.
+struct StagingDirectory(PathBuf);
+
+impl Drop for StagingDirectory {
+    fn drop(self: &mut Self) {
+        if let Err(err) = std::fs::remove_dir_all(&self.0) {
+            println!("Warning: staging directory {} not removed: {}", self.0.display(), err);
+        }
+    }
+}
+
 
 #[derive(Debug, Clone, Serialize)]
-pub struct AddressLabel {
-    pub last_name: String,
-    pub first_name: String,
-    pub address1: String,
-    pub address2: String,
-    pub city: String,
-    pub state: String,
-    pub zip_code: String
+pub struct BallotOrderRow {
+    pub mail_piece: usize,
+    pub serial: String
 }
 
 
 #[derive(Debug, Clone, Serialize)]
-pub struct CompleteBallotRow {
-    pub serial: String,
-    pub choice1_votecode: String,
-    pub choice1_value: String,
-    pub choice2_votecode: String,
-    pub choice2_value: String
+pub struct DecoySerialRow {
+    pub serial: String
 }
 
+
 #[derive(Debug, Clone, Serialize)]
 pub struct SplitBallotRow {
     pub serial: String,
+    pub question: usize,
     pub votecode: String,
     pub choice: String
 }
 
 
-pub fn generate_print_files(pollconf_filename: &str, addresses_filename: &str, ballots_filename: &str) -> Result<()> {
+pub fn generate_print_files(pollconf_filename: &str, addresses_filename: &str, ballots_filename: &str, pdf_output: &BallotPdfOutput, mailing: &MailingOutput) -> Result<()> {
     // Read poll configuration file.
     let secured_poll_configuration = read_poll_configuration_file(pollconf_filename)?;
 
     // Reconstruct the Poll Master Key from the trustee passwords.
     let (poll_master_key, aead_pmk) = read_poll_master_key(&secured_poll_configuration);
 
+    generate_print_files_run(secured_poll_configuration, poll_master_key, aead_pmk, addresses_filename, ballots_filename, pdf_output, mailing)
+}
+
+
+pub fn generate_print_files_run(secured_poll_configuration: SecuredPollConfiguration, poll_master_key: PollMasterKey, aead_pmk: AEADKey, addresses_filename: &str, ballots_filename: &str, pdf_output: &BallotPdfOutput, mailing: &MailingOutput) -> Result<()> {
+
     // Decrypt poll configuration state.
     let pollconf_aead_values = secured_poll_configuration.encrypted_poll_configuration.values()?;
     let serialized_pollconf = aead_decrypt(&aead_pmk, &pollconf_aead_values)?;
     let pollconf: PollConfiguration = serde_yaml::from_slice(&serialized_pollconf).unwrap();
-    
-    assert!(pollconf.poll_state.summands_drawn,
-        "Summands must be drawn to generate voters and print content for public audit.");
 
     // Derive the poll secrets.
-    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key);
-    
-    // Regenerate the Committed Summands.
-    let committed_summands = CommittedSummands::from_csprng(
-        poll_secrets.summands_root,
-        pollconf.num_ballots,
-        pollconf.voter_roster_size);
-    // Regenerate the Drawn Summands.
-    let drawn_summands_seed: Vec<u8> = {
-        let seed = pollconf.drawn_summands_seed.clone();
-        hex::decode(seed.unwrap())?
-    };
-    let drawn_summands = DrawnSummands::from_csprng(
-        CSPRNGSeed::from_vec(&drawn_summands_seed),
-        pollconf.num_ballots,
-        pollconf.voter_roster_size);
-    // Select the Voters.
-    let roster_indices = select_voters(
-        committed_summands,
-        drawn_summands,
-        pollconf.voter_roster_size)?;
-    debug!("Selected Voters: {:?}", roster_indices);
+    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key, pollconf.num_questions(), pollconf.num_planes);
 
-    // Generate the Ballots.
-    let serials: Vec<BallotSerial> = (0..pollconf.num_ballots).collect();
-    let votecodes: Vec<VoteCode> = generate_votecodes(
-        poll_secrets.votecode_root,
-        2 * pollconf.num_ballots);
-    let ballots = generate_ballots(&serials, &votecodes);
-    debug!("Ballots: {:?}", ballots);
-
-    // Print the Address Labels
-    let roster: VoterRoster = {
-        let encoded_roster = pollconf.voter_roster.clone().unwrap();
-        let decoded_roster = base64::decode(&encoded_roster.0)?;
-        let serialized_roster = str::from_utf8(&decoded_roster)?;
-        serde_yaml::from_str(serialized_roster)?
-    };
-    let addresses: Vec<AddressLabel> = roster_indices.iter()
-        .map(|&n| { roster.records[n].voter_info.clone() })
-        .map(|voter| {
-            AddressLabel {
-                last_name: voter.last_name,
-                first_name: voter.first_name,
-                address1: voter.street_address,
-                address2: "".to_owned(),
-                city: voter.city,
-                state: voter.state,
-                zip_code: voter.zip_code
-            }
-        }).collect();
-    let address_labels_path = Path::new(addresses_filename);
-    let mut csvwriter = csv::Writer::from_path(address_labels_path)?;
-    addresses.iter()
-        .for_each(|record| { csvwriter.serialize(record).unwrap(); });
-
-    // Print the Ballots
-    let ballots_path = Path::new(ballots_filename);
-    let mut csvwriter = csv::Writer::from_path(ballots_path)?;
-    ballots.iter()
-        .for_each(|ballot| {
-            print::print_ballot(&ballot, &pollconf.question, &pollconf.option1, &pollconf.option2); 
-            let record = CompleteBallotRow {
-                serial: string_from_ballotserial(&ballot.serial, pollconf.num_ballots),
-                choice1_votecode: string_from_votecode(&ballot.choice1.votecode),
-                choice1_value: string_from_choicevalue(&ballot.choice1.choice),
-                choice2_votecode: string_from_votecode(&ballot.choice2.votecode),
-                choice2_value: string_from_choicevalue(&ballot.choice2.choice)
-            };
-            debug!("{:?}", record);
-            csvwriter.serialize(record).unwrap();
-        });
-
-    // No need to update the poll state since this is not a public operation.
-    Ok(())
+    write_print_files(&pollconf, &poll_secrets, &aead_pmk, addresses_filename, ballots_filename, pdf_output, mailing)
 }
 
 
-pub fn generate_print_files_run(secured_poll_configuration: SecuredPollConfiguration, poll_master_key: PollMasterKey, aead_pmk: AEADKey, addresses_filename: &str, ballots_filename: &str) -> Result<()> {
-
-    // Decrypt poll configuration state.
-    let pollconf_aead_values = secured_poll_configuration.encrypted_poll_configuration.values()?;
-    let serialized_pollconf = aead_decrypt(&aead_pmk, &pollconf_aead_values)?;
-    let pollconf: PollConfiguration = serde_yaml::from_slice(&serialized_pollconf).unwrap();
-    
+/// Step 3 proper: select the voters, deal them and the ballots over the
+/// mail pieces, and write the files of each vendor and the decoy packet.
+fn write_print_files(pollconf: &PollConfiguration, poll_secrets: &PollSecrets, aead_pmk: &AEADKey, addresses_filename: &str, ballots_filename: &str, pdf_output: &BallotPdfOutput, mailing: &MailingOutput) -> Result<()> {
     assert!(pollconf.poll_state.summands_drawn,
         "Summands must be drawn to generate voters and print content for public audit.");
 
-    // Derive the poll secrets.
-    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key);
-    
+    // Stage the files of each vendor when they are sealed.
+    let staging: Option<StagingDirectory> = match &mailing.packages {
+        Some(packages) => {
+            let directory = Path::new(packages).join(STAGING_DIRECTORY);
+            std::fs::create_dir_all(&directory)?;
+            Some(StagingDirectory(directory))
+        },
+        None => None
+    };
+    let staged = |directory: &Path, vendor: &str, filename: &str| -> Result<PathBuf> {
+        let name = Path::new(filename).file_name().ok_or_else(|| format!("File name missing from {}.", filename))?;
+        Ok(directory.join(vendor).join(name))
+    };
+    let (addresses_path, ballots_path, mailing, pdf_output): (PathBuf, PathBuf, MailingOutput, BallotPdfOutput) = match &staging {
+        Some(StagingDirectory(directory)) => (
+            staged(directory, ADDRESSING_VENDOR, addresses_filename)?,
+            staged(directory, BALLOT_PRINTER, ballots_filename)?,
+            MailingOutput { directory: directory.join(ADDRESSING_VENDOR).display().to_string(), ..mailing.clone() },
+            BallotPdfOutput { directory: directory.join(BALLOT_PRINTER).display().to_string(), ..pdf_output.clone() }
+        ),
+        None => (PathBuf::from(addresses_filename), PathBuf::from(ballots_filename), mailing.clone(), pdf_output.clone())
+    };
+    let (mailing, pdf_output) = (&mailing, &pdf_output);
+
     // Regenerate the Committed Summands.
-    let committed_summands = CommittedSummands::from_csprng(
+    let committed_summands = CommittedSummands::from_csprng_strata(
         poll_secrets.summands_root,
-        pollconf.num_ballots,
-        pollconf.voter_roster_size);
+        &pollconf.summand_strata());
     // Regenerate the Drawn Summands.
     let drawn_summands_seed: Vec<u8> = {
         let seed = pollconf.drawn_summands_seed.clone();
         hex::decode(seed.unwrap())?
     };
-    let drawn_summands = DrawnSummands::from_csprng(
+    let drawn_summands = DrawnSummands::from_csprng_strata(
         CSPRNGSeed::from_vec(&drawn_summands_seed),
-        pollconf.num_ballots,
-        pollconf.voter_roster_size);
+        &pollconf.summand_strata());
     // Select the Voters.
-    let roster_indices = select_voters(
+    let strata = pollconf.sampling_strata();
+    let (members, collisions) = select_members_counting_collisions(
         committed_summands,
         drawn_summands,
-        pollconf.voter_roster_size)?;
+        &strata)?;
+    let roster_indices = member_positions(
+        bound_roster_records(pollconf, aead_pmk)?,
+        pollconf.stratification.as_ref(),
+        &strata,
+        &members)?;
     debug!("Selected Voters: {:?}", roster_indices);
+    println!("Selection collisions resolved by probing: {}", collisions);
 
-    // Generate the Ballots.
+    // Generate the Ballots of each question.
     let serials: Vec<BallotSerial> = (0..pollconf.num_ballots).collect();
-    let votecodes: Vec<VoteCode> = generate_votecodes(
-        poll_secrets.votecode_root,
-        2 * pollconf.num_ballots);
-    let ballots = generate_ballots(&serials, &votecodes);
+    let ballots: Vec<Vec<Ballot>> = (0..pollconf.num_questions()).into_iter()
+        .map(|q| {
+            let votecodes: Vec<VoteCode> = generate_votecodes(
+                poll_secrets.question_secrets[q].votecode_root,
+                pollconf.num_rows(q),
+                &pollconf.votecode_format);
+            generate_ballots(&serials, &votecodes, pollconf.num_options(q))
+        }).collect();
     debug!("Ballots: {:?}", ballots);
 
+    // Deal the Voters and Ballots over the Mail Pieces
+    let voters: Vec<VoterInfo> = bound_roster_selection(pollconf, aead_pmk, &roster_indices)?;
+    assert!(voters.len() == pollconf.num_ballots,
+        "Every ballot must go to a sampled voter.");
+    let pieces = MailPieces::derive(poll_secrets.mailing_root, pollconf.num_ballots);
+    // The ballot of each serial is printed in the language of its voter.
+    let mut serial_languages: Vec<String> = vec!["".to_owned(); pollconf.num_ballots];
+    (0..pieces.len()).into_iter()
+        .for_each(|piece| {
+            serial_languages[pieces.serials[piece]] = voters[pieces.voters[piece]].language.clone();
+        });
+
     // Print the Address Labels
-    let roster: VoterRoster = {
-        let encoded_roster = pollconf.voter_roster.clone().unwrap();
-        let decoded_roster = base64::decode(&encoded_roster.0)?;
-        let serialized_roster = str::from_utf8(&decoded_roster)?;
-        serde_yaml::from_str(serialized_roster)?
-    };
-    let addresses: Vec<AddressLabel> = roster_indices.iter()
-        .map(|&n| { roster.records[n].voter_info.clone() })
-        .map(|voter| {
-            AddressLabel {
-                last_name: voter.last_name,
-                first_name: voter.first_name,
-                address1: voter.street_address,
-                address2: "".to_owned(),
-                city: voter.city,
-                state: voter.state,
-                zip_code: voter.zip_code
-            }
-        }).collect();
-    let address_labels_path = Path::new(addresses_filename);
+    // The addresses are listed in mail piece order, which is not the sampled order.
+    let mut addresses: Vec<AddressLabel> = (0..pieces.len()).into_iter()
+        .map(|piece| AddressLabel::from_voter(MailPieces::number(piece), voters[pieces.voters[piece]].clone()))
+        .collect();
+    if let Some(intelligent_mail) = &mailing.intelligent_mail {
+        intelligent_mail.check(addresses.len())?;
+        addresses.iter_mut().enumerate()
+            .for_each(|(piece, address)| {
+                address.intelligent_mail = intelligent_mail.field(piece, &address.zip_code);
+            });
+    }
+    if let Some(directory) = addresses_path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+    let address_labels_path = addresses_path.as_path();
     let mut csvwriter = csv::Writer::from_path(address_labels_path)?;
     addresses.iter()
         .for_each(|record| { csvwriter.serialize(record).unwrap(); });
+    csvwriter.flush()?;
+    let mut addressing_files: Vec<String> = vec![address_labels_path.display().to_string()];
+    if let Some(format) = &mailing.label_sheet {
+        let (file, overflows) = print_label_sheets(&addresses, format, &mailing.directory)?;
+        println!("Address label sheets: {}", file);
+        println!("Addresses cut to fit their label: {}", overflows);
+        addressing_files.push(file);
+    }
+    if let Some(window) = &mailing.envelope_window {
+        let (file, overflows) = print_envelope_pages(&addresses, window, &mailing.directory)?;
+        println!("Envelope address pages: {}", file);
+        println!("Addresses cut to fit the envelope window: {}", overflows);
+        addressing_files.push(file);
+    }
+
+    // The texts of each voter language, in that language when translated.
+    let mut language_texts: BTreeMap<String, (BallotTexts, bool)> = BTreeMap::new();
+    serial_languages.iter()
+        .for_each(|language| {
+            language_texts.entry(language.clone())
+                .or_insert_with(|| BallotTexts::for_language(&pollconf.questions, &pollconf.ballot_template, &pollconf.languages, language));
+        });
+    let untranslated = serial_languages.iter()
+        .filter(|language| !language_texts[language.as_str()].1)
+        .count();
+    println!("Ballots in the default language for lack of a translation: {}", untranslated);
 
     // Print the Ballots
-    let ballots_path = Path::new(ballots_filename);
-    let mut csvwriter = csv::Writer::from_path(ballots_path)?;
-    ballots.iter()
-        .for_each(|ballot| {
-            print::print_ballot(&ballot, &pollconf.question, &pollconf.option1, &pollconf.option2); 
-            let record = CompleteBallotRow {
-                serial: string_from_ballotserial(&ballot.serial, pollconf.num_ballots),
-                choice1_votecode: string_from_votecode(&ballot.choice1.votecode),
-                choice1_value: string_from_choicevalue(&ballot.choice1.choice),
-                choice2_votecode: string_from_votecode(&ballot.choice2.votecode),
-                choice2_value: string_from_choicevalue(&ballot.choice2.choice)
+    // The choices of ballots.csv keep the option labels of the poll.
+    // All questions are printed on the same mailed ballot.
+    let ballots_of = |serial: BallotSerial| -> Vec<Ballot> {
+        ballots.iter()
+            .map(|question_ballots| question_ballots[serial].clone())
+            .collect()
+    };
+    let characters: std::collections::BTreeSet<char> = language_texts.values()
+        .flat_map(|(texts, _)| texts.characters())
+        .collect();
+    let ballot_fonts = BallotFonts::load(&pollconf.ballot_template)?.subset(&characters)?;
+    if let Some(directory) = ballots_path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+    let mut csvwriter = csv::Writer::from_path(&ballots_path)?;
+    let mut printer_files: Vec<String> = vec![ballots_path.display().to_string()];
+    serials.iter()
+        .map(|&serial| -> Result<()> {
+            let serial_ballots: Vec<Ballot> = ballots_of(serial);
+            if !pdf_output.batched {
+                let (texts, _) = &language_texts[serial_languages[serial].as_str()];
+                printer_files.extend(print::print_ballot(&serial_ballots, texts, &pollconf.votecode_format, pollconf.voting_url.as_ref(), &pollconf.ballot_template, &ballot_fonts, pdf_output)?);
+            }
+            // One row per choice, so ballots of any number of options share a layout.
+            serial_ballots.iter().zip(pollconf.questions.iter()).enumerate()
+                .for_each(|(q, (ballot, question))| {
+                    ballot.choices.iter()
+                        .for_each(|choice| {
+                            let record = SplitBallotRow {
+                                serial: string_from_ballotserial(&ballot.serial, pollconf.num_ballots),
+                                question: q + 1,
+                                votecode: string_from_votecode(&choice.votecode, &pollconf.votecode_format),
+                                choice: string_from_choicevalue(&choice.choice, &question.options)
+                            };
+                            debug!("{:?}", record);
+                            csvwriter.serialize(record).unwrap();
+                        });
+                });
+            Ok(())
+        })
+        .collect::<Result<()>>()?;
+    csvwriter.flush()?;
+
+    // A batch is printed in mail piece order, so it stacks with the addresses.
+    if pdf_output.batched {
+        let batch: Vec<(Vec<Ballot>, &BallotTexts)> = pieces.serials.iter()
+            .map(|&serial| (ballots_of(serial), &language_texts[serial_languages[serial].as_str()].0))
+            .collect();
+        let files = print::print_ballots_batched(&batch, &pollconf.votecode_format, pollconf.voting_url.as_ref(), &pollconf.ballot_template, &ballot_fonts, pdf_output)?;
+        files.iter()
+            .for_each(|file| println!("Ballot PDF: {}", file));
+        printer_files.extend(files);
+    }
+
+    // Print the Ballot Order of the ballot printer
+    std::fs::create_dir_all(&pdf_output.directory)?;
+    let ballot_order_path = Path::new(&pdf_output.directory).join(BALLOT_ORDER_FILE);
+    let mut csvwriter = csv::Writer::from_path(&ballot_order_path)?;
+    pieces.serials.iter().enumerate()
+        .for_each(|(piece, serial)| {
+            let record = BallotOrderRow {
+                mail_piece: MailPieces::number(piece),
+                serial: string_from_ballotserial(serial, pollconf.num_ballots)
+            };
+            csvwriter.serialize(record).unwrap();
+        });
+    csvwriter.flush()?;
+    printer_files.push(ballot_order_path.display().to_string());
+
+    // Seal the Vendor Packages
+    if let Some(directory) = &mailing.packages {
+        vec![(ADDRESSING_VENDOR, &addressing_files), (BALLOT_PRINTER, &printer_files)].into_iter()
+            .map(|(vendor, files)| -> Result<()> {
+                let mut package = VendorPackage::new(vendor);
+                files.iter()
+                    .map(|file| package.add_file(&Path::new(file)))
+                    .collect::<Result<()>>()?;
+                let password = read_trustee_password(vendor);
+                let file = package.seal(&password)?.to_file(directory)?;
+                println!("Vendor package: {}", file);
+                Ok(())
+            })
+            .collect::<Result<()>>()?;
+        // Drop the staged plain files.
+        drop(staging);
+    } else {
+        println!("Warning: the addresses and ballots are written unencrypted to {}, {} and {}; \
+            pass --packages DIR to seal them for the vendors.", addressing_files.join(", "), ballots_filename, pdf_output.directory);
+    }
+
+    // Print the Decoy Packet
+    let decoys: Vec<BallotSerial> = generate_decoy_serials(
+        poll_secrets.decoy_root,
+        pollconf.num_decoys,
+        pollconf.num_ballots);
+    print::print_decoy_stickers(&decoys, pollconf.num_ballots, &pdf_output.decoy_directory)?;
+    let decoy_serials_path = {
+        let mut pathbuf = PathBuf::new();
+        pathbuf.push(&pdf_output.decoy_directory);
+        pathbuf.push("decoy_serials");
+        pathbuf.set_extension("csv");
+        pathbuf.into_boxed_path()
+    };
+    let mut csvwriter = csv::Writer::from_path(&decoy_serials_path)?;
+    decoys.iter()
+        .for_each(|serial| {
+            let record = DecoySerialRow {
+                serial: string_from_ballotserial(serial, pollconf.num_ballots)
             };
-            debug!("{:?}", record);
             csvwriter.serialize(record).unwrap();
         });
 
     // No need to update the poll state since this is not a public operation.
     Ok(())
 }
-
@@ -5,7 +5,10 @@ use super::*;
 
 
 
-pub fn generate_tally_audit(pollconf_filename: &str, seed: &str) -> Result<()> {
+pub fn generate_tally_audit(pollconf_filename: &str, seed: Option<&str>, seed_transcript: Option<&str>, dry_run: bool) -> Result<()> {
+    let (seed, transcript) = resolve_seed(seed, seed_transcript, AUDITED_COLUMNS_PURPOSE)?;
+    let seed: &str = &seed;
+
     let pollconf_path = Path::new(pollconf_filename);
 
     // Read poll configuration file.
@@ -16,6 +19,8 @@ pub fn generate_tally_audit(pollconf_filename: &str, seed: &str) -> Result<()> {
 
     // Ensure the data directory exists.
     let datadir_path = ensure_poll_data_directory_exists(&secured_poll_configuration, &aead_pmk)?;
+    let datadir_path = if dry_run { ensure_dry_run_directory_exists(&datadir_path)? } else { datadir_path };
+    let mut report = DryRunReport::new("Step 7: Generate audited plane columns", &datadir_path);
 
     // Decrypt poll configuration state.
     let pollconf_aead_values = secured_poll_configuration.encrypted_poll_configuration.values()?;
@@ -29,7 +34,20 @@ pub fn generate_tally_audit(pollconf_filename: &str, seed: &str) -> Result<()> {
     let audited_columns_seed: Vec<u8> = hex::decode(seed)?;
     assert!(audited_columns_seed.len() == CSPRNGSeed::SIZE,
         "Seed for Audited Columns must be {} bytes long.", CSPRNGSeed::SIZE);
+    report.state_transition("audited_columns_seed", &pollconf.audited_columns_seed, &Some(seed));
     pollconf.audited_columns_seed = Some(seed.to_owned());
+    // Keep the seed transcript, so anyone can re-derive the seed.
+    if let Some(transcript) = transcript {
+        let transcript_path = {
+            let mut pathbuf = PathBuf::new();
+            pathbuf.push(&datadir_path);
+            pathbuf.push("audited_columns_seed_transcript");
+            pathbuf.set_extension("yaml");
+            pathbuf.into_boxed_path()
+        };
+        transcript.to_file(&transcript_path)?;
+        report.file(&transcript_path);
+    }
 
     // Draw the Audited Column.
     let audited_columns_path = {
@@ -42,7 +60,7 @@ pub fn generate_tally_audit(pollconf_filename: &str, seed: &str) -> Result<()> {
     let audited_columns: Vec<usize> = {
         let seed = CSPRNGSeed::from_vec(&audited_columns_seed);
         let mut prng = CSPRNG::from_csprng_seed(seed);
-        (0..NUMBER_OF_PLANES).into_iter().map(|_| prng.gen_range(0, 2)).collect()
+        (0..pollconf.num_planes).into_iter().map(|_| prng.gen_range(0, 2)).collect()
     };
     let audited_columns_readable: Vec<String> = {
         audited_columns.iter().enumerate().map(|(n, &bit)| {
@@ -51,8 +69,13 @@ pub fn generate_tally_audit(pollconf_filename: &str, seed: &str) -> Result<()> {
         }).collect()
     };
     serde_yaml::to_writer(
-        File::create(audited_columns_path)?,
+        File::create(&audited_columns_path)?,
         &audited_columns_readable)?;
+    report.file(&audited_columns_path);
+    if dry_run {
+        report.print();
+        return Ok(());
+    }
 
     // Re-encrypt the poll configuration.
     let serialized_pollconf = serde_yaml::to_string(&pollconf)?;
@@ -100,7 +123,7 @@ pub fn generate_tally_audit_run(pollconf_filename: &str, seed: &str, mut secured
     let audited_columns: Vec<usize> = {
         let seed = CSPRNGSeed::from_vec(&audited_columns_seed);
         let mut prng = CSPRNG::from_csprng_seed(seed);
-        (0..NUMBER_OF_PLANES).into_iter().map(|_| prng.gen_range(0, 2)).collect()
+        (0..pollconf.num_planes).into_iter().map(|_| prng.gen_range(0, 2)).collect()
     };
     let audited_columns_readable: Vec<String> = {
         audited_columns.iter().enumerate().map(|(n, &bit)| {
@@ -56,49 +56,112 @@ pub fn ensure_poll_data_directory_exists(secured_poll_configuration: &SecuredPol
     Ok(identifier)
 }
 
-/// Generate the column planes using the poll secrets.
-pub fn generate_column_planes(secrets: &PollSecrets, num_planes: usize, num_rows: usize, num_decoys: usize) -> Result<Vec<Plane>> {
-    fn generate_column_plane(secrets: &PollSecrets, plane_num: usize, votecodes: Vec<VoteCode>, decoys: Vec<BallotSerial>) -> Result<Plane> {
-        let num_ballots: usize = votecodes.len() / 2;
+
+/// Ballot template of a new poll, read from the file it names (relative
+/// to the new poll configuration) or the default layout.
+pub fn read_ballot_template(pollconf_path: &Path, new_poll_configuration: &NewPollConfiguration) -> Result<BallotTemplate> {
+    let template = match &new_poll_configuration.ballot_template {
+        Some(template_filename) => {
+            let template_path = pollconf_path.parent()
+                .unwrap_or(Path::new(""))
+                .join(template_filename);
+            BallotTemplate::from_file(&template_path)?
+        },
+        None => BallotTemplate::default()
+    };
+    // The questions are printed with the template, without shaping.
+    for question in new_poll_configuration.questions.iter() {
+        let texts = std::iter::once(&question.question)
+            .chain(question.options.iter())
+            .chain(question.translations.values().flat_map(|translation| {
+                std::iter::once(&translation.question).chain(translation.options.iter())
+            }));
+        for text in texts {
+            check_unshaped_text(text)?;
+        }
+    }
+    Ok(template)
+}
+
+
+/// Seed of a step: given in hex, or taken from a seed transcript, which
+/// is re-derived from its public inputs and checked against its purpose.
+pub fn resolve_seed(seed: Option<&str>, transcript_filename: Option<&str>, purpose: &str) -> Result<(String, Option<SeedTranscript>)> {
+    match (seed, transcript_filename) {
+        (_, Some(filename)) => {
+            let transcript = SeedTranscript::from_file(&Path::new(filename))?;
+            transcript.verify()?;
+            if transcript.purpose != purpose {
+                return Err(From::from(format!("Seed transcript is for {}, not {}.", transcript.purpose, purpose)));
+            }
+            if seed.map_or(false, |seed| seed != transcript.seed) {
+                return Err(From::from("Seed does not match the seed transcript."));
+            }
+            Ok((transcript.seed.clone(), Some(transcript)))
+        },
+        (Some(seed), None) => Ok((seed.to_owned(), None)),
+        (None, None) => Err(From::from("A seed or a seed transcript is needed."))
+    }
+}
+
+
+/// Ensure the scratch directory for a dry run exists.
+///
+/// The scratch directory sits next to the poll data directory, so a
+/// rehearsal never overwrites the files that will be published.
+pub fn ensure_dry_run_directory_exists(datadir_path: &str) -> Result<String> {
+    let scratch_path = format!("{}-dry-run", datadir_path);
+    match DirBuilder::new().create(Path::new(&scratch_path)) {
+        Ok(_) => (),
+        Err(err) => {
+            match err.kind() {
+                ErrorKind::AlreadyExists => (),
+                _ => return Err(Box::new(err))
+            }
+        }
+    }
+    Ok(scratch_path)
+}
+
+/// Name of the file holding a column plane of a poll question.
+pub fn plane_file_name(prefix: &str, question: usize, plane: usize) -> String {
+    format!("{}_q{:02}_plane_{:02}", prefix, question + 1, plane + 1)
+}
+
+/// Generate the column planes of a poll question using the poll secrets.
+///
+/// Each plane has one row per option per ballot, ordered by ballot serial
+/// and then by option. The decoy ballots are the same for every question.
+pub fn generate_column_planes(secrets: &PollSecrets, question: usize, num_planes: usize, num_ballots: usize, num_options: usize, num_decoys: usize, votecode_format: &VoteCodeFormat) -> Result<Vec<Plane>> {
+    fn generate_column_plane(secrets: &QuestionSecrets, plane_num: usize, num_options: usize, votecodes: Vec<VoteCode>, decoys: Vec<BallotSerial>) -> Result<Plane> {
+        let num_ballots: usize = votecodes.len() / num_options;
         let psecrets = secrets.plane_secrets[plane_num].resolve(votecodes.len());
         // Column 1
         let col1: Vec<Column1Entry> = {
             let unencrypted: Vec<Column1Entry> = votecodes.iter().enumerate()
-                .map(|(n, &vc)| {
-                    Column1Entry::Entry(format!("{}: {}",
-                        string_from_ballotserial(&(n / 2), num_ballots),
-                        string_from_votecode(&vc)
-                    ))
-                    /*
+                .map(|(n, vc)| {
                     Column1Entry::Entry(SerialVoteCode {
-                        serial: n / 2,
-                        votecode: vc
+                        serial: n / num_options,
+                        votecode: vc.clone()
                     })
-                    */
                 }).collect();
             unencrypted.iter().zip(psecrets.col1_keys.iter()).zip(psecrets.col1_nonce.iter())
                 .map(|((entry, key), nonce)| {
-                    entry.encrypt(key, nonce).unwrap()
+                    entry.encrypt(key, nonce, num_ballots).unwrap()
                 }).collect::<Vec<Column1Entry>>()
         };
         // Column 2
         let col2: Vec<Column2Entry> = votecodes.iter().map(|_| Column2Entry::Empty).collect();
         // Column 3
         let col3: Vec<Column3Entry> = {
-            let unencrypted: Vec<Column3Entry> = votecodes.iter().enumerate().zip(CHOICE_VALUES.iter().cycle())
-                .map(|((n, _), &cv)| {
-                    let serial = n / 2;
+            let unencrypted: Vec<Column3Entry> = votecodes.iter().enumerate()
+                .map(|(n, _)| {
+                    let serial = n / num_options;
                     let tagged_choice = match decoys.contains(&serial) {
                         true => TaggedChoiceValue::Decoy,
-                        false => TaggedChoiceValue::from(cv)
+                        false => TaggedChoiceValue::from(ChoiceValue(n % num_options))
                     };
-                    Column3Entry::Entry(string_from_taggedchoicevalue_padded(&tagged_choice))
-                    /*
-                    Column3Entry::Entry(match decoys.contains(&serial) {
-                        true => TaggedChoiceValue::Decoy,
-                        false => TaggedChoiceValue::from(cv)
-                    })
-                    */
+                    Column3Entry::Entry(string_from_taggedchoicevalue_padded(&tagged_choice, num_options))
                 }).collect();
             unencrypted.iter().zip(psecrets.col3_keys.iter()).zip(psecrets.col3_nonce.iter())
                 .map(|((entry, key), nonce)| {
@@ -113,12 +176,53 @@ pub fn generate_column_planes(secrets: &PollSecrets, num_planes: usize, num_rows
         })
     }
 
-    let votecodes: Vec<VoteCode> = generate_votecodes(secrets.votecode_root, num_rows);
-    let decoys: Vec<BallotSerial> = generate_decoy_serials(secrets.decoy_root, num_decoys, num_rows / 2);
+    let qsecrets = &secrets.question_secrets[question];
+    let votecodes: Vec<VoteCode> = generate_votecodes(qsecrets.votecode_root, num_ballots * num_options, votecode_format);
+    let decoys: Vec<BallotSerial> = generate_decoy_serials(secrets.decoy_root, num_decoys, num_ballots);
 
     Ok((0..num_planes).into_iter()
         .map(|n| {
-            generate_column_plane(secrets, n, votecodes.clone(), decoys.clone()).unwrap()
+            generate_column_plane(qsecrets, n, num_options, votecodes.clone(), decoys.clone()).unwrap()
         }).collect::<Vec<Plane>>())
 }
 
+/// Reveal the roster records of the selected voters and their salts, so
+/// anyone can check them against the salted roster commitment.
+pub fn reveal_selected_voters(pollconf: &PollConfiguration, poll_secrets: &PollSecrets, aead_pmk: &AEADKey, path: &Path) -> Result<()> {
+    // Select the Voters.
+    let committed_summands = CommittedSummands::from_csprng_strata(
+        poll_secrets.summands_root,
+        &pollconf.summand_strata());
+    let drawn_summands_seed: Vec<u8> = {
+        let seed = pollconf.drawn_summands_seed.clone();
+        hex::decode(seed.unwrap())?
+    };
+    let drawn_summands = DrawnSummands::from_csprng_strata(
+        CSPRNGSeed::from_vec(&drawn_summands_seed),
+        &pollconf.summand_strata());
+    let strata = pollconf.sampling_strata();
+    let members = crate::voter_selection::select_members_in_strata(
+        committed_summands,
+        drawn_summands,
+        &strata)?;
+    let roster_indices: std::collections::HashSet<usize> = crate::voter_selection::member_positions(
+        bound_roster_records(pollconf, aead_pmk)?,
+        pollconf.stratification.as_ref(),
+        &strata,
+        &members)?.into_iter().collect();
+
+    // Stream the roster and its salts, in roster order so the file does
+    // not hint at the ballot of each voter.
+    let mut csvwriter = csv::Writer::from_path(path)?;
+    for (record, salt) in bound_roster_records(pollconf, aead_pmk)?.zip(roster_salts(poll_secrets.roster_root)) {
+        let record = record?;
+        if roster_indices.contains(&record.position) {
+            csvwriter.serialize(RevealedRosterFileRow {
+                position: record.position,
+                restricted: RestrictedVoterInfo::from(record.voter_info),
+                salt: hex::encode(&salt.0)
+            })?;
+        }
+    }
+    Ok(())
+}
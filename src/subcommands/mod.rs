@@ -9,16 +9,23 @@ use std::path::{Path, PathBuf};
 use std::fs::{File, DirBuilder};
 use serde::{Serialize, Deserialize};
 use crate::*;
+use crate::seeds::*;
 
 pub mod helpers;
 pub use helpers::*;
 
+pub mod dry_run;
+pub use dry_run::*;
+
 pub mod create_new_poll;
 pub use create_new_poll::*;
 
 pub mod bind_roster;
 pub use bind_roster::*;
 
+pub mod check_roster;
+pub use check_roster::*;
+
 pub mod generate_poll_commitments;
 pub use generate_poll_commitments::*;
 
@@ -28,6 +35,9 @@ pub use generate_drawn_summands::*;
 pub mod generate_print_files;
 pub use generate_print_files::*;
 
+pub mod decoy_requests;
+pub use decoy_requests::*;
+
 pub mod record_audited_ballots;
 pub use record_audited_ballots::*;
 
@@ -40,6 +50,15 @@ pub use generate_tally_audit::*;
 pub mod generate_poll_revelations;
 pub use generate_poll_revelations::*;
 
+pub mod verify_roster;
+pub use verify_roster::*;
+
+pub mod verify_selection;
+pub use verify_selection::*;
+
+pub mod roster_proof;
+pub use roster_proof::*;
+
 pub mod sign;
 pub use sign::*;
 
@@ -49,5 +68,17 @@ pub use proofs::*;
 pub mod audit;
 pub use audit::*;
 
+pub mod seed_derivation;
+pub use seed_derivation::*;
+
+pub mod ballot_url;
+pub use ballot_url::*;
+
+pub mod open_package;
+pub use open_package::*;
+
+pub mod plan;
+pub use plan::*;
+
 pub mod run;
 pub use run::*;
\ No newline at end of file
@@ -0,0 +1,18 @@
+//! # Command: Open Vendor Package
+//!
+//! `open_package` decrypts a vendor package sealed by step 3 with the
+//! password of its vendor and writes out its files, for the addressing
+//! vendor or the ballot printer on their own machine.
+
+use super::*;
+
+
+pub fn open_package(package_filename: &str, output_directory: &str) -> Result<()> {
+    let sealed_package = SealedPackage::from_file(&Path::new(package_filename))?;
+    let password = read_trustee_password(&sealed_package.vendor);
+    let package = sealed_package.open(&password)?;
+    println!("Package of {}:", package.vendor);
+    package.write_files(output_directory)?.iter()
+        .for_each(|file| println!("  {}", file));
+    Ok(())
+}
@@ -0,0 +1,35 @@
+//! # Command: Plan
+//!
+//! `plan` reports how likely tampering with the column planes survives
+//! the random column audit, so the trustees can choose the number of
+//! planes of a poll before creating it.
+
+use super::*;
+use crate::soundness::*;
+
+
+pub fn plan(num_planes: usize, num_rows: usize, target: Option<f64>) -> Result<()> {
+    if num_planes == 0 {
+        return Err(From::from("A poll must have at least one column plane."));
+    }
+    if num_rows == 0 {
+        return Err(From::from("At least one row must be tampered with."));
+    }
+
+    println!("Column planes: {}", num_planes);
+    println!("Tampered rows: {}", num_rows);
+    println!("Survival probability (careful adversary): {:e}",
+        worst_case_survival(num_planes));
+    println!("Survival probability (rows tampered at random columns): {:e}",
+        random_tampering_survival(num_planes, num_rows));
+
+    if let Some(target) = target {
+        let planes = planes_for_target(target)?;
+        println!("Planes needed for survival probability at most {:e}: {}", target, planes);
+        if planes > num_planes {
+            println!("Warning: {} planes fall short of the target.", num_planes);
+        }
+    }
+
+    Ok(())
+}
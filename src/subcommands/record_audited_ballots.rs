@@ -10,7 +10,7 @@ pub struct AuditedBallotRecord {
     serial: BallotSerial
 }
 
-pub fn record_audited_ballots(pollconf_filename: &str, audited_ballots_filename: &str, force: bool, xxn: &str) -> Result<()> {
+pub fn record_audited_ballots(pollconf_filename: &str, audited_ballots_filename: &str, force: bool, xxn: &str, dry_run: bool) -> Result<()> {
     let pollconf_path = Path::new(pollconf_filename);
 
     // Read poll configuration file.
@@ -21,6 +21,8 @@ pub fn record_audited_ballots(pollconf_filename: &str, audited_ballots_filename:
 
     // Ensure the data directory exists.
     let datadir_path = ensure_poll_data_directory_exists(&secured_poll_configuration, &aead_pmk)?;
+    let datadir_path = if dry_run { ensure_dry_run_directory_exists(&datadir_path)? } else { datadir_path };
+    let mut report = DryRunReport::new("Step 4: Record audited (spoiled) ballots", &datadir_path);
 
     // Decrypt poll configuration state.
     let pollconf_aead_values = secured_poll_configuration.encrypted_poll_configuration.values()?;
@@ -33,7 +35,7 @@ pub fn record_audited_ballots(pollconf_filename: &str, audited_ballots_filename:
         "Audited ballots already recorded. To re-record, pass --force.");
 
     // Derive the poll secrets.
-    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key);
+    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key, pollconf.num_questions(), pollconf.num_planes);
     
     // Record audited ballots.
     let audited_ballots = {
@@ -46,54 +48,94 @@ pub fn record_audited_ballots(pollconf_filename: &str, audited_ballots_filename:
                 else { None }
             }).collect::<Vec<BallotSerial>>()
     };
-    pollconf.audited_ballots = Some(audited_ballots.iter()
+    let recorded_audited_ballots: Vec<String> = audited_ballots.iter()
         .map(|serial| serial.to_string())
-        .collect());
+        .collect();
+    report.state_transition("audited_ballots", &pollconf.audited_ballots, &recorded_audited_ballots);
+    pollconf.audited_ballots = Some(recorded_audited_ballots);
 
-    // Post the Column Planes.
-    let column_planes: Vec<Plane> = generate_column_planes(
-        &poll_secrets,
-        NUMBER_OF_PLANES,
-        2 * pollconf.num_ballots,
-        pollconf.num_decoys)?;
-    // Filter planes.
-    column_planes.iter().enumerate()
-        .for_each(|(n, plane)| {
-            let posted_planes_path = {
-                let mut pathbuf = PathBuf::new();
-                pathbuf.push(&datadir_path);
-                pathbuf.push(format!("print_audit_plane_{:02}", n+1));
-                pathbuf.set_extension("csv");
-                pathbuf.into_boxed_path()
-            };
-            let posted_keys_path = {
-                let mut pathbuf = PathBuf::new();
-                pathbuf.push(&datadir_path);
-                pathbuf.push(format!("print_audit_plane_{:02}_keys", n+1));
-                pathbuf.set_extension("csv");
-                pathbuf.into_boxed_path()
-            };
-            let psecrets = poll_secrets.plane_secrets[n].resolve(plane.len());
-            let filter = PlaneFilter::from(&psecrets.col1_keys, &psecrets.col3_keys)
-                .decrypt_serials(&audited_ballots);
-
-            let permuted_plane = plane.decrypt(&filter).permute(&psecrets.permutation);
-            let mut csvwriter = csv::Writer::from_path(posted_planes_path).unwrap();
-            permuted_plane.rows.iter()
-                .for_each(|rec| {
-                    csvwriter.serialize(rec.serializable(pollconf.num_ballots)).unwrap();
-                });
+    let mut column_planes: Vec<Plane> = Vec::new();
+    // Post the Column Planes of each question.
+    for q in 0..pollconf.num_questions() {
+        let question_planes: Vec<Plane> = generate_column_planes(
+            &poll_secrets,
+            q,
+            pollconf.num_planes,
+            pollconf.num_ballots,
+            pollconf.num_options(q),
+            pollconf.num_decoys,
+            &pollconf.votecode_format)?;
+        // Filter planes.
+        question_planes.iter().enumerate()
+            .map(|(n, plane)| -> Result<()> {
+                let posted_planes_path = {
+                    let mut pathbuf = PathBuf::new();
+                    pathbuf.push(&datadir_path);
+                    pathbuf.push(plane_file_name("print_audit", q, n));
+                    pathbuf.set_extension("csv");
+                    pathbuf.into_boxed_path()
+                };
+                let posted_keys_path = {
+                    let mut pathbuf = PathBuf::new();
+                    pathbuf.push(&datadir_path);
+                    pathbuf.push(format!("{}_keys", plane_file_name("print_audit", q, n)));
+                    pathbuf.set_extension("csv");
+                    pathbuf.into_boxed_path()
+                };
+                let psecrets = poll_secrets.question_secrets[q].plane_secrets[n].resolve(plane.len());
+                let filter = PlaneFilter::from(&psecrets.col1_keys, &psecrets.col3_keys)
+                    .decrypt_serials(&audited_ballots, pollconf.num_options(q));
+
+                let permuted_plane = plane.decrypt(&filter)?.permute(&psecrets.permutation);
+                let mut csvwriter = csv::Writer::from_path(&posted_planes_path).unwrap();
+                permuted_plane.rows.iter()
+                    .for_each(|rec| {
+                        csvwriter.serialize(rec.serializable(pollconf.num_ballots)).unwrap();
+                    });
+                report.file(&posted_planes_path);
             
-            let permuted_filter = filter.permute(&psecrets.permutation);
-            let mut csvwriter = csv::Writer::from_path(posted_keys_path).unwrap();
-            permuted_filter.serializable().iter()
-                .for_each(|rec| {
-                    csvwriter.serialize(rec).unwrap();
-                });
-        });
+                let permuted_filter = filter.permute(&psecrets.permutation);
+                let mut csvwriter = csv::Writer::from_path(&posted_keys_path).unwrap();
+                permuted_filter.serializable().iter()
+                    .for_each(|rec| {
+                        csvwriter.serialize(rec).unwrap();
+                    });
+                report.file(&posted_keys_path);
+                Ok(())
+            }).collect::<Result<()>>()?;
+        column_planes.extend(question_planes);
+    }
 
     // Update the poll state.
+    report.state_transition("ceremony_conducted", &pollconf.poll_state.ceremony_conducted, &true);
     pollconf.poll_state.ceremony_conducted = true;
+    if dry_run {
+        // Build the commitment without posting it to the ledger.
+        let roster_tree = blockchain::roster_tree(&pollconf, &poll_secrets, &aead_pmk)?;
+        let merkle_tree = blockchain::commitment_tree(&pollconf, &roster_tree, &column_planes)?;
+        let merkle_tree_path = {
+            let mut pathbuf = PathBuf::new();
+            pathbuf.push(&datadir_path);
+            pathbuf.push("merkle");
+            pathbuf.set_extension("yaml");
+            pathbuf.into_boxed_path()
+        };
+        blockchain::merkle::store_tree(&merkle_tree, merkle_tree_path.display().to_string())?;
+        report.file(&merkle_tree_path);
+        let roster_tree_path = {
+            let mut pathbuf = PathBuf::new();
+            pathbuf.push(&datadir_path);
+            pathbuf.push("roster_merkle");
+            pathbuf.set_extension("yaml");
+            pathbuf.into_boxed_path()
+        };
+        blockchain::merkle::store_tree(&roster_tree, roster_tree_path.display().to_string())?;
+        report.file(&roster_tree_path);
+        report.ledger_post(format!("Merkle root {} to the network configured in \"{}\", committing roster root {}",
+            hex::encode(merkle_tree.root()), xxn, hex::encode(roster_tree.root())));
+        report.print();
+        return Ok(());
+    }
     // Re-encrypt the poll configuration.
     let serialized_pollconf = serde_yaml::to_string(&pollconf)?;
     let secure_serialized_pollconf = AEADString::from_values(
@@ -106,7 +148,7 @@ pub fn record_audited_ballots(pollconf_filename: &str, audited_ballots_filename:
         File::create(pollconf_path)?,
         &secured_poll_configuration)?;
 
-    blockchain::commit(xxn, pollconf, column_planes)?;
+    blockchain::commit(xxn, pollconf, &poll_secrets, &aead_pmk, column_planes)?;
 
     Ok(())
 }
@@ -128,7 +170,7 @@ pub fn record_audited_ballots_run(pollconf_filename: &str, mut secured_poll_conf
         "Audited ballots already recorded.");
 
     // Derive the poll secrets.
-    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key);
+    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key, pollconf.num_questions(), pollconf.num_planes);
     
     // Record audited ballots.
     let audited_ballots = {
@@ -145,47 +187,55 @@ pub fn record_audited_ballots_run(pollconf_filename: &str, mut secured_poll_conf
         .map(|serial| serial.to_string())
         .collect());
 
-    // Post the Column Planes.
-    let column_planes: Vec<Plane> = generate_column_planes(
-        &poll_secrets,
-        NUMBER_OF_PLANES,
-        2 * pollconf.num_ballots,
-        pollconf.num_decoys)?;
-    // Filter planes.
-    column_planes.iter().enumerate()
-        .for_each(|(n, plane)| {
-            let posted_planes_path = {
-                let mut pathbuf = PathBuf::new();
-                pathbuf.push(&datadir_path);
-                pathbuf.push(format!("print_audit_plane_{:02}", n+1));
-                pathbuf.set_extension("csv");
-                pathbuf.into_boxed_path()
-            };
-            let posted_keys_path = {
-                let mut pathbuf = PathBuf::new();
-                pathbuf.push(&datadir_path);
-                pathbuf.push(format!("print_audit_plane_{:02}_keys", n+1));
-                pathbuf.set_extension("csv");
-                pathbuf.into_boxed_path()
-            };
-            let psecrets = poll_secrets.plane_secrets[n].resolve(plane.len());
-            let filter = PlaneFilter::from(&psecrets.col1_keys, &psecrets.col3_keys)
-                .decrypt_serials(&audited_ballots);
-
-            let permuted_plane = plane.decrypt(&filter).permute(&psecrets.permutation);
-            let mut csvwriter = csv::Writer::from_path(posted_planes_path).unwrap();
-            permuted_plane.rows.iter()
-                .for_each(|rec| {
-                    csvwriter.serialize(rec.serializable(pollconf.num_ballots)).unwrap();
-                });
+    let mut column_planes: Vec<Plane> = Vec::new();
+    // Post the Column Planes of each question.
+    for q in 0..pollconf.num_questions() {
+        let question_planes: Vec<Plane> = generate_column_planes(
+            &poll_secrets,
+            q,
+            pollconf.num_planes,
+            pollconf.num_ballots,
+            pollconf.num_options(q),
+            pollconf.num_decoys,
+            &pollconf.votecode_format)?;
+        // Filter planes.
+        question_planes.iter().enumerate()
+            .map(|(n, plane)| -> Result<()> {
+                let posted_planes_path = {
+                    let mut pathbuf = PathBuf::new();
+                    pathbuf.push(&datadir_path);
+                    pathbuf.push(plane_file_name("print_audit", q, n));
+                    pathbuf.set_extension("csv");
+                    pathbuf.into_boxed_path()
+                };
+                let posted_keys_path = {
+                    let mut pathbuf = PathBuf::new();
+                    pathbuf.push(&datadir_path);
+                    pathbuf.push(format!("{}_keys", plane_file_name("print_audit", q, n)));
+                    pathbuf.set_extension("csv");
+                    pathbuf.into_boxed_path()
+                };
+                let psecrets = poll_secrets.question_secrets[q].plane_secrets[n].resolve(plane.len());
+                let filter = PlaneFilter::from(&psecrets.col1_keys, &psecrets.col3_keys)
+                    .decrypt_serials(&audited_ballots, pollconf.num_options(q));
+
+                let permuted_plane = plane.decrypt(&filter)?.permute(&psecrets.permutation);
+                let mut csvwriter = csv::Writer::from_path(posted_planes_path).unwrap();
+                permuted_plane.rows.iter()
+                    .for_each(|rec| {
+                        csvwriter.serialize(rec.serializable(pollconf.num_ballots)).unwrap();
+                    });
             
-            let permuted_filter = filter.permute(&psecrets.permutation);
-            let mut csvwriter = csv::Writer::from_path(posted_keys_path).unwrap();
-            permuted_filter.serializable().iter()
-                .for_each(|rec| {
-                    csvwriter.serialize(rec).unwrap();
-                });
-        });
+                let permuted_filter = filter.permute(&psecrets.permutation);
+                let mut csvwriter = csv::Writer::from_path(posted_keys_path).unwrap();
+                permuted_filter.serializable().iter()
+                    .for_each(|rec| {
+                        csvwriter.serialize(rec).unwrap();
+                    });
+                Ok(())
+            }).collect::<Result<()>>()?;
+        column_planes.extend(question_planes);
+    }
 
     // Update the poll state.
     pollconf.poll_state.ceremony_conducted = true;
@@ -201,7 +251,7 @@ pub fn record_audited_ballots_run(pollconf_filename: &str, mut secured_poll_conf
         File::create(pollconf_path)?,
         &secured_poll_configuration)?;
 
-    blockchain::commit(xxn, pollconf, column_planes)?;
+    blockchain::commit(xxn, pollconf, &poll_secrets, &aead_pmk, column_planes)?;
 
     Ok(())
 }
\ No newline at end of file
@@ -3,7 +3,6 @@
 //! `record_votes` takes a votes file (list of vote codes) and records them
 //! as part of the secured poll configuration.
 
-use std::cmp::max;
 use super::*;
 
 
@@ -14,22 +13,40 @@ pub struct VoteRecordFileRow {
 
 
 impl VoteRecordFileRow {
-    fn to_votecode(self: &Self) -> VoteCode {
-        let mut votecode: VoteCode = [0; VOTE_CODE_LENGTH];
-        let votecode_vec: Vec<u8> = self.votecode.replace("-", "").split("")
-            .filter_map(|x| {
-                if 0 < x.len() { Some(u8::from_str_radix(x, 10).unwrap()) }
-                else { None }
-            })
-            .collect();
-        let copylen: usize = max(votecode_vec.len(), VOTE_CODE_LENGTH);
-        votecode.copy_from_slice(&votecode_vec[0..copylen]);
-        votecode
+    fn to_votecode(self: &Self, format: &VoteCodeFormat) -> std::result::Result<VoteCode, VoteCodeError> {
+        validate(&self.votecode, format)
     }
 }
 
 
-pub fn record_votes(pollconf_filename: &str, votes_file: &str, force: bool) -> Result<()> {
+/// Read the vote codes of a votes file. Malformed rows and mistyped vote
+/// codes are reported and left out of the tally.
+pub fn read_votes_file(votes_file: &str, format: &VoteCodeFormat) -> Result<Vec<VoteCode>> {
+    let votes_path = Path::new(votes_file);
+    let mut csvreader = csv::Reader::from_path(votes_path)?;
+    let records = csvreader.deserialize::<VoteRecordFileRow>();
+    Ok(records.enumerate()
+        .filter_map(|(n, row)| {
+            // Line 1 holds the header.
+            let row = match row {
+                Ok(row) => row,
+                Err(err) => {
+                    warn!("Skipping malformed row on line {}: {}", n + 2, err);
+                    return None;
+                }
+            };
+            match row.to_votecode(format) {
+                Ok(votecode) => Some(votecode),
+                Err(err) => {
+                    warn!("Skipping vote on line {}: {}", n + 2, err);
+                    None
+                }
+            }
+        }).collect())
+}
+
+
+pub fn record_votes(pollconf_filename: &str, votes_file: &str, force: bool, dry_run: bool) -> Result<()> {
     let pollconf_path = Path::new(pollconf_filename);
 
     // Read poll configuration file.
@@ -40,6 +57,8 @@ pub fn record_votes(pollconf_filename: &str, votes_file: &str, force: bool) -> R
 
     // Ensure the data directory exists.
     let datadir_path = ensure_poll_data_directory_exists(&secured_poll_configuration, &aead_pmk)?;
+    let datadir_path = if dry_run { ensure_dry_run_directory_exists(&datadir_path)? } else { datadir_path };
+    let mut report = DryRunReport::new("Step 6: Record votes", &datadir_path);
 
     // Decrypt poll configuration state.
     let pollconf_aead_values = secured_poll_configuration.encrypted_poll_configuration.values()?;
@@ -52,7 +71,7 @@ pub fn record_votes(pollconf_filename: &str, votes_file: &str, force: bool) -> R
         "Votes already committed. To re-commit, pass --force.");
 
     // Derive the poll secrets.
-    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key);
+    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key, pollconf.num_questions(), pollconf.num_planes);
 
     // Re-construct the audited ballots.
     let audited_ballots: Vec<BallotSerial> = {
@@ -62,69 +81,81 @@ pub fn record_votes(pollconf_filename: &str, votes_file: &str, force: bool) -> R
     };
 
     // Read the Votes file.
-    let votes: Vec<VoteCode> = {
-        let votes_path = Path::new(votes_file);
-        let mut csvreader = csv::Reader::from_path(votes_path)?;
-        let records = csvreader.deserialize::<VoteRecordFileRow>();
-        records.map(|row| { row.unwrap().to_votecode() }).collect()
-    };
+    let votes: Vec<VoteCode> = read_votes_file(votes_file, &pollconf.votecode_format)?;
+    report.state_transition("votes",
+        &pollconf.votes.as_ref().map(|votes| votes.len()),
+        &Some(votes.len()));
     pollconf.votes = Some(votes.clone());
-    let marked_rows: Vec<usize> = {
-        let votecodes: Vec<VoteCode> = generate_votecodes(
-            poll_secrets.votecode_root,
-            2 * pollconf.num_ballots);
-        votecodes.iter().enumerate()
-            .filter_map(|(n, vc)| {
-                debug!("{:?}", vc);
-                if votes.contains(vc) { Some(n) }
-                else { None }
-            }).collect()
-    };
 
-    // Post the Column Planes.
-    let column_planes: Vec<Plane> = generate_column_planes(
-        &poll_secrets,
-        NUMBER_OF_PLANES,
-        2 * pollconf.num_ballots,
-        pollconf.num_decoys)?;
-    // Filter planes.
-    column_planes.iter().enumerate()
-        .for_each(|(n, plane)| {
-            let posted_planes_path = {
-                let mut pathbuf = PathBuf::new();
-                pathbuf.push(&datadir_path);
-                pathbuf.push(format!("vote_plane_{:02}", n+1));
-                pathbuf.set_extension("csv");
-                pathbuf.into_boxed_path()
-            };
-            let posted_keys_path = {
-                let mut pathbuf = PathBuf::new();
-                pathbuf.push(&datadir_path);
-                pathbuf.push(format!("vote_plane_{:02}_keys", n+1));
-                pathbuf.set_extension("csv");
-                pathbuf.into_boxed_path()
-            };
-            let psecrets = poll_secrets.plane_secrets[n].resolve(plane.len());
-            let filter = PlaneFilter::from(&psecrets.col1_keys, &psecrets.col3_keys)
-                .decrypt_serials(&audited_ballots);
-
-            let permuted_plane = plane.mark_rows(&marked_rows).decrypt(&filter).permute(&psecrets.permutation);
-            let mut csvwriter = csv::Writer::from_path(posted_planes_path).unwrap();
-            permuted_plane.rows.iter()
-                .for_each(|rec| {
-                    csvwriter.serialize(rec.serializable(pollconf.num_ballots)).unwrap();
-                });
+    // Post the Column Planes of each question.
+    for q in 0..pollconf.num_questions() {
+        let marked_rows: Vec<usize> = {
+            let votecodes: Vec<VoteCode> = generate_votecodes(
+                poll_secrets.question_secrets[q].votecode_root,
+                pollconf.num_rows(q),
+                &pollconf.votecode_format);
+            votecodes.iter().enumerate()
+                .filter_map(|(n, vc)| {
+                    debug!("{:?}", vc);
+                    if votes.contains(vc) { Some(n) }
+                    else { None }
+                }).collect()
+        };
+        let column_planes: Vec<Plane> = generate_column_planes(
+            &poll_secrets,
+            q,
+            pollconf.num_planes,
+            pollconf.num_ballots,
+            pollconf.num_options(q),
+            pollconf.num_decoys,
+            &pollconf.votecode_format)?;
+        // Filter planes.
+        column_planes.iter().enumerate()
+            .map(|(n, plane)| -> Result<()> {
+                let posted_planes_path = {
+                    let mut pathbuf = PathBuf::new();
+                    pathbuf.push(&datadir_path);
+                    pathbuf.push(plane_file_name("vote", q, n));
+                    pathbuf.set_extension("csv");
+                    pathbuf.into_boxed_path()
+                };
+                let posted_keys_path = {
+                    let mut pathbuf = PathBuf::new();
+                    pathbuf.push(&datadir_path);
+                    pathbuf.push(format!("{}_keys", plane_file_name("vote", q, n)));
+                    pathbuf.set_extension("csv");
+                    pathbuf.into_boxed_path()
+                };
+                let psecrets = poll_secrets.question_secrets[q].plane_secrets[n].resolve(plane.len());
+                let filter = PlaneFilter::from(&psecrets.col1_keys, &psecrets.col3_keys)
+                    .decrypt_serials(&audited_ballots, pollconf.num_options(q));
+
+                let permuted_plane = plane.mark_rows(&marked_rows).decrypt(&filter)?.permute(&psecrets.permutation);
+                let mut csvwriter = csv::Writer::from_path(&posted_planes_path).unwrap();
+                permuted_plane.rows.iter()
+                    .for_each(|rec| {
+                        csvwriter.serialize(rec.serializable(pollconf.num_ballots)).unwrap();
+                    });
+                report.file(&posted_planes_path);
             
-            let permuted_filter = filter.permute(&psecrets.permutation);
-            let mut csvwriter = csv::Writer::from_path(posted_keys_path).unwrap();
-            permuted_filter.serializable().iter()
-                .for_each(|rec| {
-                    csvwriter.serialize(rec).unwrap();
-                });
-        });
+                let permuted_filter = filter.permute(&psecrets.permutation);
+                let mut csvwriter = csv::Writer::from_path(&posted_keys_path).unwrap();
+                permuted_filter.serializable().iter()
+                    .for_each(|rec| {
+                        csvwriter.serialize(rec).unwrap();
+                    });
+                report.file(&posted_keys_path);
+                Ok(())
+            }).collect::<Result<()>>()?;
+    }
 
     // Update the poll state.
+    report.state_transition("votes_committed", &pollconf.poll_state.votes_committed, &true);
     pollconf.poll_state.votes_committed = true;
+    if dry_run {
+        report.print();
+        return Ok(());
+    }
     // Re-encrypt the poll configuration.
     let serialized_pollconf = serde_yaml::to_string(&pollconf)?;
     let secure_serialized_pollconf = AEADString::from_values(
@@ -164,7 +195,7 @@ pub fn record_votes_run (pollconf_filename: &str, votes_file: &str) -> Result<(S
         "Votes already committed. To re-commit.");
 
     // Derive the poll secrets.
-    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key);
+    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key, pollconf.num_questions(), pollconf.num_planes);
 
     // Re-construct the audited ballots.
     let audited_ballots: Vec<BallotSerial> = {
@@ -174,66 +205,68 @@ pub fn record_votes_run (pollconf_filename: &str, votes_file: &str) -> Result<(S
     };
 
     // Read the Votes file.
-    let votes: Vec<VoteCode> = {
-        let votes_path = Path::new(votes_file);
-        let mut csvreader = csv::Reader::from_path(votes_path)?;
-        let records = csvreader.deserialize::<VoteRecordFileRow>();
-        records.map(|row| { row.unwrap().to_votecode() }).collect()
-    };
+    let votes: Vec<VoteCode> = read_votes_file(votes_file, &pollconf.votecode_format)?;
     pollconf.votes = Some(votes.clone());
-    let marked_rows: Vec<usize> = {
-        let votecodes: Vec<VoteCode> = generate_votecodes(
-            poll_secrets.votecode_root,
-            2 * pollconf.num_ballots);
-        votecodes.iter().enumerate()
-            .filter_map(|(n, vc)| {
-                debug!("{:?}", vc);
-                if votes.contains(vc) { Some(n) }
-                else { None }
-            }).collect()
-    };
 
-    // Post the Column Planes.
-    let column_planes: Vec<Plane> = generate_column_planes(
-        &poll_secrets,
-        NUMBER_OF_PLANES,
-        2 * pollconf.num_ballots,
-        pollconf.num_decoys)?;
-    // Filter planes.
-    column_planes.iter().enumerate()
-        .for_each(|(n, plane)| {
-            let posted_planes_path = {
-                let mut pathbuf = PathBuf::new();
-                pathbuf.push(&datadir_path);
-                pathbuf.push(format!("vote_plane_{:02}", n+1));
-                pathbuf.set_extension("csv");
-                pathbuf.into_boxed_path()
-            };
-            let posted_keys_path = {
-                let mut pathbuf = PathBuf::new();
-                pathbuf.push(&datadir_path);
-                pathbuf.push(format!("vote_plane_{:02}_keys", n+1));
-                pathbuf.set_extension("csv");
-                pathbuf.into_boxed_path()
-            };
-            let psecrets = poll_secrets.plane_secrets[n].resolve(plane.len());
-            let filter = PlaneFilter::from(&psecrets.col1_keys, &psecrets.col3_keys)
-                .decrypt_serials(&audited_ballots);
-
-            let permuted_plane = plane.mark_rows(&marked_rows).decrypt(&filter).permute(&psecrets.permutation);
-            let mut csvwriter = csv::Writer::from_path(posted_planes_path).unwrap();
-            permuted_plane.rows.iter()
-                .for_each(|rec| {
-                    csvwriter.serialize(rec.serializable(pollconf.num_ballots)).unwrap();
-                });
+    // Post the Column Planes of each question.
+    for q in 0..pollconf.num_questions() {
+        let marked_rows: Vec<usize> = {
+            let votecodes: Vec<VoteCode> = generate_votecodes(
+                poll_secrets.question_secrets[q].votecode_root,
+                pollconf.num_rows(q),
+                &pollconf.votecode_format);
+            votecodes.iter().enumerate()
+                .filter_map(|(n, vc)| {
+                    debug!("{:?}", vc);
+                    if votes.contains(vc) { Some(n) }
+                    else { None }
+                }).collect()
+        };
+        let column_planes: Vec<Plane> = generate_column_planes(
+            &poll_secrets,
+            q,
+            pollconf.num_planes,
+            pollconf.num_ballots,
+            pollconf.num_options(q),
+            pollconf.num_decoys,
+            &pollconf.votecode_format)?;
+        // Filter planes.
+        column_planes.iter().enumerate()
+            .map(|(n, plane)| -> Result<()> {
+                let posted_planes_path = {
+                    let mut pathbuf = PathBuf::new();
+                    pathbuf.push(&datadir_path);
+                    pathbuf.push(plane_file_name("vote", q, n));
+                    pathbuf.set_extension("csv");
+                    pathbuf.into_boxed_path()
+                };
+                let posted_keys_path = {
+                    let mut pathbuf = PathBuf::new();
+                    pathbuf.push(&datadir_path);
+                    pathbuf.push(format!("{}_keys", plane_file_name("vote", q, n)));
+                    pathbuf.set_extension("csv");
+                    pathbuf.into_boxed_path()
+                };
+                let psecrets = poll_secrets.question_secrets[q].plane_secrets[n].resolve(plane.len());
+                let filter = PlaneFilter::from(&psecrets.col1_keys, &psecrets.col3_keys)
+                    .decrypt_serials(&audited_ballots, pollconf.num_options(q));
+
+                let permuted_plane = plane.mark_rows(&marked_rows).decrypt(&filter)?.permute(&psecrets.permutation);
+                let mut csvwriter = csv::Writer::from_path(posted_planes_path).unwrap();
+                permuted_plane.rows.iter()
+                    .for_each(|rec| {
+                        csvwriter.serialize(rec.serializable(pollconf.num_ballots)).unwrap();
+                    });
             
-            let permuted_filter = filter.permute(&psecrets.permutation);
-            let mut csvwriter = csv::Writer::from_path(posted_keys_path).unwrap();
-            permuted_filter.serializable().iter()
-                .for_each(|rec| {
-                    csvwriter.serialize(rec).unwrap();
-                });
-        });
+                let permuted_filter = filter.permute(&psecrets.permutation);
+                let mut csvwriter = csv::Writer::from_path(posted_keys_path).unwrap();
+                permuted_filter.serializable().iter()
+                    .for_each(|rec| {
+                        csvwriter.serialize(rec).unwrap();
+                    });
+                Ok(())
+            }).collect::<Result<()>>()?;
+    }
 
     // Update the poll state.
     pollconf.poll_state.votes_committed = true;
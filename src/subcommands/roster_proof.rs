@@ -0,0 +1,56 @@
+//! # Command: Roster Proof
+//!
+//! `generate_roster_proof` writes the inclusion proof of one roster
+//! record, to be handed to that voter. `verify_roster_proof_file` checks
+//! a proof against the published roster root and needs no poll secrets.
+
+use super::*;
+use crate::blockchain::roster_proof::*;
+
+
+pub fn generate_roster_proof(pollconf_filename: &str, position: usize, proof_filename: &str) -> Result<()> {
+    // Read poll configuration file.
+    let secured_poll_configuration = read_poll_configuration_file(pollconf_filename)?;
+
+    // Reconstruct the Poll Master Key from the trustee passwords.
+    let (poll_master_key, aead_pmk) = read_poll_master_key(&secured_poll_configuration);
+
+    // Decrypt poll configuration state.
+    let pollconf_aead_values = secured_poll_configuration.encrypted_poll_configuration.values()?;
+    let serialized_pollconf = aead_decrypt(&aead_pmk, &pollconf_aead_values)?;
+    let pollconf: PollConfiguration = serde_yaml::from_slice(&serialized_pollconf).unwrap();
+
+    assert!(pollconf.poll_state.roster_committed,
+        "Voter roster must be bound before generating roster proofs.");
+
+    // Derive the poll secrets.
+    let poll_secrets: PollSecrets = PollSecrets::derive(&poll_master_key, pollconf.num_questions(), pollconf.num_planes);
+
+    if position >= pollconf.voter_roster_size {
+        return Err(From::from(format!("Roster position {} out of range; the roster holds {} records.", position, pollconf.voter_roster_size)));
+    }
+
+    // Read only the record of the proof, and derive only its salt.
+    let voter_info = bound_roster_selection(&pollconf, &aead_pmk, &vec![position])?.remove(0);
+    let salt = if pollconf.voter_privacy { roster_salts(poll_secrets.roster_root).nth(position) } else { None };
+
+    // Generate the proof.
+    let tree = blockchain::roster_tree(&pollconf, &poll_secrets, &aead_pmk)?;
+    let root = hex::encode(tree.root());
+    let proof = record_proof(tree, position, RestrictedVoterInfo::from(voter_info), salt)?;
+    serde_yaml::to_writer(File::create(Path::new(proof_filename))?, &proof)?;
+    println!("Roster root: {}", root);
+
+    Ok(())
+}
+
+
+pub fn verify_roster_proof_file(proof_filename: &str, root: &str) -> Result<()> {
+    let proof: RosterProof = serde_yaml::from_reader(File::open(Path::new(proof_filename))?)?;
+
+    if !verify_roster_proof(&proof, root) {
+        return Err(From::from(format!("Record {} is not included under roster root {}.", proof.position, root)));
+    }
+    println!("Record {} is included under roster root {}.", proof.position, root);
+    Ok(())
+}
@@ -8,6 +8,9 @@ use super::*;
 pub fn start(
     pollconf_filename: &str,
     roster_filename: &str,
+    roster_profile: Option<&str>,
+    check_roster: bool,
+    roster_report: &str,
     disable_voter_privacy: bool,
     drawn_summands_seed: &str,
     address_label: &str,
@@ -20,7 +23,7 @@ pub fn start(
     let secure_pollconf_filename = pollconf_filename.to_owned() + ".secure";
 
     // Bind roster to Poll
-    let secure_poll_configuration = bind_roster_run(&secure_pollconf_filename, secure_poll_configuration, aead_pmk, roster_filename, disable_voter_privacy)?;
+    let secure_poll_configuration = bind_roster_run(&secure_pollconf_filename, secure_poll_configuration, aead_pmk, roster_filename, roster_profile, check_roster, roster_report, disable_voter_privacy)?;
 
     // Step 1
     let secure_poll_configuration = generate_poll_commitments_run (&secure_pollconf_filename, secure_poll_configuration, poll_master_key.clone(), aead_pmk)?;
@@ -29,7 +32,7 @@ pub fn start(
     let secure_poll_configuration = generate_drawn_summands_run (&secure_pollconf_filename, secure_poll_configuration, aead_pmk, drawn_summands_seed)?;
 
     // Step 3
-    generate_print_files_run (secure_poll_configuration.clone(), poll_master_key.clone(), aead_pmk, address_label, ballot_information)?;
+    generate_print_files_run (secure_poll_configuration.clone(), poll_master_key.clone(), aead_pmk, address_label, ballot_information, &BallotPdfOutput::default(), &MailingOutput::default())?;
     
     // Step 4
     record_audited_ballots_run (&secure_pollconf_filename, secure_poll_configuration, poll_master_key.clone(), aead_pmk, audited_ballots, xxn_config)
@@ -0,0 +1,117 @@
+//! # Command: Seed Derivation
+//!
+//! `seed_derive` derives the seed of step 2 or step 7 from public input
+//! files and writes its transcript. `seed_verify` re-derives the seed of
+//! a transcript. Steps 2 and 7 take the transcript in place of the seed
+//! and keep a copy in the poll data directory.
+//!
+//! `seed_keygen` draws the key pair a participant signs commitments
+//! with. `seed_commit`, `seed_reveal` and `seed_combine` run a
+//! commit-reveal among trustees and observers, and `seed_combine` checks
+//! the commitments against the participant keys and writes the transcript
+//! of the combined seed.
+
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use super::*;
+
+
+fn ensure_seed_purpose(purpose: &str) -> Result<()> {
+    if purpose != DRAWN_SUMMANDS_PURPOSE && purpose != AUDITED_COLUMNS_PURPOSE {
+        return Err(From::from(format!("Seed purpose must be {} or {}.", DRAWN_SUMMANDS_PURPOSE, AUDITED_COLUMNS_PURPOSE)));
+    }
+    Ok(())
+}
+
+
+pub fn seed_derive(purpose: &str, input_arguments: Vec<&str>, transcript_filename: &str) -> Result<()> {
+    ensure_seed_purpose(purpose)?;
+    let inputs = input_arguments.iter()
+        .map(|argument| SeedInput::from_argument(argument))
+        .collect::<Result<Vec<SeedInput>>>()?;
+    let transcript = SeedTranscript::derive(purpose, inputs);
+    transcript.to_file(&Path::new(transcript_filename))?;
+    println!("Seed: {}", transcript.seed);
+    Ok(())
+}
+
+
+pub fn seed_verify(transcript_filename: &str) -> Result<()> {
+    let transcript = SeedTranscript::from_file(&Path::new(transcript_filename))?;
+    transcript.verify()?;
+    println!("Seed for {} re-derived from {} inputs: {}",
+        transcript.purpose, transcript.inputs.len(), transcript.seed);
+    Ok(())
+}
+
+
+pub fn seed_keygen(participant: &str, key_filename: &str) -> Result<()> {
+    let key = ParticipantKey::new(participant)?;
+
+    // Never overwrite a key whose public key may already be published.
+    let key_file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(Path::new(key_filename))?;
+    serde_yaml::to_writer(key_file, &key)?;
+    println!("Participant key of {}: {}={}", participant, participant, key.public_key.0);
+    Ok(())
+}
+
+
+pub fn seed_commit(key_filename: &str, purpose: &str, commitment_filename: &str, secret_filename: &str) -> Result<()> {
+    ensure_seed_purpose(purpose)?;
+    let key: ParticipantKey = serde_yaml::from_reader(File::open(Path::new(key_filename))?)?;
+    let participant = key.participant.as_str();
+    let (secret, commitment) = SeedCommitmentSecret::new(&key, purpose)?;
+
+    // Never overwrite a secret whose commitment may already be published.
+    let secret_file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(Path::new(secret_filename))?;
+    serde_yaml::to_writer(secret_file, &secret)?;
+    serde_yaml::to_writer(File::create(Path::new(commitment_filename))?, &commitment)?;
+    println!("Commitment of {}: {}", participant, commitment.commitment);
+    Ok(())
+}
+
+
+pub fn seed_reveal(secret_filename: &str, reveal_filename: &str) -> Result<()> {
+    let secret: SeedCommitmentSecret = serde_yaml::from_reader(File::open(Path::new(secret_filename))?)?;
+    serde_yaml::to_writer(File::create(Path::new(reveal_filename))?, &secret.reveal())?;
+    Ok(())
+}
+
+
+pub fn seed_combine(purpose: &str, participant_key_arguments: Vec<&str>, commitment_filenames: Vec<&str>, reveal_filenames: Vec<&str>, transcript_filename: &str) -> Result<()> {
+    ensure_seed_purpose(purpose)?;
+    // Each participant key is given as NAME=PUBLIC_KEY, as seed-keygen prints it.
+    let mut participant_keys: BTreeMap<String, Base64String> = BTreeMap::new();
+    for argument in participant_key_arguments.iter() {
+        let mut parts = argument.splitn(2, '=');
+        let (participant, public_key) = match (parts.next(), parts.next()) {
+            (Some(participant), Some(public_key)) if !participant.is_empty() && !public_key.is_empty() => (participant, public_key),
+            _ => return Err(From::from(format!("Participant key \"{}\" is not of the form NAME=PUBLIC_KEY.", argument)))
+        };
+        if participant_keys.insert(participant.to_owned(), Base64String(public_key.to_owned())).is_some() {
+            return Err(From::from(format!("{} is listed more than once.", participant)));
+        }
+    }
+    let commitments = commitment_filenames.iter()
+        .map(|filename| -> Result<SeedCommitment> {
+            let commitment: SeedCommitment = serde_yaml::from_reader(File::open(Path::new(filename))?)?;
+            Ok(commitment)
+        })
+        .collect::<Result<Vec<SeedCommitment>>>()?;
+    let reveals = reveal_filenames.iter()
+        .map(|filename| -> Result<SeedReveal> {
+            let reveal: SeedReveal = serde_yaml::from_reader(File::open(Path::new(filename))?)?;
+            Ok(reveal)
+        })
+        .collect::<Result<Vec<SeedReveal>>>()?;
+    let transcript = combine_reveals(purpose, commitments, reveals, &participant_keys)?;
+    transcript.to_file(&Path::new(transcript_filename))?;
+    println!("Seed combined from {} contributions: {}", transcript.inputs.len(), transcript.seed);
+    Ok(())
+}
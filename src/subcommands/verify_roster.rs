@@ -0,0 +1,39 @@
+//! # Command: Verify Roster
+//!
+//! `verify_roster` checks the records revealed in step 8 against the
+//! salted roster commitment published in step 1. It needs no poll
+//! secrets, so anyone holding the two files can run it.
+
+use super::*;
+
+
+pub fn verify_roster(committed_filename: &str, revealed_filename: &str) -> Result<()> {
+    // Read the published commitments.
+    let committed: Vec<RestrictedVoterRosterFileRow> = {
+        let mut csvreader = csv::Reader::from_path(Path::new(committed_filename))?;
+        csvreader.deserialize().collect::<std::result::Result<_, _>>()?
+    };
+
+    // Read the revealed records.
+    let revealed: Vec<RevealedRosterFileRow> = {
+        let mut csvreader = csv::Reader::from_path(Path::new(revealed_filename))?;
+        csvreader.deserialize().collect::<std::result::Result<_, _>>()?
+    };
+
+    // Check each revealed record.
+    let failures: Vec<usize> = revealed.iter()
+        .filter(|record| {
+            !committed.iter().find(|commitment| commitment.position == record.position)
+                .map_or(false, |commitment| verify_revealed_record(record, commitment))
+        })
+        .map(|record| record.position)
+        .collect();
+    failures.iter()
+        .for_each(|position| println!("Record {} does not match the roster commitment.", position));
+    println!("Revealed records verified: {} of {}", revealed.len() - failures.len(), revealed.len());
+
+    if !failures.is_empty() {
+        return Err(From::from("Revealed roster does not match the roster commitment."));
+    }
+    Ok(())
+}
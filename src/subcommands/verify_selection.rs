@@ -0,0 +1,163 @@
+//! # Command: Verify Selection
+//!
+//! `verify_selection` re-checks the selection of voters from published
+//! files only: the sampling strata of step 1, the drawn summands of
+//! step 2 and the committed summands revealed in step 8. It checks the
+//! ballot allocation and the range of every summand, reports the voters
+//! selected in each stratum and the collisions resolved by probing and,
+//! given the revealed roster, checks that exactly the selected voters
+//! were revealed.
+//!
+//! With stratification the strata file commits to the roster position of
+//! every voter of each stratum, salted with the salt of the record. The
+//! salts revealed in step 8 open the commitments of the selected voters,
+//! which must be the voters selected in each stratum, in roster order.
+
+use std::collections::{BTreeMap, BTreeSet};
+use super::*;
+use crate::voter_selection::*;
+
+
+pub fn verify_selection(strata_filename: &str, committed_filename: &str, drawn_filename: &str, revealed_filename: Option<&str>) -> Result<()> {
+    // Read the published files.
+    let strata_file: StrataFile = serde_yaml::from_reader(File::open(Path::new(strata_filename))?)?;
+    let committed_summands: CommittedSummands = {
+        let mut csvreader = csv::Reader::from_path(Path::new(committed_filename))?;
+        let records: SummandRecords = csvreader.deserialize().collect::<std::result::Result<_, _>>()?;
+        CommittedSummands::from(Summands { records: records })
+    };
+    let drawn_summands: DrawnSummands = serde_yaml::from_reader(File::open(Path::new(drawn_filename))?)?;
+    let strata = strata_file.strata;
+    let mut failures: Vec<String> = Vec::new();
+
+    // Check the allocation of the ballots.
+    let num_ballots: usize = strata.iter().map(|stratum| stratum.ballots).sum();
+    if num_ballots != committed_summands.len() || num_ballots != drawn_summands.len() {
+        return Err(From::from(format!("Strata are allocated {} ballots, but {} committed and {} drawn summands were published.",
+            num_ballots, committed_summands.len(), drawn_summands.len())));
+    }
+    if strata.iter().any(|stratum| stratum.voters == 0) {
+        return Err(From::from("A stratum has no voters."));
+    }
+    if strata_file.stratification.is_some() {
+        if strata_file.members.len() != strata.len()
+            || strata.iter().zip(strata_file.members.iter()).any(|(stratum, members)| stratum.voters != members.len()) {
+            return Err(From::from("Strata file does not commit to the voters of each stratum."));
+        }
+        // A voter in two strata has the same commitment in both.
+        let commitments: BTreeSet<&String> = strata_file.members.iter().flatten().collect();
+        if commitments.len() != strata_file.members.iter().map(|members| members.len()).sum::<usize>() {
+            failures.push("Strata overlap.".to_owned());
+        }
+    } else if strata.len() != 1 {
+        return Err(From::from("Strata file lists strata without a stratification."));
+    }
+    if let Some(stratification) = &strata_file.stratification {
+        let expected: Vec<usize> = match stratification.allocation {
+            StratumAllocation::Proportional => {
+                let sizes: Vec<usize> = strata.iter().map(|stratum| stratum.voters).collect();
+                allocate_proportionally(&sizes, num_ballots)
+            },
+            StratumAllocation::Explicit => {
+                strata.iter()
+                    .map(|stratum| *stratification.ballots.get(&stratum.key).unwrap_or(&0))
+                    .collect()
+            }
+        };
+        strata.iter().zip(expected.iter())
+            .filter(|(stratum, ballots)| stratum.ballots != **ballots)
+            .for_each(|(stratum, ballots)| {
+                failures.push(format!("Stratum \"{}\" is allocated {} ballots instead of {}.",
+                    stratum.key, stratum.ballots, ballots));
+            });
+    }
+
+    // Check that every summand is below the size of its stratum.
+    let moduli: Vec<usize> = strata.iter()
+        .flat_map(|stratum| std::iter::repeat(stratum.voters).take(stratum.ballots))
+        .collect();
+    Summands::from(committed_summands.clone()).records.iter()
+        .zip(Summands::from(drawn_summands.clone()).records.iter())
+        .zip(moduli.iter())
+        .filter(|((crec, drec), modulus)| crec.summand >= **modulus || drec.summand >= **modulus)
+        .for_each(|((crec, _), _)| {
+            failures.push(format!("Summand {} is out of range for its stratum.", crec.position));
+        });
+    if !failures.is_empty() {
+        failures.iter().for_each(|failure| println!("{}", failure));
+        return Err(From::from("Voter selection failed verification."));
+    }
+
+    // Re-compute the selection of each stratum.
+    let (members, collisions) = select_members_counting_collisions(committed_summands, drawn_summands, &strata)?;
+    // Each selected voter as (stratum, index among the voters of the stratum).
+    let mut selected: BTreeSet<(usize, usize)> = BTreeSet::new();
+    let mut offset = 0;
+    strata.iter().enumerate()
+        .for_each(|(n, stratum)| {
+            let voters: BTreeSet<&usize> = members[offset..offset + stratum.ballots].iter().collect();
+            println!("Stratum \"{}\": {} voters, {} ballots, {} voters selected",
+                stratum.key, stratum.voters, stratum.ballots, voters.len());
+            voters.into_iter().for_each(|&member| { selected.insert((n, member)); });
+            offset += stratum.ballots;
+        });
+    println!("Selection collisions resolved by probing: {}", collisions);
+
+    // Check the revealed voters against the selection.
+    if let Some(revealed_filename) = revealed_filename {
+        let revealed_rows: Vec<RevealedRosterFileRow> = {
+            let mut csvreader = csv::Reader::from_path(Path::new(revealed_filename))?;
+            csvreader.deserialize().collect::<std::result::Result<_, _>>()?
+        };
+        // Without stratification the members of the whole roster are its positions.
+        let commitment_members: BTreeMap<&String, (usize, usize)> = strata_file.members.iter().enumerate()
+            .flat_map(|(n, members)| members.iter().enumerate().map(move |(member, commitment)| (commitment, (n, member))))
+            .collect();
+        let stratified = strata_file.stratification.is_some();
+        let mut revealed: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+        let mut unopened: Vec<usize> = Vec::new();
+        revealed_rows.iter()
+            .for_each(|row| {
+                let member = match stratified {
+                    true => RosterSalt::from_hex(&row.salt)
+                        .and_then(|salt| commitment_members.get(&member_commitment(row.position, &salt)).cloned()),
+                    false => Some((0, row.position))
+                };
+                match member {
+                    Some(member) => { revealed.insert(member, row.position); },
+                    None => unopened.push(row.position)
+                }
+            });
+        if !unopened.is_empty() {
+            println!("Revealed but in no stratum: {:?}", unopened);
+            return Err(From::from("Revealed roster does not open the strata commitments."));
+        }
+        let revealed_members: BTreeSet<(usize, usize)> = revealed.keys().cloned().collect();
+        if revealed_members != selected {
+            let positions = |members: Vec<&(usize, usize)>| -> Vec<String> {
+                members.iter()
+                    .map(|member| match revealed.get(member) {
+                        Some(position) => position.to_string(),
+                        None => format!("voter {} of stratum \"{}\"", member.1, strata[member.0].key)
+                    })
+                    .collect()
+            };
+            println!("Selected but not revealed: {:?}", positions(selected.difference(&revealed_members).collect()));
+            println!("Revealed but not selected: {:?}", positions(revealed_members.difference(&selected).collect()));
+            return Err(From::from("Revealed roster does not match the voter selection."));
+        }
+        // The voters of a stratum are committed in roster order.
+        let mut previous: Option<(usize, usize)> = None;
+        for (&(n, _), &position) in revealed.iter() {
+            if let Some((previous_stratum, previous_position)) = previous {
+                if previous_stratum == n && previous_position >= position {
+                    return Err(From::from(format!("Voters of stratum \"{}\" are not committed in roster order.", strata[n].key)));
+                }
+            }
+            previous = Some((n, position));
+        }
+        println!("Revealed roster matches the voter selection.");
+    }
+
+    Ok(())
+}
@@ -29,7 +29,14 @@ impl CommittedSummands {
     }
 
     pub fn from_csprng(seed: CSPRNGSeed, count: usize, modulus: usize) -> Self {
-        committed_summands_from_csprng_fdr(seed, count, modulus)
+        committed_summands_from_csprng_fdr(seed, &vec![(count, modulus)])
+    }
+
+    /// Summands of several strata, each given as its number of summands
+    /// and their modulus. A single stratum gives the same summands as
+    /// `from_csprng`.
+    pub fn from_csprng_strata(seed: CSPRNGSeed, strata: &Vec<(usize, usize)>) -> Self {
+        committed_summands_from_csprng_fdr(seed, strata)
     }
 
     pub fn len(self: &Self) -> usize { self.records.len() }
@@ -49,7 +56,7 @@ impl From<CommittedSummands> for Summands {
 
 
 /// Generate a list of summands using a CSPRNG feeding the Fast Dice Roller.
-fn committed_summands_from_csprng_fdr(seed: CSPRNGSeed, count: usize, modulus: usize) -> CommittedSummands {
+fn committed_summands_from_csprng_fdr(seed: CSPRNGSeed, strata: &Vec<(usize, usize)>) -> CommittedSummands {
     /*
     fn try_generate(seed: CSPRNGSeed, count: usize, modulus: usize, num_bytes: usize) -> Option<Vec<usize>> {
         let mut prng = CSPRNG::from_csprng_seed(seed);
@@ -85,8 +92,10 @@ fn committed_summands_from_csprng_fdr(seed: CSPRNGSeed, count: usize, modulus: u
     */
     let mut prng = CSPRNG::from_csprng_seed(seed);
     CommittedSummands {
-        records: (0..count).into_iter()
-            .map(|n| {
+        records: strata.iter()
+            .flat_map(|&(count, modulus)| std::iter::repeat(modulus).take(count))
+            .enumerate()
+            .map(|(n, modulus)| {
                 SummandRecord { position: n, summand: prng.gen_range(0, modulus) }
             }).collect()
     }
@@ -13,7 +13,14 @@ pub struct DrawnSummands {
 
 impl DrawnSummands {
     pub fn from_csprng(seed: CSPRNGSeed, count: usize, modulus: usize) -> Self {
-        drawn_summands_from_csprng_fdr(seed, count, modulus)
+        drawn_summands_from_csprng_fdr(seed, &vec![(count, modulus)])
+    }
+
+    /// Summands of several strata, each given as its number of summands
+    /// and their modulus. A single stratum gives the same summands as
+    /// `from_csprng`.
+    pub fn from_csprng_strata(seed: CSPRNGSeed, strata: &Vec<(usize, usize)>) -> Self {
+        drawn_summands_from_csprng_fdr(seed, strata)
     }
 
     pub fn len(self: &Self) -> usize { self.records.len() }
@@ -32,11 +39,13 @@ impl From<DrawnSummands> for Summands {
 }
 
 /// Generate a list of summands using a CSPRNG feeding the Fast Dice Roller.
-fn drawn_summands_from_csprng_fdr(seed: CSPRNGSeed, count: usize, modulus: usize) -> DrawnSummands {
+fn drawn_summands_from_csprng_fdr(seed: CSPRNGSeed, strata: &Vec<(usize, usize)>) -> DrawnSummands {
     let mut prng = CSPRNG::from_csprng_seed(seed);
     DrawnSummands {
-        records: (0..count).into_iter()
-            .map(|n| {
+        records: strata.iter()
+            .flat_map(|&(count, modulus)| std::iter::repeat(modulus).take(count))
+            .enumerate()
+            .map(|(n, modulus)| {
                 SummandRecord { position: n, summand: prng.gen_range(0, modulus) }
             }).collect()
     }
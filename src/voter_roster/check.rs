@@ -0,0 +1,250 @@
+//! # Roster Check
+//!
+//! `check` normalizes a voter roster and reports what would skew the
+//! sampling or waste a mailing: missing fields, malformed ZIP codes and
+//! duplicate voters. A voter listed twice is twice as likely to be
+//! selected, so exact duplicates fail the check. Fuzzy duplicates and
+//! households (several voters at one address) are reported for review.
+//!
+//! Fuzzy duplicates are looked for by sorted neighbourhood: the records
+//! are sorted on a blocking key and each is compared with the next few
+//! records only, so the check stays linear in the size of the roster. One
+//! key leads with the name and one with the street, so a typo at the start
+//! of one is still caught through the other.
+//!
+//! Normalization upper-cases every field, collapses whitespace and writes
+//! US ZIP codes as `NNNNN` or `NNNNN-NNNN`.
+
+use std::collections::{BTreeSet, HashMap};
+use std::hash::Hash;
+use serde::Serialize;
+use super::*;
+
+const US_COUNTRY_NAMES: [&str; 4] = ["", "US", "USA", "UNITED STATES"];
+const FUZZY_DISTANCE: usize = 2;
+/// Number of following records, in blocking key order, each record is
+/// compared with for fuzzy duplicates.
+const FUZZY_WINDOW: usize = 8;
+
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RosterFieldIssue {
+    pub position: usize,
+    pub field: String
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RosterCheckReport {
+    pub num_records: usize,
+    pub num_normalized: usize,
+    pub num_households: usize,
+    pub missing_fields: Vec<RosterFieldIssue>,
+    pub malformed_zip_codes: Vec<usize>,
+    pub exact_duplicates: Vec<Vec<usize>>,
+    pub fuzzy_duplicates: Vec<Vec<usize>>,
+    pub households: Vec<Vec<usize>>,
+    pub roster_hash: String
+}
+
+impl RosterCheckReport {
+    /// Whether the roster must be fixed before it is bound.
+    pub fn has_errors(self: &Self) -> bool {
+        !self.missing_fields.is_empty()
+            || !self.malformed_zip_codes.is_empty()
+            || !self.exact_duplicates.is_empty()
+    }
+
+    pub fn to_file(self: &Self, path: &dyn AsRef<Path>) -> Result<()> {
+        serde_yaml::to_writer(std::fs::File::create(path)?, self)?;
+        Ok(())
+    }
+}
+
+
+/// Normalize a roster and check it. The report refers to voters by their
+/// roster position.
+pub fn check_roster(roster: &VoterRoster) -> (VoterRoster, RosterCheckReport) {
+    let normalized = VoterRoster {
+        records: roster.records.iter()
+            .map(|record| VoterRosterRecord {
+                position: record.position,
+                voter_info: normalize_voter_info(&record.voter_info)
+            }).collect()
+    };
+    let num_normalized = roster.records.iter().zip(normalized.records.iter())
+        .filter(|(original, normalized)| original.voter_info.fields() != normalized.voter_info.fields())
+        .count();
+
+    // Missing fields and malformed ZIP codes.
+    let mut missing_fields = Vec::new();
+    let mut malformed_zip_codes = Vec::new();
+    normalized.records.iter()
+        .for_each(|record| {
+            let info = &record.voter_info;
+            [("last_name", &info.last_name), ("first_name", &info.first_name),
+             ("street_address", &info.street_address), ("city", &info.city),
+             ("state", &info.state), ("zip_code", &info.zip_code)].iter()
+                .filter(|(_, value)| value.is_empty())
+                .for_each(|(field, _)| {
+                    missing_fields.push(RosterFieldIssue {
+                        position: record.position,
+                        field: field.to_string()
+                    });
+                });
+            if !info.zip_code.is_empty() && is_us(info) && !is_us_zip_code(&info.zip_code) {
+                malformed_zip_codes.push(record.position);
+            }
+        });
+
+    // Exact duplicates and households.
+    let exact_duplicates = groups_by(&normalized, |info| info.fields());
+    let households = groups_by(&normalized, |info| address_key(info));
+
+    // Fuzzy duplicates: similar name and street within a ZIP code.
+    let mut fuzzy_pairs: BTreeSet<(usize, usize)> = BTreeSet::new();
+    let blocking_keys: [fn(&VoterInfo) -> [&str; 4]; 2] = [
+        |info| [zip5(info), &info.last_name, &info.first_name, &info.street_address],
+        |info| [zip5(info), &info.street_address, &info.last_name, &info.first_name]
+    ];
+    blocking_keys.iter()
+        .for_each(|blocking_key| {
+            let mut sorted: Vec<&VoterRosterRecord> = normalized.records.iter().collect();
+            sorted.sort_by(|a, b| blocking_key(&a.voter_info).cmp(&blocking_key(&b.voter_info)));
+            sorted.iter().enumerate()
+                .for_each(|(n, a)| {
+                    sorted.iter().skip(n + 1).take(FUZZY_WINDOW)
+                        .filter(|b| is_fuzzy_duplicate(&a.voter_info, &b.voter_info))
+                        .for_each(|b| { fuzzy_pairs.insert((a.position.min(b.position), a.position.max(b.position))); });
+                });
+        });
+    let fuzzy_duplicates: Vec<Vec<usize>> = fuzzy_pairs.into_iter()
+        .map(|(a, b)| vec![a, b])
+        .collect();
+
+    let report = RosterCheckReport {
+        num_records: normalized.len(),
+        num_normalized: num_normalized,
+        num_households: households.len(),
+        missing_fields: missing_fields,
+        malformed_zip_codes: malformed_zip_codes,
+        exact_duplicates: exact_duplicates,
+        fuzzy_duplicates: fuzzy_duplicates,
+        households: households,
+        roster_hash: normalized.hash()
+    };
+    (normalized, report)
+}
+
+pub fn normalize_voter_info(info: &VoterInfo) -> VoterInfo {
+    let mut normalized = VoterInfo {
+        last_name: normalize_field(&info.last_name),
+        first_name: normalize_field(&info.first_name),
+        street_address: normalize_field(&info.street_address),
+        address2: normalize_field(&info.address2),
+        apartment: normalize_field(&info.apartment),
+        city: normalize_field(&info.city),
+        state: normalize_field(&info.state),
+        zip_code: normalize_field(&info.zip_code),
+        country: normalize_field(&info.country),
+        language: info.language.trim().to_owned()
+    };
+    if is_us(&normalized) {
+        normalized.zip_code = normalize_us_zip_code(&normalized.zip_code);
+    }
+    normalized
+}
+
+fn normalize_field(value: &str) -> String {
+    value.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_uppercase()
+}
+
+fn is_us(info: &VoterInfo) -> bool {
+    US_COUNTRY_NAMES.contains(&info.country.as_str())
+}
+
+/// Write a US ZIP code as `NNNNN` or `NNNNN-NNNN`, restoring the leading
+/// zeros spreadsheets drop. Anything else is left for the report.
+fn normalize_us_zip_code(value: &str) -> String {
+    let digits: String = value.chars().filter(|c| *c != ' ' && *c != '-').collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) { return value.to_owned(); }
+    match digits.len() {
+        3 | 4 | 5 => format!("{:0>5}", digits),
+        9 => format!("{}-{}", &digits[..5], &digits[5..]),
+        _ => value.to_owned()
+    }
+}
+
+fn is_us_zip_code(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    match bytes.len() {
+        5 => bytes.iter().all(|b| b.is_ascii_digit()),
+        10 => bytes[5] == b'-'
+            && bytes[..5].iter().all(|b| b.is_ascii_digit())
+            && bytes[6..].iter().all(|b| b.is_ascii_digit()),
+        _ => false
+    }
+}
+
+fn address_key(info: &VoterInfo) -> [&str; 4] {
+    [&info.street_address, &info.apartment, &info.address2, &info.zip_code]
+}
+
+fn zip5(info: &VoterInfo) -> &str {
+    match info.zip_code.char_indices().nth(5) {
+        Some((end, _)) => &info.zip_code[..end],
+        None => &info.zip_code
+    }
+}
+
+/// Group the positions of the records sharing a key. Only groups of
+/// several records are returned, ordered by their first position.
+fn groups_by<'a, K: Hash + Eq, F: Fn(&'a VoterInfo) -> K>(roster: &'a VoterRoster, key: F) -> Vec<Vec<usize>> {
+    let mut groups: HashMap<K, Vec<usize>> = HashMap::new();
+    roster.records.iter()
+        .for_each(|record| {
+            groups.entry(key(&record.voter_info)).or_insert_with(Vec::new).push(record.position);
+        });
+    let mut groups: Vec<Vec<usize>> = groups.into_iter()
+        .map(|(_, positions)| positions)
+        .filter(|positions| positions.len() > 1)
+        .collect();
+    groups.sort();
+    groups
+}
+
+fn is_fuzzy_duplicate(a: &VoterInfo, b: &VoterInfo) -> bool {
+    let exact = a.last_name == b.last_name
+        && a.first_name == b.first_name
+        && address_key(a) == address_key(b);
+    // An initial matches any first name starting with it.
+    let first_names_match = edit_distance(&a.first_name, &b.first_name) <= FUZZY_DISTANCE
+        || (a.first_name.len() == 1 && b.first_name.starts_with(&a.first_name))
+        || (b.first_name.len() == 1 && a.first_name.starts_with(&b.first_name));
+    !exact
+        && first_names_match
+        && edit_distance(&a.last_name, &b.last_name) <= FUZZY_DISTANCE
+        && edit_distance(&a.street_address, &b.street_address) <= FUZZY_DISTANCE
+        && a.apartment == b.apartment
+        && zip5(a) == zip5(b)
+}
+
+/// Levenshtein distance between two strings.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    a.iter().enumerate()
+        .for_each(|(i, ca)| {
+            let mut current: Vec<usize> = vec![i + 1];
+            b.iter().enumerate()
+                .for_each(|(j, cb)| {
+                    let substitution = previous[j] + if ca == cb { 0 } else { 1 };
+                    current.push(*[substitution, previous[j + 1] + 1, current[j] + 1].iter().min().unwrap());
+                });
+            previous = current;
+        });
+    previous[b.len()]
+}
@@ -0,0 +1,111 @@
+//! # Private Roster Commitment
+//!
+//! With voter privacy, the published roster holds one salted commitment
+//! per record instead of names and addresses:
+//!
+//! `commitment = SHA-256(salt || restricted voter information)`
+//!
+//! Each record has its own 32-byte salt drawn from the poll secrets, so
+//! a commitment cannot be confirmed by guessing a voter's details. After
+//! the poll only the records and salts of the selected voters are
+//! revealed, which lets anyone check that they were on the committed
+//! roster while everyone else stays private.
+//!
+//! The roster is committed as a Merkle tree over one leaf per record (see
+//! `roster_leaf`), so a voter can check their own record against the root
+//! with a short inclusion proof.
+
+use serde::{Serialize, Deserialize};
+use sha2::{Sha256, Digest};
+use crate::cryptography::csprng::*;
+use super::*;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RosterSalt(pub [u8; 32]);
+
+impl RosterSalt {
+    pub fn from_hex(value: &str) -> Option<Self> {
+        match hex::decode(value) {
+            Ok(bytes) if bytes.len() == 32 => {
+                let mut salt = RosterSalt([0; 32]);
+                salt.0.copy_from_slice(&bytes);
+                Some(salt)
+            },
+            _ => None
+        }
+    }
+}
+
+/// Revealed record of a selected voter.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevealedRosterFileRow {
+    pub position: usize,
+    pub restricted: RestrictedVoterInfo,
+    pub salt: String
+}
+
+
+pub fn generate_roster_salts(seed: CSPRNGSeed, count: usize) -> Vec<RosterSalt> {
+    roster_salts(seed).take(count).collect()
+}
+
+/// Salts of the roster records in roster order, drawn as needed.
+pub fn roster_salts(seed: CSPRNGSeed) -> impl Iterator<Item = RosterSalt> {
+    let mut rng = CSPRNG::from_csprng_seed(seed);
+    std::iter::repeat_with(move || {
+        let mut salt = RosterSalt([0; 32]);
+        rng.fill_bytes(&mut salt.0);
+        salt
+    })
+}
+
+/// Salted commitment of restricted voter information, in hex.
+pub fn salted_commitment(restricted: &RestrictedVoterInfo, salt: &RosterSalt) -> String {
+    let mut hasher = Sha256::new();
+    hasher.input(&salt.0);
+    hasher.input(restricted.as_bytes());
+    hex::encode(hasher.result())
+}
+
+/// Salted commitment of the roster position of a stratum member, in hex.
+/// It takes the salt of the record, so revealing the record opens it.
+pub fn member_commitment(position: usize, salt: &RosterSalt) -> String {
+    salted_commitment(&format!("member:{}", position), salt)
+}
+
+/// Check a revealed record against the published commitment.
+pub fn verify_revealed_record(revealed: &RevealedRosterFileRow, committed: &RestrictedVoterRosterFileRow) -> bool {
+    let salt = match RosterSalt::from_hex(&revealed.salt) {
+        Some(salt) => salt,
+        None => return false
+    };
+    revealed.position == committed.position
+        && salted_commitment(&revealed.restricted, &salt) == committed.restricted
+}
+
+/// Canonical encoding of a roster record as a leaf of the roster tree:
+/// its position and its restricted voter information, or the salted
+/// commitment of it with voter privacy.
+pub fn roster_leaf(position: usize, restricted: &RestrictedVoterInfo, salt: Option<&RosterSalt>) -> String {
+    match salt {
+        Some(salt) => format!("{}:{}", position, salted_commitment(restricted, salt)),
+        None => format!("{}:{}", position, restricted)
+    }
+}
+
+impl VoterRoster {
+    /// Restricted roster holding the salted commitment of each record.
+    pub fn salted_commitments(self: &Self, salts: &Vec<RosterSalt>) -> RestrictedVoterRoster {
+        assert!(salts.len() == self.len(),
+            "One salt is needed per roster record.");
+        let restricted = self.restricted();
+        RestrictedVoterRoster {
+            records: restricted.records.iter().zip(salts.iter())
+                .map(|(record, salt)| RestrictedVoterRosterRecord {
+                    position: record.position,
+                    voter_info: salted_commitment(&record.voter_info, salt)
+                }).collect()
+        }
+    }
+}
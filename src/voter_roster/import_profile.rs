@@ -0,0 +1,206 @@
+//! # Roster Import Profiles
+//!
+//! Voter lists come from several vendors, each with its own headers and
+//! file format. An import profile (YAML) names the file format and the
+//! source column holding each `VoterInfo` field:
+//!
+//! ```yaml
+//! format: tsv          # csv (default), tsv or jsonl
+//! columns:
+//!   last_name: "Surname"
+//!   first_name: "Given Name"
+//!   street_address: "Address Line 1"
+//!   address2: "Address Line 2"
+//!   apartment: "Unit"
+//!   city: "Town"
+//!   state: "Region"
+//!   zip_code: "Postcode"
+//!   country: "Country"
+//!   language: "Language"
+//! ```
+//!
+//! Columns left out of the profile keep their `VoterInfo` name. The
+//! `address2`, `apartment`, `country` and `language` columns may be
+//! missing from the source, every other column is required.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::collections::HashMap;
+use serde::Deserialize;
+use super::*;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum RosterFormat {
+    #[serde(rename = "csv")]
+    Csv,
+    #[serde(rename = "tsv")]
+    Tsv,
+    #[serde(rename = "jsonl")]
+    JsonLines
+}
+
+impl Default for RosterFormat {
+    fn default() -> Self { RosterFormat::Csv }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RosterColumnMapping {
+    pub last_name: String,
+    pub first_name: String,
+    pub street_address: String,
+    pub address2: String,
+    pub apartment: String,
+    pub city: String,
+    pub state: String,
+    pub zip_code: String,
+    pub country: String,
+    pub language: String
+}
+
+impl Default for RosterColumnMapping {
+    fn default() -> Self {
+        RosterColumnMapping {
+            last_name: "last_name".to_owned(),
+            first_name: "first_name".to_owned(),
+            street_address: "street_address".to_owned(),
+            address2: "address2".to_owned(),
+            apartment: "apartment".to_owned(),
+            city: "city".to_owned(),
+            state: "state".to_owned(),
+            zip_code: "zip_code".to_owned(),
+            country: "country".to_owned(),
+            language: "language".to_owned()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RosterImportProfile {
+    pub format: RosterFormat,
+    pub columns: RosterColumnMapping
+}
+
+/// A roster row that could not be imported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RosterImportError {
+    pub line: u64,
+    pub message: String
+}
+
+impl fmt::Display for RosterImportError {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// A source row: its line number and its values by column name.
+type RosterSourceRow = (u64, HashMap<String, String>);
+
+
+impl RosterImportProfile {
+    pub fn from_file(path: &dyn AsRef<Path>) -> Result<Self> {
+        let profile: RosterImportProfile = serde_yaml::from_reader(File::open(path)?)?;
+        Ok(profile)
+    }
+
+    /// Read every row of a roster file. Rows that cannot be read or lack
+    /// a required column are returned as errors, in file order.
+    pub fn read(self: &Self, path: &dyn AsRef<Path>) -> Result<Vec<std::result::Result<VoterInfo, RosterImportError>>> {
+        let rows = match self.format {
+            RosterFormat::Csv => read_delimited(path, b',')?,
+            RosterFormat::Tsv => read_delimited(path, b'\t')?,
+            RosterFormat::JsonLines => read_json_lines(path)?
+        };
+        Ok(rows.into_iter()
+            .map(|row| row.and_then(|(line, values)| self.voter_info(line, &values)))
+            .collect())
+    }
+
+    fn voter_info(self: &Self, line: u64, values: &HashMap<String, String>) -> std::result::Result<VoterInfo, RosterImportError> {
+        let required = |column: &str| {
+            values.get(column)
+                .map(|value| value.trim().to_owned())
+                .ok_or(RosterImportError {
+                    line: line,
+                    message: format!("missing column \"{}\"", column)
+                })
+        };
+        let optional = |column: &str| {
+            values.get(column)
+                .map(|value| value.trim().to_owned())
+                .unwrap_or_default()
+        };
+        let columns = &self.columns;
+        Ok(VoterInfo {
+            last_name: required(&columns.last_name)?,
+            first_name: required(&columns.first_name)?,
+            street_address: required(&columns.street_address)?,
+            address2: optional(&columns.address2),
+            apartment: optional(&columns.apartment),
+            city: required(&columns.city)?,
+            state: required(&columns.state)?,
+            zip_code: required(&columns.zip_code)?,
+            country: optional(&columns.country),
+            language: optional(&columns.language)
+        })
+    }
+}
+
+fn read_delimited(path: &dyn AsRef<Path>, delimiter: u8) -> Result<Vec<std::result::Result<RosterSourceRow, RosterImportError>>> {
+    let mut csvreader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .from_path(path)?;
+    let headers: Vec<String> = csvreader.headers()?.iter()
+        .map(|header| header.trim().to_owned())
+        .collect();
+    Ok(csvreader.records()
+        .map(|result| {
+            match result {
+                Ok(record) => {
+                    let line = record.position().map(|position| position.line()).unwrap_or(0);
+                    Ok((line, headers.iter().cloned().zip(record.iter().map(|value| value.to_owned())).collect()))
+                },
+                Err(err) => {
+                    Err(RosterImportError {
+                        line: err.position().map(|position| position.line()).unwrap_or(0),
+                        message: err.to_string()
+                    })
+                }
+            }
+        }).collect())
+}
+
+fn read_json_lines(path: &dyn AsRef<Path>) -> Result<Vec<std::result::Result<RosterSourceRow, RosterImportError>>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut rows = Vec::new();
+    for (n, text) in reader.lines().enumerate() {
+        let text = text?;
+        let line = (n + 1) as u64;
+        if text.trim().is_empty() { continue; }
+        let row = serde_json::from_str::<HashMap<String, serde_json::Value>>(&text)
+            .map(|object| {
+                // Vendors write zip codes as numbers; only nested values are refused.
+                let values = object.into_iter()
+                    .filter_map(|(column, value)| {
+                        match value {
+                            serde_json::Value::String(value) => Some((column, value)),
+                            serde_json::Value::Number(value) => Some((column, value.to_string())),
+                            serde_json::Value::Bool(value) => Some((column, value.to_string())),
+                            serde_json::Value::Null => Some((column, "".to_owned())),
+                            _ => None
+                        }
+                    }).collect();
+                (line, values)
+            })
+            .map_err(|err| RosterImportError {
+                line: line,
+                message: err.to_string()
+            });
+        rows.push(row);
+    }
+    Ok(rows)
+}
@@ -12,5 +12,17 @@ pub use restricted::*;
 pub mod voter_roster_file;
 pub use voter_roster_file::*;
 
+pub mod import_profile;
+pub use import_profile::*;
+
+pub mod check;
+pub use check::*;
+
+pub mod commitment;
+pub use commitment::*;
+
 pub mod restricted_file;
 pub use restricted_file::*;
+
+pub mod sidecar;
+pub use sidecar::*;
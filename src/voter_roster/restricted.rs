@@ -8,7 +8,7 @@ pub struct RestrictedVoterRoster {
 }
 
 /// List of restricted voter roster records
-type RestrictedVoterRosterRecords = Vec<RestrictedVoterRosterRecord>;
+pub type RestrictedVoterRosterRecords = Vec<RestrictedVoterRosterRecord>;
 
 /// Restricted voter roster record
 pub struct RestrictedVoterRosterRecord {
@@ -55,11 +55,15 @@ impl From<VoterRosterRecord> for RestrictedVoterRosterRecord {
 impl From<VoterInfo> for RestrictedVoterInfo {
     fn from(info: VoterInfo) -> Self {
         let public = format!("{}, {}", info.last_name, info.first_name);
-        let private = format!("{}\n{} {}, {}",
-                info.street_address,
+        let mut private = info.street_address.clone();
+        if !info.apartment.is_empty() { private = format!("{} {}", private, info.apartment); }
+        if !info.address2.is_empty() { private = format!("{}\n{}", private, info.address2); }
+        private = format!("{}\n{} {}, {}",
+                private,
                 info.city,
                 info.state,
                 info.zip_code);
+        if !info.country.is_empty() { private = format!("{}\n{}", private, info.country); }
         format!("{}\n{}", public, private)
     }
 }
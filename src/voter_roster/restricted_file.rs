@@ -1,11 +1,10 @@
 //! Module for the Restricted Voter Roster File rows.
 
-use serde::Serialize;
+use serde::{Serialize, Deserialize};
 use super::RestrictedVoterInfo;
 
-#[derive(Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RestrictedVoterRosterFileRow {
     pub position: usize,
     pub restricted: RestrictedVoterInfo
 }
-
@@ -0,0 +1,147 @@
+//! # Roster Sidecar
+//!
+//! Large rosters are not embedded in the secured poll configuration.
+//! They are bound as a sidecar file next to the poll data, one encrypted
+//! record per line:
+//!
+//! `$chacha20_poly1305_aead$nonce$position$record$tag$`
+//!
+//! Each record is encrypted under the poll's AEAD key with its roster
+//! position as associated data, so records cannot be reordered. The poll
+//! configuration holds the path and the SHA-256 of the file, which is
+//! checked before any record is read. Records are read one at a time, so
+//! a step never needs the whole roster in memory.
+
+use std::fs::File;
+use std::io::{Read, Write, BufRead, BufReader, BufWriter, Lines};
+use serde::{Serialize, Deserialize};
+use sha2::{Sha256, Digest};
+use crate::cryptography::*;
+use crate::poll_configuration::PollConfiguration;
+use super::*;
+
+
+/// Reference to a roster sidecar from the poll configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RosterSidecarReference {
+    pub path: String,
+    pub hash: String
+}
+
+/// Streaming reader of the records of a roster sidecar.
+pub struct RosterSidecarReader {
+    lines: Lines<BufReader<File>>,
+    key: AEADKey,
+    position: usize
+}
+
+/// Records of a bound roster, in roster order.
+pub type RosterRecordStream = Box<dyn Iterator<Item = Result<VoterRosterRecord>>>;
+
+
+/// Write a roster sidecar and return its reference.
+pub fn write_roster_sidecar(roster: &VoterRoster, key: &AEADKey, path: &str) -> Result<RosterSidecarReference> {
+    {
+        let mut writer = BufWriter::new(File::create(Path::new(path))?);
+        for record in roster.records.iter() {
+            let line = AEADString::from_values(
+                aead_encrypt(key,
+                             record.position.to_string().into_bytes(),
+                             serde_json::to_vec(&record.voter_info)?)?);
+            writeln!(writer, "{}", line.0)?;
+        }
+        writer.flush()?;
+    }
+    Ok(RosterSidecarReference {
+        path: path.to_owned(),
+        hash: hash_file(path)?
+    })
+}
+
+/// Open a roster sidecar, checking it against its reference.
+pub fn open_roster_sidecar(reference: &RosterSidecarReference, key: &AEADKey) -> Result<RosterSidecarReader> {
+    let hash = hash_file(&reference.path)?;
+    if hash != reference.hash {
+        return Err(From::from(format!("Roster sidecar {} does not match the bound roster.", reference.path)));
+    }
+    Ok(RosterSidecarReader {
+        lines: BufReader::new(File::open(Path::new(&reference.path))?).lines(),
+        key: *key,
+        position: 0
+    })
+}
+
+/// SHA-256 of a file, in hex, read in blocks.
+pub fn hash_file(path: &str) -> Result<String> {
+    let mut file = File::open(Path::new(path))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 65536];
+    loop {
+        let count = file.read(&mut buffer)?;
+        if count == 0 { break; }
+        hasher.input(&buffer[..count]);
+    }
+    Ok(hex::encode(hasher.result()))
+}
+
+impl Iterator for RosterSidecarReader {
+    type Item = Result<VoterRosterRecord>;
+
+    fn next(self: &mut Self) -> Option<Self::Item> {
+        let line = self.lines.next()?;
+        let position = self.position;
+        self.position += 1;
+        Some(line.map_err(From::from).and_then(|line| {
+            let values = AEADString(line).values()?;
+            if values.aad != position.to_string().into_bytes() {
+                return Err(From::from(format!("Roster sidecar record out of place at position {}.", position)));
+            }
+            let voter_info: VoterInfo = serde_json::from_slice(&aead_decrypt(&self.key, &values)?)?;
+            Ok(VoterRosterRecord {
+                position: position,
+                voter_info: voter_info
+            })
+        }))
+    }
+}
+
+
+/// Stream the records of the roster bound to a poll, from its sidecar or
+/// from the configuration for rosters bound before sidecars.
+pub fn bound_roster_records(pollconf: &PollConfiguration, key: &AEADKey) -> Result<RosterRecordStream> {
+    match (&pollconf.voter_roster_sidecar, &pollconf.voter_roster) {
+        (Some(reference), _) => Ok(Box::new(open_roster_sidecar(reference, key)?)),
+        (None, Some(encoded_roster)) => {
+            let decoded_roster = base64::decode(&encoded_roster.0)?;
+            let serialized_roster = std::str::from_utf8(&decoded_roster)?;
+            let roster: VoterRoster = serde_yaml::from_str(serialized_roster)?;
+            Ok(Box::new(roster.records.into_iter().map(Ok)))
+        },
+        (None, None) => Err(From::from("No voter roster bound to the poll."))
+    }
+}
+
+/// Read the whole roster bound to a poll.
+pub fn bound_roster(pollconf: &PollConfiguration, key: &AEADKey) -> Result<VoterRoster> {
+    Ok(VoterRoster {
+        records: bound_roster_records(pollconf, key)?.collect::<Result<VoterRosterRecords>>()?
+    })
+}
+
+/// Read only the records at the given positions of the roster bound to a
+/// poll, returned in the order of the positions.
+pub fn bound_roster_selection(pollconf: &PollConfiguration, key: &AEADKey, positions: &Vec<usize>) -> Result<Vec<VoterInfo>> {
+    let wanted: std::collections::HashSet<usize> = positions.iter().cloned().collect();
+    let mut found: std::collections::HashMap<usize, VoterInfo> = std::collections::HashMap::new();
+    for record in bound_roster_records(pollconf, key)? {
+        let record = record?;
+        if wanted.contains(&record.position) {
+            found.insert(record.position, record.voter_info);
+        }
+    }
+    positions.iter()
+        .map(|position| {
+            found.get(position).cloned()
+                .ok_or_else(|| From::from(format!("No voter at roster position {}.", position)))
+        }).collect()
+}
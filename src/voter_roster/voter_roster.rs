@@ -1,9 +1,27 @@
 //! Implementation for the Voter Roster.
+//!
+//! The roster hash bound to the poll has a fixed, versioned encoding, so
+//! it does not depend on how a serializer happens to lay out the roster:
+//!
+//! ```text
+//! SHA-256( "seventh-estate-roster-v1" || 0x00 || u64(n)
+//!          || u64(position_1) || u64(len) || last_name || ... || u64(len) || language
+//!          || ...
+//!          || u64(position_n) || ... )
+//! ```
+//!
+//! where `u64` is big-endian and each record lists its fields in the order
+//! of `VoterInfo::fields`, empty fields included.
 
 use serde::{Serialize, Deserialize};
+use log::error;
+use sha2::{Sha256, Digest};
 use super::*;
 
 
+pub const ROSTER_ENCODING: &str = "seventh-estate-roster-v1";
+
+
 /// Voter Roster
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VoterRoster {
@@ -21,26 +39,70 @@ pub struct VoterRosterRecord {
 }
 
 /// Voter Information contained in the roster.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The optional address fields are left out of the serialized roster when
+/// empty, so rosters bound before they existed keep their commitments.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct VoterInfo {
     pub last_name: String,
     pub first_name: String,
     pub street_address: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub address2: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub apartment: String,
     pub city: String,
     pub state: String,
-    pub zip_code: String
+    pub zip_code: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub country: String,
+    /// Language the ballot is printed in, the poll language if empty.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub language: String
+}
+
+impl VoterInfo {
+    /// The fields in the order of the roster encoding.
+    pub fn fields(self: &Self) -> [&str; 10] {
+        [
+            &self.last_name,
+            &self.first_name,
+            &self.street_address,
+            &self.address2,
+            &self.apartment,
+            &self.city,
+            &self.state,
+            &self.zip_code,
+            &self.country,
+            &self.language
+        ]
+    }
 }
 
 impl VoterRoster {
     pub fn from_file(path: &dyn AsRef<Path>) -> Result<Self> {
-        let mut csvreader = csv::Reader::from_path(path)?;
-        let records = csvreader.deserialize::<VoterRosterFileRow>();
+        Self::from_file_with_profile(path, &RosterImportProfile::default())
+    }
+
+    /// Read a roster file as described by an import profile. Every bad
+    /// row is logged with its line number before the import fails.
+    pub fn from_file_with_profile(path: &dyn AsRef<Path>, profile: &RosterImportProfile) -> Result<Self> {
+        let rows = profile.read(path)?;
+        let errors: Vec<&RosterImportError> = rows.iter()
+            .filter_map(|row| row.as_ref().err())
+            .collect();
+        if !errors.is_empty() {
+            errors.iter().for_each(|err| { error!("Roster {}", err); });
+            return Err(From::from(format!("{} bad rows in voter roster.", errors.len())));
+        }
         Ok(VoterRoster {
-            records: records.enumerate()
-                .map(|(n, result)| {
+            records: rows.into_iter()
+                .filter_map(|row| row.ok())
+                .enumerate()
+                .map(|(n, voter_info)| {
                     VoterRosterRecord {
                         position: n,
-                        voter_info: VoterInfo::from(result.unwrap())
+                        voter_info: voter_info
                     }})
                 .collect::<VoterRosterRecords>()
         })
@@ -50,6 +112,25 @@ impl VoterRoster {
         self.records.len()
     }
 
+    /// SHA-256 of the roster encoded as above, in hex. This is the form
+    /// bound to the poll, sampled from and committed to.
+    pub fn hash(self: &Self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.input(ROSTER_ENCODING.as_bytes());
+        hasher.input(&[0u8]);
+        hasher.input(&(self.records.len() as u64).to_be_bytes());
+        self.records.iter()
+            .for_each(|record| {
+                hasher.input(&(record.position as u64).to_be_bytes());
+                record.voter_info.fields().iter()
+                    .for_each(|field| {
+                        hasher.input(&(field.len() as u64).to_be_bytes());
+                        hasher.input(field.as_bytes());
+                    });
+            });
+        hex::encode(hasher.result())
+    }
+
     pub fn restricted(self: &Self) -> RestrictedVoterRoster {
         RestrictedVoterRoster::from((*self).clone())
     }
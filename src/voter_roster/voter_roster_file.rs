@@ -20,9 +20,13 @@ impl From<VoterRosterFileRow> for VoterInfo {
             last_name: row.last_name,
             first_name: row.first_name,
             street_address: row.street_address,
+            address2: "".to_owned(),
+            apartment: "".to_owned(),
             city: row.city,
             state: row.state,
-            zip_code: row.zip_code
+            zip_code: row.zip_code,
+            country: "".to_owned(),
+            language: "".to_owned()
         }
     }
 }
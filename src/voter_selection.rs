@@ -2,23 +2,244 @@
 //!
 //! Voters are selected by combining the committed and drawn summands
 //! modulo the number of entries in the voter roster.
+//!
+//! With stratification, the ballots are split between the strata of the
+//! roster and the summands of each stratum are taken modulo the number of
+//! voters in that stratum. Ballots are assigned to strata in stratum
+//! order, so the first summands belong to the first stratum.
+//!
+//! The selection picks voters by their index among the voters of their
+//! stratum, in roster order; `member_positions` streams the roster to
+//! find their roster positions. Without stratification the only stratum
+//! is the whole roster and the indices are the positions. The positions
+//! of the voters of each stratum are published only as salted
+//! commitments (see `stratum_member_commitments`), so the stratum of a
+//! voter stays private until their record is revealed.
+//!
+//! Voters are selected without replacement: a ballot landing on a voter
+//! already selected probes forward to the next voter not yet selected.
 
 use std::convert::From;
+use std::collections::{BTreeMap, HashMap};
 use crate::Result;
 use crate::summands::*;
+use crate::voter_roster::{VoterRoster, VoterRosterRecord, VoterInfo, RosterSalt, member_commitment};
+use crate::poll_configuration::{Stratification, StratumField, StratumAllocation, Stratum};
 
 pub type VoterRosterIndices = Vec<usize>;
+/// Voters selected in each stratum, as indices among the voters of their
+/// stratum in roster order. Ballots are in stratum order.
+pub type StratumMembers = Vec<usize>;
 
 pub fn select_voters(committed: CommittedSummands, drawn: DrawnSummands, roster_size: usize) -> Result<VoterRosterIndices> {
+    // The members of the whole roster are its positions.
+    let whole_roster = vec![Stratum {
+        key: "".to_owned(),
+        ballots: committed.len(),
+        voters: roster_size
+    }];
+    select_members_in_strata(committed, drawn, &whole_roster)
+}
+
+/// Select the voters of each stratum, in stratum order.
+pub fn select_members_in_strata(committed: CommittedSummands, drawn: DrawnSummands, strata: &Vec<Stratum>) -> Result<StratumMembers> {
+    let (members, _collisions) = select_members_counting_collisions(committed, drawn, strata)?;
+    Ok(members)
+}
+
+/// Select the voters of each stratum and count the collisions: ballots
+/// whose summands landed on a voter already selected. Such a ballot goes
+/// to the next voter of its stratum not yet selected, wrapping around,
+/// so anyone holding the summands reaches the same selection.
+pub fn select_members_counting_collisions(committed: CommittedSummands, drawn: DrawnSummands, strata: &Vec<Stratum>) -> Result<(StratumMembers, usize)> {
     assert!(committed.len() == drawn.len(),
         "Number of committed summands and number of drawn summands must be equal.");
-    Ok(Summands::from(committed.clone()).records.iter()
-        .zip(Summands::from(drawn.clone()).records.iter())
-        .map(|(crec, drec)| {
+    assert!(committed.len() == strata.iter().map(|stratum| stratum.ballots).sum::<usize>(),
+        "Number of summands must match the ballots allocated to the strata.");
+    if let Some(stratum) = strata.iter().find(|stratum| stratum.ballots > stratum.voters) {
+        return Err(From::from(format!("Stratum \"{}\" has {} ballots for {} voters.",
+            stratum.key, stratum.ballots, stratum.voters)));
+    }
+    let committed = Summands::from(committed);
+    let drawn = Summands::from(drawn);
+    let mut records = committed.records.iter().zip(drawn.records.iter());
+    let mut members = StratumMembers::new();
+    let mut collisions = 0;
+    for stratum in strata.iter() {
+        let mut selected = vec![false; stratum.voters];
+        for (crec, drec) in records.by_ref().take(stratum.ballots) {
             assert!(crec.position == drec.position,
                 "Summand record positions do not match.");
-            (crec.summand + drec.summand) % roster_size
-        })
-        .collect::<VoterRosterIndices>())
+            let mut n = (crec.summand + drec.summand) % stratum.voters;
+            if selected[n] { collisions += 1; }
+            while selected[n] { n = (n + 1) % stratum.voters; }
+            selected[n] = true;
+            members.push(n);
+        }
+    }
+    Ok((members, collisions))
+}
+
+/// Walk a roster stream and call `f` with the stratum, the index among
+/// the voters of that stratum and the roster position of each record.
+/// Fails if the roster does not match the strata.
+fn for_each_member<I, F>(records: I, stratification: Option<&Stratification>, strata: &Vec<Stratum>, mut f: F) -> Result<()>
+    where I: Iterator<Item = Result<VoterRosterRecord>>,
+          F: FnMut(usize, usize, usize) {
+    let index: HashMap<&str, usize> = strata.iter().enumerate()
+        .map(|(n, stratum)| (stratum.key.as_str(), n))
+        .collect();
+    let mut counts = vec![0; strata.len()];
+    for record in records {
+        let record = record?;
+        let stratum = match stratification {
+            Some(stratification) => {
+                let key = stratum_key(&record.voter_info, stratification);
+                match index.get(key.as_str()) {
+                    Some(&stratum) => stratum,
+                    None => return Err(From::from(format!("Roster record {} is in no stratum.", record.position)))
+                }
+            },
+            None if strata.len() == 1 => 0,
+            None => return Err(From::from("Strata without a stratification."))
+        };
+        f(stratum, counts[stratum], record.position);
+        counts[stratum] += 1;
+    }
+    if let Some((stratum, count)) = strata.iter().zip(counts.iter()).find(|(stratum, count)| stratum.voters != **count) {
+        return Err(From::from(format!("Stratum \"{}\" has {} voters in the roster, {} when bound.",
+            stratum.key, count, stratum.voters)));
+    }
+    Ok(())
+}
+
+/// Roster positions of the selected members, in selection order. The
+/// roster is streamed; only the selected positions are held.
+pub fn member_positions<I>(records: I, stratification: Option<&Stratification>, strata: &Vec<Stratum>, members: &StratumMembers) -> Result<VoterRosterIndices>
+    where I: Iterator<Item = Result<VoterRosterRecord>> {
+    // Ballots are in stratum order: each ballot's stratum follows from the allocation.
+    let mut slots: HashMap<(usize, usize), usize> = HashMap::new();
+    strata.iter().enumerate()
+        .flat_map(|(n, stratum)| std::iter::repeat(n).take(stratum.ballots))
+        .zip(members.iter())
+        .enumerate()
+        .for_each(|(slot, (stratum, &member))| { slots.insert((stratum, member), slot); });
+    let mut positions: Vec<Option<usize>> = vec![None; members.len()];
+    for_each_member(records, stratification, strata, |stratum, member, position| {
+        if let Some(&slot) = slots.get(&(stratum, member)) {
+            positions[slot] = Some(position);
+        }
+    })?;
+    positions.into_iter()
+        .map(|position| position.ok_or_else(|| From::from("Selected voter missing from the roster.")))
+        .collect()
+}
+
+/// Roster positions of the voters of each stratum, in roster order.
+pub fn stratum_positions<I>(records: I, stratification: Option<&Stratification>, strata: &Vec<Stratum>) -> Result<Vec<Vec<usize>>>
+    where I: Iterator<Item = Result<VoterRosterRecord>> {
+    let mut positions: Vec<Vec<usize>> = strata.iter().map(|stratum| Vec::with_capacity(stratum.voters)).collect();
+    for_each_member(records, stratification, strata, |stratum, _member, position| {
+        positions[stratum].push(position);
+    })?;
+    Ok(positions)
+}
+
+/// Salted commitments of the roster positions of the voters of each
+/// stratum, in roster order. `salts` yields the salt of each record, in
+/// roster order.
+pub fn stratum_member_commitments<I, S>(records: I, salts: S, stratification: &Stratification, strata: &Vec<Stratum>) -> Result<Vec<Vec<String>>>
+    where I: Iterator<Item = Result<VoterRosterRecord>>,
+          S: Iterator<Item = RosterSalt> {
+    let mut salts = salts;
+    let mut commitments: Vec<Vec<String>> = strata.iter().map(|stratum| Vec::with_capacity(stratum.voters)).collect();
+    let mut missing_salt = false;
+    for_each_member(records, Some(stratification), strata, |stratum, _member, position| {
+        match salts.next() {
+            Some(salt) => commitments[stratum].push(member_commitment(position, &salt)),
+            None => missing_salt = true
+        }
+    })?;
+    if missing_salt {
+        return Err(From::from("One salt is needed per roster record."));
+    }
+    Ok(commitments)
 }
 
+/// Split a roster into strata and allocate the ballots between them.
+/// Strata are ordered by key.
+pub fn stratify(roster: &VoterRoster, stratification: &Stratification, num_ballots: usize) -> Result<Vec<Stratum>> {
+    let mut members: BTreeMap<String, usize> = BTreeMap::new();
+    roster.records.iter()
+        .for_each(|record| {
+            *members.entry(stratum_key(&record.voter_info, stratification)).or_insert(0) += 1;
+        });
+    let sizes: Vec<usize> = members.values().cloned().collect();
+    let ballots: Vec<usize> = match stratification.allocation {
+        StratumAllocation::Proportional => allocate_proportionally(&sizes, num_ballots),
+        StratumAllocation::Explicit => {
+            if let Some(key) = stratification.ballots.keys().find(|key| !members.contains_key(*key)) {
+                return Err(From::from(format!("No voter in stratum \"{}\".", key)));
+            }
+            let ballots: Vec<usize> = members.keys()
+                .map(|key| *stratification.ballots.get(key).unwrap_or(&0))
+                .collect();
+            if ballots.iter().sum::<usize>() != num_ballots {
+                return Err(From::from(format!("Strata are allocated {} ballots, the poll has {}.",
+                    ballots.iter().sum::<usize>(), num_ballots)));
+            }
+            ballots
+        }
+    };
+    if let Some(((key, _), _)) = members.iter().zip(ballots.iter()).find(|((_, voters), ballots)| **ballots > **voters) {
+        return Err(From::from(format!("Stratum \"{}\" is allocated more ballots than it has voters.", key)));
+    }
+    Ok(members.into_iter().zip(ballots.into_iter())
+        .map(|((key, voters), ballots)| Stratum {
+            key: key,
+            ballots: ballots,
+            voters: voters
+        }).collect())
+}
+
+/// Stratum of a voter: the upper-cased field, cut to the prefix if any.
+pub fn stratum_key(info: &VoterInfo, stratification: &Stratification) -> String {
+    let value = match stratification.field {
+        StratumField::State => &info.state,
+        StratumField::ZipCode => &info.zip_code,
+        StratumField::City => &info.city,
+        StratumField::Country => &info.country
+    };
+    let value = value.trim().to_uppercase();
+    match stratification.prefix {
+        Some(prefix) => value.chars().take(prefix).collect(),
+        None => value
+    }
+}
+
+/// Allocate ballots in proportion to the stratum sizes by the largest
+/// remainder method. Ties go to the earlier stratum.
+///
+/// # Examples
+///
+/// ```
+/// use seventh_estate::voter_selection::*;
+///
+/// assert_eq!(vec![3, 2, 1], allocate_proportionally(&vec![5, 3, 2], 6));
+/// ```
+pub fn allocate_proportionally(sizes: &Vec<usize>, num_ballots: usize) -> Vec<usize> {
+    let total: usize = sizes.iter().sum();
+    assert!(0 < total,
+        "Cannot allocate ballots to an empty roster.");
+    let mut ballots: Vec<usize> = sizes.iter()
+        .map(|&size| num_ballots * size / total)
+        .collect();
+    let mut remainders: Vec<(usize, usize)> = sizes.iter().enumerate()
+        .map(|(n, &size)| (n, num_ballots * size % total))
+        .collect();
+    remainders.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let left = num_ballots - ballots.iter().sum::<usize>();
+    remainders.iter().take(left)
+        .for_each(|&(n, _)| ballots[n] += 1);
+    ballots
+}
@@ -1,4 +1,5 @@
-// use seventh_estate::blockchain::*;
+use seventh_estate::ballots::*;
+use seventh_estate::blockchain::*;
 use seventh_estate::blockchain::merkle::*;
 
 #[tokio::test]
@@ -10,4 +11,58 @@ async fn test_post() {
     let _tree = new_tree(data).unwrap();
     // TODO: Futures not resolving in test
     // assert_eq!((), post(tree.root()).unwrap());
-}
\ No newline at end of file
+}
+fn ballots(num_ballots: usize, num_options: usize) -> Vec<Ballot> {
+    let serials: Vec<BallotSerial> = (0..num_ballots).collect();
+    let votecodes: Vec<VoteCode> = (0..num_ballots * num_options).map(|n| vec![n as u8]).collect();
+    generate_ballots(&serials, &votecodes, num_options)
+}
+
+#[test]
+fn test_generate_ballots() {
+    let ballots = ballots(2, 3);
+    assert_eq!(vec![0, 1], ballots.iter().map(|ballot| ballot.serial).collect::<Vec<BallotSerial>>());
+    // Each ballot takes the next vote codes, one per option in option order.
+    let ballot = &ballots[1];
+    assert_eq!(vec![vec![3], vec![4], vec![5]], ballot.choices.iter().map(|choice| choice.votecode.clone()).collect::<Vec<VoteCode>>());
+    assert_eq!(vec![ChoiceValue(0), ChoiceValue(1), ChoiceValue(2)], ballot.choices.iter().map(|choice| choice.choice).collect::<Vec<ChoiceValue>>());
+    assert!(ballot.choices.iter().all(|choice| choice.serial == 1));
+}
+
+#[test]
+fn test_tagged_choice_value() {
+    let choice = TaggedChoiceValue::from(ChoiceValue(2));
+    assert_eq!("Option 3", string_from_taggedchoicevalue(&choice));
+    assert_eq!("Decoy", string_from_taggedchoicevalue(&TaggedChoiceValue::Decoy));
+    // Every entry of a poll pads to the same length.
+    assert_eq!(string_from_taggedchoicevalue_padded(&choice, 12).len(),
+               string_from_taggedchoicevalue_padded(&TaggedChoiceValue::Decoy, 12).len());
+    assert_eq!("Option 12", string_from_taggedchoicevalue_padded(&TaggedChoiceValue::Choice(11), 12));
+}
+
+#[test]
+fn test_tally_votes() {
+    // Two questions, with three and four options.
+    let questions = vec![ballots(4, 3), {
+        let serials: Vec<BallotSerial> = (0..4).collect();
+        let votecodes: Vec<VoteCode> = (0..16).map(|n| vec![100 + n as u8]).collect();
+        generate_ballots(&serials, &votecodes, 4)
+    }];
+    let choices = map_votes(&questions).unwrap();
+    let serials = map_serials(&questions).unwrap();
+    let num_options = vec![3, 4];
+    let code = |q: usize, serial: usize, option: usize| questions[q][serial].choices[option].votecode.clone();
+
+    let votecodes = vec![
+        code(0, 0, 2), code(1, 0, 3),
+        // A code submitted twice counts once.
+        code(0, 1, 1), code(0, 1, 1), code(1, 1, 3),
+        // Codes of two options of a question count for neither.
+        code(0, 2, 0), code(0, 2, 2), code(1, 2, 1),
+        // The ballot 3 is excluded, and unknown codes are skipped.
+        code(0, 3, 0), vec![255]
+    ];
+    let tally = tally_votes(&choices, &serials, &votecodes, &num_options, &vec![3]);
+    assert_eq!(vec![vec![0, 1, 1], vec![0, 1, 0, 2]], tally.counts);
+    assert_eq!(vec![(2, 0)], tally.overvotes);
+}
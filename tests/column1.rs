@@ -0,0 +1,56 @@
+use seventh_estate::ballots::*;
+use seventh_estate::planes::*;
+use seventh_estate::cryptography::*;
+
+#[test]
+fn test_canonical_encoding() {
+    let format = VoteCodeFormat::default();
+    let votecode = votecode_from_string("12340-56784-00000-99994", &format).unwrap();
+    let entry = SerialVoteCode { serial: 42, votecode: votecode.clone() };
+    assert_eq!("v1:0042:12340567840000099994", entry.to_canonical(1000));
+    assert_eq!(entry, SerialVoteCode::from_canonical(&entry.to_canonical(1000)).unwrap());
+
+    // Serials past two digits are read in full.
+    let entry = SerialVoteCode { serial: 1234, votecode: votecode };
+    assert_eq!(1234, SerialVoteCode::from_canonical(&entry.to_canonical(5000)).unwrap().serial);
+}
+
+#[test]
+fn test_canonical_encoding_crockford() {
+    let entry = SerialVoteCode { serial: 3, votecode: vec![31, 0, 17, 10] };
+    assert_eq!("v1:3:Z0HA", entry.to_canonical(10));
+    assert_eq!(entry, SerialVoteCode::from_canonical("v1:3:Z0HA").unwrap());
+}
+
+#[test]
+fn test_malformed_entries() {
+    assert!(SerialVoteCode::from_canonical("0042: 12340-56784-00000-99994").is_err());
+    assert!(SerialVoteCode::from_canonical("v2:0042:1234").is_err());
+    assert!(SerialVoteCode::from_canonical("v1:00x2:1234").is_err());
+    assert!(SerialVoteCode::from_canonical("v1:0042:12-34").is_err());
+}
+
+#[test]
+fn test_encrypted_roundtrip() {
+    let entry = Column1Entry::Entry(SerialVoteCode { serial: 150, votecode: vec![1, 2, 3, 4, 0] });
+    let key = AEADKey([7; 32]);
+    let nonce = AEADNonce([3; 12]);
+    let encrypted = entry.encrypt(&key, &nonce, 200).unwrap();
+    let filter = PlaneFilterEntry { key: key, decrypt: true };
+    match encrypted.decrypt(&filter).unwrap() {
+        Column1Entry::Entry(decrypted) => assert_eq!(150, decrypted.serial),
+        Column1Entry::Encrypted(_) => panic!("Column 1 entry was not decrypted.")
+    }
+}
+
+#[test]
+fn test_corrupted_entry() {
+    let key = AEADKey([7; 32]);
+    let nonce = AEADNonce([3; 12]);
+    let corrupted = Column1Entry::Encrypted(AEADString::from_values(
+        aead_encrypt_ex(&key, &nonce, Vec::new(), b"not a serial vote code".to_vec()).unwrap()));
+    assert!(corrupted.decrypt(&PlaneFilterEntry { key: key, decrypt: true }).is_err());
+    let entry = Column1Entry::Entry(SerialVoteCode { serial: 150, votecode: vec![1, 2, 3, 4, 0] });
+    let encrypted = entry.encrypt(&key, &nonce, 200).unwrap();
+    assert!(encrypted.decrypt(&PlaneFilterEntry { key: AEADKey([8; 32]), decrypt: true }).is_err(), "A wrong key is an error.");
+}
@@ -0,0 +1,30 @@
+use seventh_estate::ballots::*;
+use seventh_estate::cryptography::CSPRNGSeed;
+use seventh_estate::poll_configuration::*;
+
+#[test]
+fn test_hand_out_decoys() {
+    let decoys = generate_decoy_serials(CSPRNGSeed::from_vec(&vec![7; CSPRNGSeed::SIZE]), 2, 100);
+    let mut requests: Vec<DecoyRequest> = Vec::new();
+
+    // Decoys are handed out in order, as in the list of decoy serials.
+    let first = hand_out_decoy(&mut requests, &decoys, 100, "voter1", "2020-11-01T10:00:00+00:00".to_owned()).unwrap();
+    assert_eq!(string_from_ballotserial(&decoys[0], 100), first);
+    assert_eq!(2, first.len());
+
+    // A voter gets one decoy only, and that is not a panic.
+    assert!(hand_out_decoy(&mut requests, &decoys, 100, "voter1", "2020-11-01T11:00:00+00:00".to_owned()).is_err());
+    let second = hand_out_decoy(&mut requests, &decoys, 100, "voter2", "2020-11-01T12:00:00+00:00".to_owned()).unwrap();
+    assert_eq!(string_from_ballotserial(&decoys[1], 100), second);
+
+    // Once every decoy is handed out, a request fails.
+    assert!(hand_out_decoy(&mut requests, &decoys, 100, "voter3", "2020-11-01T13:00:00+00:00".to_owned()).is_err());
+    assert_eq!(2, requests.len());
+
+    // The status names the voters but no decoy serial.
+    let status = decoy_status_lines(&requests, decoys.len());
+    assert_eq!(vec![
+        "Decoys handed out: 2 of 2",
+        "  2020-11-01T10:00:00+00:00: voter1",
+        "  2020-11-01T12:00:00+00:00: voter2"], status);
+}
@@ -0,0 +1,181 @@
+use seventh_estate::mailing::*;
+use seventh_estate::ballots::TemplateFont;
+use seventh_estate::voter_roster::VoterInfo;
+use seventh_estate::cryptography::CSPRNGSeed;
+use std::path::Path;
+use std::fs;
+
+fn voter(last_name: &str, apartment: &str, address2: &str, zip_code: &str) -> VoterInfo {
+    VoterInfo {
+        last_name: last_name.to_owned(),
+        first_name: "Ada".to_owned(),
+        street_address: "12 Main Street".to_owned(),
+        address2: address2.to_owned(),
+        apartment: apartment.to_owned(),
+        city: "Springfield".to_owned(),
+        state: "IL".to_owned(),
+        zip_code: zip_code.to_owned(),
+        ..Default::default()
+    }
+}
+
+fn intelligent_mail(mailer_id: &str, first_serial: u64) -> IntelligentMail {
+    IntelligentMail {
+        barcode_id: "00".to_owned(),
+        service_type: "270".to_owned(),
+        mailer_id: mailer_id.to_owned(),
+        first_serial: first_serial
+    }
+}
+
+#[test]
+fn test_address_label_lines() {
+    let address = AddressLabel::from_voter(1, voter("Lovelace", "Apt 3", "Building B", "62701"));
+    assert_eq!("Apt 3, Building B", address.address2);
+    assert_eq!(vec!["Ada Lovelace", "12 Main Street", "Apt 3, Building B", "Springfield IL 62701"], address.lines());
+
+    let address = AddressLabel::from_voter(1, voter("Lovelace", "", "", "62701"));
+    assert_eq!(3, address.lines().len(), "Empty lines are skipped.");
+}
+
+#[test]
+fn test_label_sheet_formats() {
+    let format = LabelSheetFormat::builtin("30-up").unwrap();
+    assert!(format.check().is_ok());
+    assert_eq!(30, format.labels_per_sheet());
+    let format = LabelSheetFormat::builtin("14-up").unwrap();
+    assert!(format.check().is_ok());
+    assert_eq!(14, format.labels_per_sheet());
+    assert!(LabelSheetFormat::builtin("99-up").is_none());
+    assert!(LabelSheetFormat::from_name_or_file("no-such-format.yaml").is_err());
+
+    let mut format = LabelSheetFormat::builtin("30-up").unwrap();
+    format.columns = 4;
+    assert!(format.check().is_err(), "Four columns of 30-up labels are wider than the sheet.");
+    let mut format = LabelSheetFormat::builtin("30-up").unwrap();
+    format.padding = 20.0;
+    assert!(format.check().is_err(), "The padding leaves no room for text.");
+
+    let window = EnvelopeWindow::builtin("no10").unwrap();
+    assert!(window.check().is_ok());
+    let mut window = EnvelopeWindow::builtin("no10").unwrap();
+    window.y = 270.0;
+    assert!(window.check().is_err(), "The window is off the page.");
+}
+
+#[test]
+fn test_intelligent_mail() {
+    assert!(intelligent_mail("123456", 0).check(1000).is_ok());
+    assert!(intelligent_mail("912345678", 0).check(1000000).is_err(), "A 9-digit mailer ID leaves 6 digits of serial.");
+    assert!(intelligent_mail("912345678", 0).check(999999).is_ok());
+    assert!(intelligent_mail("912345", 0).check(1).is_err(), "A 6-digit mailer ID does not start with 9.");
+    assert!(intelligent_mail("12345", 0).check(1).is_err());
+    let mut imb = intelligent_mail("123456", 0);
+    imb.barcode_id = "05".to_owned();
+    assert!(imb.check(1).is_err());
+
+    let imb = intelligent_mail("912345678", 7);
+    assert_eq!("00270912345678000009", imb.tracking_code(2));
+    assert_eq!("0027091234567800000762701", imb.field(0, "62701"));
+    assert_eq!(20 + 11, imb.field(0, "62701-1234 56").len());
+}
+
+#[test]
+fn test_mailing_files() {
+    let directory = "test_mailing_files/";
+    let addresses: Vec<AddressLabel> = (0..35)
+        .map(|n| AddressLabel::from_voter(n + 1, voter(&format!("Voter {}", n), "", "", "62701")))
+        .collect();
+
+    let (labels, overflows) = print_label_sheets(&addresses, &LabelSheetFormat::builtin("30-up").unwrap(), directory).unwrap();
+    assert!(Path::new(&labels).exists());
+    assert_eq!(0, overflows);
+
+    // A long address is printed smaller; a far longer one is cut.
+    let building = "The Very Long Name Of A Building, Somewhere On The Campus Of A Large University ";
+    let mut long = addresses.clone();
+    long[0].address2 = building.to_owned();
+    long[1].address2 = building.repeat(4);
+    let (_, overflows) = print_label_sheets(&long, &LabelSheetFormat::builtin("30-up").unwrap(), directory).unwrap();
+    assert_eq!(1, overflows, "The longest address does not fit its label.");
+
+    long[0].address2 = building.repeat(2);
+    long[1].address2 = building.repeat(8);
+    let (pages, overflows) = print_envelope_pages(&long, &EnvelopeWindow::builtin("no10").unwrap(), directory).unwrap();
+    assert!(Path::new(&pages).exists());
+    assert_eq!(1, overflows, "The longest address does not fit the window.");
+
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn test_mailing_fonts() {
+    let directory = "test_mailing_fonts/";
+    let mut addresses: Vec<AddressLabel> = (0..3)
+        .map(|n| AddressLabel::from_voter(n + 1, voter(&format!("Voter {}", n), "", "", "62701")))
+        .collect();
+    addresses[0].first_name = "Zoë".to_owned();
+    addresses[0].last_name = "Émile".to_owned();
+
+    // A font file is subset to the characters of the addresses.
+    let font = "tests/fonts/DejaVuSans-ExtraLight.ttf";
+    let mut format = LabelSheetFormat::builtin("30-up").unwrap();
+    format.font = TemplateFont::File { file: font.to_owned(), hash: None };
+    let (labels, _) = print_label_sheets(&addresses, &format, directory).unwrap();
+    assert!(fs::metadata(&labels).unwrap().len() < fs::metadata(font).unwrap().len() / 2);
+    let mut window = EnvelopeWindow::builtin("no10").unwrap();
+    window.font = TemplateFont::File { file: font.to_owned(), hash: None };
+    let (pages, _) = print_envelope_pages(&addresses, &window, directory).unwrap();
+    assert!(fs::metadata(&pages).unwrap().len() < fs::metadata(font).unwrap().len() / 2);
+
+    // Addresses that need shaping are rejected rather than printed wrongly.
+    addresses[1].city = "القاهرة".to_owned();
+    assert!(print_label_sheets(&addresses, &format, directory).is_err());
+    assert!(print_envelope_pages(&addresses, &window, directory).is_err());
+
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn test_mail_pieces() {
+    let pieces = MailPieces::derive(CSPRNGSeed::from_vec(&vec![1; 32]), 200);
+    assert_eq!(200, pieces.len());
+    let mut voters = pieces.voters.clone();
+    voters.sort();
+    let mut serials = pieces.serials.clone();
+    serials.sort();
+    assert_eq!((0..200).collect::<Vec<usize>>(), voters, "Every voter gets one mail piece.");
+    assert_eq!((0..200).collect::<Vec<usize>>(), serials, "Every serial goes in one mail piece.");
+    assert_ne!(pieces.voters, pieces.serials, "The voters and serials are shuffled independently.");
+    assert_ne!((0..200).collect::<Vec<usize>>(), pieces.serials, "Ballots do not go out in serial order.");
+    assert_ne!(pieces, MailPieces::derive(CSPRNGSeed::from_vec(&vec![2; 32]), 200));
+    assert_eq!(1, MailPieces::number(0));
+}
+
+#[test]
+fn test_vendor_packages() {
+    let directory = "test_vendor_packages/";
+    fs::create_dir_all(directory).unwrap();
+    let file = Path::new(directory).join("addresses.csv");
+    fs::write(&file, "mail_piece,last_name\n1,Lovelace\n").unwrap();
+
+    let mut package = VendorPackage::new(ADDRESSING_VENDOR);
+    package.add_file(&file).unwrap();
+    assert!(package.add_file(&file).is_err(), "A package holds one file of a name.");
+    let sealed_file = package.seal("password").unwrap().to_file(directory).unwrap();
+    assert!(sealed_file.ends_with("addressing_vendor.package"));
+
+    let sealed = SealedPackage::from_file(&sealed_file).unwrap();
+    assert!(sealed.open("wrong password").is_err());
+    let mut relabeled = sealed.clone();
+    relabeled.vendor = BALLOT_PRINTER.to_owned();
+    assert!(relabeled.open("password").is_err(), "A package cannot be handed to another vendor.");
+
+    let opened = sealed.open("password").unwrap();
+    assert_eq!(package, opened);
+    let files = opened.write_files(&format!("{}opened/", directory)).unwrap();
+    assert_eq!(1, files.len());
+    assert_eq!(fs::read(&file).unwrap(), fs::read(&files[0]).unwrap());
+
+    fs::remove_dir_all(directory).unwrap();
+}
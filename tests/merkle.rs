@@ -34,6 +34,16 @@ fn test_root() {
     let t = new_tree(data).unwrap();
 
     assert_eq!("5873a2bee359c763dc3adb0d44d72fe4fff49114f5fc02a7505c8ee61aa4c184", hex::encode(t.root()));
+
+    // The same tree, built from the hashes of the data.
+    let t = new_tree_from_hashes(vec![
+        "Colombier,Gerri,7 Del Sol Lane,Philadelphia,PA,19160",
+        "64: 86961-67106-91541-74973",
+        "Not Voted",
+        "$chacha20_poly1305_aead$GZm76RMgPAkMQMki$R1ptNzZSTWdQQWtNUU1raQ==$OFz4Z9GNmg==$6MzPD1MV07tqNG+JCYkp6Q==$",
+        "13, 20, 35, 43, 58, 69, 73, 77, 81, 88, 93, 96"
+    ].into_iter().map(data_hash).collect()).unwrap();
+    assert_eq!("5873a2bee359c763dc3adb0d44d72fe4fff49114f5fc02a7505c8ee61aa4c184", hex::encode(t.root()));
 }
 
 #[test]
@@ -120,4 +130,56 @@ fn test_store_load_tree() {
 
     // Delete test file
     fs::remove_file(filename).unwrap();
-}
\ No newline at end of file
+}
+#[test]
+fn test_roster_proof() {
+    use seventh_estate::blockchain::roster_proof::*;
+    use seventh_estate::cryptography::CSPRNGSeed;
+    use seventh_estate::voter_roster::*;
+
+    let voters = vec![("McKie", "Sioux"), ("Gabbitus", "Valle"), ("Colombier", "Gerri")];
+    let roster = VoterRoster {
+        records: voters.into_iter().enumerate()
+            .map(|(n, (last_name, first_name))| VoterRosterRecord {
+                position: n,
+                voter_info: VoterInfo {
+                    last_name: last_name.to_owned(),
+                    first_name: first_name.to_owned(),
+                    street_address: "7 Del Sol Lane".to_owned(),
+                    city: "Philadelphia".to_owned(),
+                    state: "PA".to_owned(),
+                    zip_code: "19160".to_owned(),
+                    ..Default::default()
+                }
+            }).collect()
+    };
+    let salts = generate_roster_salts(CSPRNGSeed::from_vec(&vec![7; CSPRNGSeed::SIZE]), roster.len());
+
+    // Without and with voter privacy, built as generate-roster-proof builds it.
+    let records = || roster.records.clone().into_iter().map(Ok);
+    for salts in vec![None, Some(&salts)] {
+        let tree = || roster_tree_from_records(records(), salts.map(|salts| salts.clone().into_iter())).unwrap();
+        let root = hex::encode(tree().root());
+        let proof = record_proof(tree(), 1,
+            RestrictedVoterInfo::from(roster.records[1].voter_info.clone()), salts.map(|salts| salts[1])).unwrap();
+        assert_eq!(salts.is_some(), proof.salt.is_some());
+        assert!(verify_roster_proof(&proof, &root));
+        assert_eq!(proof.root(), Some(&root));
+
+        // Another root, or a tampered record, fails.
+        assert!(!verify_roster_proof(&proof, &"00".repeat(32)));
+        let mut tampered = proof.clone();
+        tampered.restricted = tampered.restricted.replace("Gabbitus", "Gabitus");
+        assert!(!verify_roster_proof(&tampered, &root));
+        let mut tampered = proof.clone();
+        tampered.position = 2;
+        assert!(!verify_roster_proof(&tampered, &root));
+
+        // A record that is not in the tree has no proof.
+        assert!(record_proof(tree(), 3,
+            RestrictedVoterInfo::from(roster.records[1].voter_info.clone()), salts.map(|salts| salts[1])).is_err());
+    }
+
+    // Voter privacy needs one salt per record.
+    assert!(roster_tree_from_records(records(), Some(salts[..2].to_vec().into_iter())).is_err());
+}
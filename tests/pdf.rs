@@ -1,44 +1,67 @@
 use seventh_estate::ballots::*;
-use hex;
+use seventh_estate::poll_configuration::{PollQuestion, QuestionTranslation};
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
 use std::fs;
 use mime_guess;
 use mime::APPLICATION_PDF;
 
-#[macro_use] extern crate slice_as_array;
+fn default_fonts() -> BallotFonts {
+    BallotFonts::load(&BallotTemplate::default()).unwrap()
+}
 
-fn slice_as_hash(xs: &[u8]) -> &[u8; 20] {
-    slice_as_array!(xs, [u8; 20]).expect("bad hash length")
+fn output_to(directory: &str) -> BallotPdfOutput {
+    BallotPdfOutput {
+        directory: directory.to_owned(),
+        ..BallotPdfOutput::default()
+    }
 }
 
-#[test]
-fn test_pdf() {
+fn question(question: &str, options: &[&str]) -> PollQuestion {
+    PollQuestion {
+        question: question.to_owned(),
+        options: options.iter().map(|option| option.to_string()).collect(),
+        translations: BTreeMap::new()
+    }
+}
 
-    let vote1 = hex::decode("b28de6131ecdd6075b1473ca6525c0bf990fde7f").unwrap();
-    let vote1 = *slice_as_hash(&vote1);
-    
-    let choice1: BallotChoice = BallotChoice {
-        serial: 123456,
-        votecode: vote1,
-        choice: ChoiceValue::For
-    };
+/// A ballot for a question of two options, with the same vote code for
+/// both choices.
+fn ballot(serial: BallotSerial) -> Ballot {
+    let votecode = votecode_from_string("12340-56784-12340-56784", &VoteCodeFormat::default()).unwrap();
+    Ballot {
+        serial: serial,
+        choices: (0..2).map(|choice| BallotChoice {
+            serial: serial,
+            votecode: votecode.clone(),
+            choice: ChoiceValue(choice)
+        }).collect()
+    }
+}
 
-    let choice2: BallotChoice = BallotChoice {
-        serial: 123456,
-        votecode: vote1,
-        choice: ChoiceValue::Against
-    };
+/// The ballot with a third choice.
+fn three_choices(ballot: Ballot) -> Ballot {
+    let mut ballot = ballot;
+    let third = BallotChoice { choice: ChoiceValue(2), ..ballot.choices[0].clone() };
+    ballot.choices.push(third);
+    ballot
+}
 
-    let ballot: Ballot = Ballot {
-        serial: 123456,
-        choice1: choice1,
-        choice2: choice2
-    };
+fn default_texts(questions: &Vec<PollQuestion>) -> BallotTexts {
+    BallotTexts::untranslated(questions, &BallotTemplate::default())
+}
+
+#[test]
+fn test_pdf() {
+
+    let format = VoteCodeFormat::default();
+    let ballot: Ballot = ballot(123456);
+    let questions: Vec<PollQuestion> = vec![question("Test Question", &["Yes", "No"])];
     
     let filename = BALLOTS_PATH.to_string() + &ballot.serial.to_string()  + ".pdf";
 
     // Test if file was created
-    assert_eq!((), print_ballot(&ballot, "Test Question", "Yes", "No"));
+    assert_eq!(vec![filename.clone()], print_ballot(&vec![ballot.clone()], &default_texts(&questions), &format, None, &BallotTemplate::default(), &default_fonts(), &BallotPdfOutput::default()).unwrap());
     assert_eq!(true, Path::new(&(filename)).exists());
 
 
@@ -48,4 +71,463 @@ fn test_pdf() {
 
     // Delete test file
     fs::remove_file(filename).unwrap();
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_pdf_multiple_questions() {
+
+    let format = VoteCodeFormat::default();
+    let ballots: Vec<Ballot> = vec![ballot(654321), three_choices(ballot(654321))];
+    let questions: Vec<PollQuestion> = vec![
+        question("First Question", &["Yes", "No"]),
+        question("Second Question", &["Red", "Green", "Blue"])
+    ];
+
+    let filename = BALLOTS_PATH.to_string() + "654321.pdf";
+
+    // Test if a single file was created for both questions
+    assert_eq!(vec![filename.clone()], print_ballot(&ballots, &default_texts(&questions), &format, None, &BallotTemplate::default(), &default_fonts(), &BallotPdfOutput::default()).unwrap());
+    assert_eq!(true, Path::new(&(filename)).exists());
+
+    // Delete test file
+    fs::remove_file(filename).unwrap();
+}
+
+#[test]
+fn test_pdf_mismatched_ballot() {
+
+    let format = VoteCodeFormat::default();
+    let texts = default_texts(&vec![question("Test Question", &["Yes", "No"])]);
+    let output = output_to("test_pdf_mismatched_ballot");
+    let print = |ballots: Vec<Ballot>| print_ballot(&ballots, &texts, &format, None, &BallotTemplate::default(), &default_fonts(), &output);
+
+    // A ballot that does not match its texts is an error, not a panic.
+    assert!(print(vec![three_choices(ballot(1))]).is_err());
+    assert!(print(vec![ballot(1), ballot(1)]).is_err());
+    assert!(print(vec![]).is_err());
+    let batch = vec![(vec![ballot(1)], &texts), (vec![three_choices(ballot(2))], &texts)];
+    assert!(print_ballots_batched(&batch, &format, None, &BallotTemplate::default(), &default_fonts(), &output).is_err());
+    assert!(!Path::new("test_pdf_mismatched_ballot").exists());
+}
+
+/// Serials of the ballots in a PDF, in page order. Page contents are
+/// not compressed, and built-in fonts write text as hexadecimal strings.
+fn printed_serials(file: &str) -> Vec<BallotSerial> {
+    let pdf = String::from_utf8_lossy(&fs::read(file).unwrap()).into_owned();
+    let prefix: String = "ballot serial: ".bytes().map(|byte| format!("{:02X}", byte)).collect();
+    pdf.split(&format!("<{}", prefix)).skip(1)
+        .map(|text| {
+            let digits = &text[..text.find('>').unwrap()];
+            let serial = hex::decode(digits).unwrap();
+            String::from_utf8(serial).unwrap().parse::<BallotSerial>().unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn test_decoy_stickers() {
+    let directory = "test_decoy_stickers";
+    let file = print_decoy_stickers(&vec![7, 42, 3], 100, directory).unwrap();
+    assert_eq!(Path::new(directory).join("decoy_stickers.pdf").display().to_string(), file);
+
+    // One sticker per decoy, in the order given, with serials as in the
+    // list of decoy serials.
+    let pdf = String::from_utf8_lossy(&fs::read(&file).unwrap()).into_owned();
+    assert_eq!(vec![7, 42, 3], printed_serials(&file));
+    let padded: String = "ballot serial: 07".bytes().map(|byte| format!("{:02X}", byte)).collect();
+    assert!(pdf.contains(&padded));
+
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn test_pdf_batched() {
+
+    let format = VoteCodeFormat::default();
+    let questions: Vec<PollQuestion> = vec![question("Test Question", &["Yes", "No"])];
+
+    let texts = default_texts(&questions);
+
+    // Ten serials, in the order of their mail pieces.
+    let order: Vec<BallotSerial> = vec![3, 7, 0, 9, 1, 8, 2, 6, 4, 5];
+    let serial_ballots: Vec<(Vec<Ballot>, &BallotTexts)> = order.iter().cloned()
+        .map(|serial| (vec![ballot(serial)], &texts))
+        .collect();
+
+    // 4-up, 10 ballots are 3 sheets, in chunks of 2 sheets.
+    let output = BallotPdfOutput {
+        directory: "ballots_batched_test/".to_owned(),
+        batched: true,
+        imposition: Imposition::FourUp,
+        sheets_per_file: Some(2),
+        scratch_off: ScratchOffLayers::Combined,
+        decoy_directory: DECOYS_PATH.to_owned()
+    };
+    let files = print_ballots_batched(&serial_ballots, &format, None, &BallotTemplate::default(), &default_fonts(), &output).unwrap();
+    assert_eq!(2, files.len());
+    files.iter().for_each(|file| {
+        assert_eq!(true, Path::new(file).exists());
+        assert_eq!(Some(APPLICATION_PDF), mime_guess::from_path(file).first());
+    });
+
+    // A chunk of no sheets is an error.
+    let empty = BallotPdfOutput {
+        sheets_per_file: Some(0),
+        ..output.clone()
+    };
+    assert!(print_ballots_batched(&serial_ballots, &format, None, &BallotTemplate::default(), &default_fonts(), &empty).is_err());
+
+    // Without chunks, a single PDF.
+    let output = BallotPdfOutput {
+        imposition: Imposition::TwoUp,
+        sheets_per_file: None,
+        ..output
+    };
+    let files = print_ballots_batched(&serial_ballots, &format, None, &BallotTemplate::default(), &default_fonts(), &output).unwrap();
+    assert_eq!(1, files.len());
+    assert_eq!(true, Path::new(&output.directory).join("ballots.pdf").exists());
+
+    // The pages follow the order given.
+    assert_eq!(order, printed_serials(&files[0]));
+
+    // Delete test files
+    fs::remove_dir_all(&output.directory).unwrap();
+}
+
+#[test]
+fn test_ballot_template() {
+
+    // The example template is the default layout.
+    let template = BallotTemplate::from_file(&Path::new("examples/ballot-template.yaml")).unwrap();
+    let default = BallotTemplate::default();
+    assert_eq!(default.paper, template.paper);
+    assert_eq!(default.instructions, template.instructions);
+    assert_eq!(default.blocks.len(), template.blocks.len());
+    assert_eq!(default.blocks[3].text, template.blocks[3].text);
+
+    // Fonts must be built-in and defined.
+    let mut bad = template.clone();
+    bad.fonts.insert("title".to_owned(), TemplateFont::Builtin("ComicSans".to_owned()));
+    assert!(bad.check().is_err());
+    let mut bad = template.clone();
+    bad.blocks[0].font = "heading".to_owned();
+    assert!(bad.check().is_err());
+
+    // Scripts that need shaping cannot be printed.
+    let mut bad = template.clone();
+    bad.blocks[1].text = "تعليمات".to_owned();
+    assert!(bad.check().is_err());
+    assert!(check_unshaped_text("Instrucciones").is_ok());
+    assert!(check_unshaped_text("निर्देश").is_err());
+
+    // An A6 ballot with other wording.
+    let mut template = template;
+    template.paper = PaperSize::A6;
+    template.blocks[1].text = "Instrucciones".to_owned();
+    assert!(template.check().is_ok());
+    assert_eq!((105.0, 148.0), template.page_size());
+
+    let format = VoteCodeFormat::default();
+    let ballot: Ballot = ballot(246810);
+    let questions: Vec<PollQuestion> = vec![question("Test Question", &["Yes", "No"])];
+    let directory = "ballots_template_test/";
+    print_ballot(&vec![ballot], &BallotTexts::untranslated(&questions, &template), &format, None, &template, &BallotFonts::load(&template).unwrap(), &output_to(directory)).unwrap();
+    assert_eq!(true, Path::new(directory).join("246810.pdf").exists());
+
+    // Delete test files
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn test_pdf_font_file() {
+
+    // A font file prints accents and Cyrillic, subset to the ballot's characters.
+    let font = "tests/fonts/DejaVuSans-ExtraLight.ttf";
+    let mut template = BallotTemplate::default();
+    template.fonts.insert("text".to_owned(), TemplateFont::File { file: font.to_owned(), hash: None });
+    template.fonts.insert("title".to_owned(), TemplateFont::File { file: font.to_owned(), hash: Some(font_hash(&fs::read(font).unwrap())) });
+    assert!(template.check().is_ok());
+    let questions: Vec<PollQuestion> = vec![question("Référendum: Референдум?", &["Oui / Да", "Non / Нет"])];
+    let texts = BallotTexts::untranslated(&questions, &template);
+    let fonts = BallotFonts::load(&template).unwrap().subset(&texts.characters()).unwrap();
+    let directory = "ballots_font_file_test/";
+    let files = print_ballot(&vec![ballot(97531)], &texts, &VoteCodeFormat::default(), None, &template, &fonts, &output_to(directory)).unwrap();
+    assert_eq!(vec![Path::new(directory).join("97531.pdf").display().to_string()], files);
+    assert!(fs::metadata(&files[0]).unwrap().len() < fs::metadata(font).unwrap().len() / 2);
+
+    // A font file that changed since the poll was created is not loaded.
+    template.fonts.insert("title".to_owned(), TemplateFont::File { file: font.to_owned(), hash: Some("00".repeat(32)) });
+    assert!(BallotFonts::load(&template).is_err());
+
+    // Delete test files
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn test_text_wrapping() {
+
+    // Built-in fonts are measured as Courier: 0.6 em per character.
+    let fonts = default_fonts();
+    let width = fonts.get("text").text_width("0123456789", 12.0);
+    assert!((width - 10.0 * 0.6 * 12.0 * 25.4 / 72.0).abs() < 1e-9);
+
+    // A long question wraps at spaces to the width of the block.
+    let question = "Should the community centre extend its opening hours to weekends and public holidays?";
+    let lines = fonts.wrap("title", question, 20.0, 128.0);
+    assert!(lines.len() > 1);
+    assert!(lines.iter().all(|line| fonts.get("title").text_width(line, 20.0) <= 128.0));
+    assert_eq!(question, lines.join(" "));
+
+    // Text without spaces breaks between characters.
+    let lines = fonts.wrap("text", "投票用紙の通し番号と投票コードを入力してください", 10.0, 30.0);
+    assert!(lines.len() > 1);
+    assert!(lines.iter().all(|line| fonts.get("text").text_width(line, 10.0) <= 30.0));
+}
+
+#[test]
+fn test_ballot_languages() {
+
+    let mut questions: Vec<PollQuestion> = vec![question("Test Question", &["Yes", "No"])];
+    questions[0].translations.insert("es".to_owned(), QuestionTranslation {
+        question: "Pregunta de prueba".to_owned(),
+        options: vec!["Sí".to_owned(), "No".to_owned()]
+    });
+    assert!(questions[0].translations_complete());
+    let mut template = BallotTemplate::default();
+    template.translations.insert("es".to_owned(), TemplateTranslation {
+        instructions: Some("vote en línea".to_owned()),
+        several_questions_title: None,
+        blocks: Some(vec!["{question}".to_owned(), "Instrucciones".to_owned(), "{instructions}".to_owned(), "serie: {serial}".to_owned()])
+    });
+    assert!(template.check().is_ok());
+    let languages = BallotLanguages {
+        default: "en".to_owned(),
+        bilingual: false
+    };
+
+    // The voter's language when translated, else the default language.
+    let (texts, translated) = BallotTexts::for_language(&questions, &template, &languages, "es");
+    assert!(translated);
+    assert_eq!(vec!["Pregunta de prueba"], texts.questions);
+    assert_eq!(vec![vec!["Sí", "No"]], texts.options);
+    assert_eq!("vote en línea", texts.instructions);
+    assert_eq!("Instrucciones", texts.blocks[1]);
+    let untranslated = BallotTexts::untranslated(&questions, &template);
+    assert_eq!((untranslated.clone(), true), BallotTexts::for_language(&questions, &template, &languages, ""));
+    assert_eq!((untranslated.clone(), true), BallotTexts::for_language(&questions, &template, &languages, "en"));
+    assert_eq!((untranslated.clone(), false), BallotTexts::for_language(&questions, &template, &languages, "vi"));
+
+    // Bilingual ballots print the voter's language first.
+    let languages = BallotLanguages { bilingual: true, ..languages };
+    let (texts, _) = BallotTexts::for_language(&questions, &template, &languages, "es");
+    assert_eq!(vec!["Pregunta de prueba / Test Question"], texts.questions);
+    assert_eq!(vec![vec!["Sí / Yes", "No"]], texts.options);
+    assert_eq!("{question}", texts.blocks[0]);
+
+    // Translations must label every option and give every block.
+    questions[0].translations.get_mut("es").unwrap().options.pop();
+    assert!(!questions[0].translations_complete());
+    template.translations.get_mut("es").unwrap().blocks.as_mut().unwrap().pop();
+    assert!(template.check().is_err());
+
+    // The ballot, and its vote codes, are the same in every language.
+    let format = VoteCodeFormat::default();
+    let ballot: Ballot = ballot(135791);
+    let directory = "ballots_languages_test/";
+    let template = BallotTemplate::default();
+    print_ballot(&vec![ballot.clone()], &texts, &format, None, &template, &default_fonts(), &output_to(directory)).unwrap();
+    assert_eq!(true, Path::new(directory).join("135791.pdf").exists());
+    let serial_ballots = vec![(vec![ballot.clone()], &texts), (vec![Ballot { serial: 0, ..ballot }], &untranslated)];
+    let output = BallotPdfOutput {
+        directory: directory.to_owned(),
+        batched: true,
+        imposition: Imposition::TwoUp,
+        sheets_per_file: None,
+        scratch_off: ScratchOffLayers::Combined,
+        decoy_directory: DECOYS_PATH.to_owned()
+    };
+    assert_eq!(1, print_ballots_batched(&serial_ballots, &format, None, &template, &default_fonts(), &output).unwrap().len());
+
+    // Delete test files
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn test_ballot_qr_code() {
+
+    let voting_url = VotingUrl {
+        url: "https://vote.example.org/poll".to_owned(),
+        short_url: None
+    };
+    assert!(voting_url.check().is_ok());
+    assert_eq!("vote.example.org/poll", voting_url.short_url());
+
+    // The URL holds the serial and decodes back to it.
+    let url = voting_url.ballot_url(&42);
+    assert_eq!("https://vote.example.org/poll?serial=42", url);
+    assert_eq!(42, voting_url.decode(&url).unwrap());
+    assert_eq!(42, decode_ballot_url("https://vote.example.org/poll?lang=vi&serial=0042#top").unwrap());
+    assert!(voting_url.decode("https://other.example.org/poll?serial=42").is_err());
+    assert!(decode_ballot_url("https://vote.example.org/poll").is_err());
+    assert!(decode_ballot_url("https://vote.example.org/poll?serial=-1").is_err());
+
+    // Voting URLs must be http(s), without a fragment or a serial, and fit a QR code.
+    let bad = |url: &str| VotingUrl { url: url.to_owned(), short_url: None }.check().is_err();
+    assert!(bad("vote.example.org"));
+    assert!(bad("https://vote.example.org/#vote"));
+    assert!(bad("https://vote.example.org/?serial=1"));
+    assert!(bad(&format!("https://vote.example.org/{}", "a".repeat(3000))));
+
+    // The template places the QR code, with a font for the short URL.
+    let mut template = BallotTemplate::default();
+    template.qr_code = Some(QrCodeLayout {
+        x: 100.0,
+        y: 30.0,
+        size: 30.0,
+        font: "short_url".to_owned(),
+        url_size: 8.0
+    });
+    assert!(template.check().is_err());
+    template.fonts.insert("short_url".to_owned(), TemplateFont::Builtin("Courier".to_owned()));
+    assert!(template.check().is_ok());
+
+    let format = VoteCodeFormat::default();
+    let ballot: Ballot = ballot(42);
+    let questions: Vec<PollQuestion> = vec![question("Test Question", &["Yes", "No"])];
+    let directory = "ballots_qr_code_test/";
+    let fonts = BallotFonts::load(&template).unwrap();
+    print_ballot(&vec![ballot], &BallotTexts::untranslated(&questions, &template), &format, Some(&voting_url), &template, &fonts, &output_to(directory)).unwrap();
+    assert_eq!(true, Path::new(directory).join("42.pdf").exists());
+
+    // Delete test files
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn test_scratch_off_layers() {
+
+    assert_eq!(Some(ScratchOffLayers::Separate), ScratchOffLayers::from_name("separate"));
+    assert_eq!(None, ScratchOffLayers::from_name("mask"));
+
+    let format = VoteCodeFormat::default();
+    let questions: Vec<PollQuestion> = vec![question("Test Question", &["Yes", "No"])];
+    let texts = default_texts(&questions);
+    let serial_ballots: Vec<(Vec<Ballot>, &BallotTexts)> = (0..6)
+        .map(|serial| (vec![ballot(serial)], &texts))
+        .collect();
+    let directory = "ballots_scratch_off_test/";
+
+    // A scratch mask layer in the same PDF.
+    let output = BallotPdfOutput {
+        scratch_off: ScratchOffLayers::Layers,
+        ..output_to(directory)
+    };
+    print_ballot(&serial_ballots[0].0, &texts, &format, None, &BallotTemplate::default(), &default_fonts(), &output).unwrap();
+    assert_eq!(true, Path::new(directory).join("0.pdf").exists());
+    assert_eq!(false, Path::new(directory).join("0_mask.pdf").exists());
+
+    // A separate mask PDF next to each PDF.
+    let output = BallotPdfOutput {
+        scratch_off: ScratchOffLayers::Separate,
+        ..output
+    };
+    print_ballot(&serial_ballots[1].0, &texts, &format, None, &BallotTemplate::default(), &default_fonts(), &output).unwrap();
+    assert_eq!(true, Path::new(directory).join("1.pdf").exists());
+    assert_eq!(true, Path::new(directory).join("1_mask.pdf").exists());
+
+    let output = BallotPdfOutput {
+        batched: true,
+        imposition: Imposition::TwoUp,
+        sheets_per_file: Some(2),
+        ..output
+    };
+    let files = print_ballots_batched(&serial_ballots, &format, None, &BallotTemplate::default(), &default_fonts(), &output).unwrap();
+    let expected: Vec<String> = vec!["ballots_1.pdf", "ballots_1_mask.pdf", "ballots_2.pdf", "ballots_2_mask.pdf"].into_iter()
+        .map(|file| Path::new(directory).join(file).display().to_string())
+        .collect();
+    assert_eq!(expected, files);
+    files.iter().for_each(|file| assert_eq!(true, Path::new(file).exists()));
+
+    // Delete test files
+    fs::remove_dir_all(directory).unwrap();
+}
+
+/// A TrueType font of five glyphs: 'A' -> 1, 'B' -> 2, 'C' -> 4, glyph 2
+/// built from glyph 3.
+fn tiny_font() -> Vec<u8> {
+    let be16 = |value: u16| value.to_be_bytes().to_vec();
+    let triangle: Vec<u8> = vec![be16(1), be16(0), be16(0), be16(100), be16(100), be16(2), be16(0),
+        vec![1, 1, 1], be16(0), be16(100), be16((-100i16) as u16), be16(0), be16(0), be16(100)].concat();
+    let composite: Vec<u8> = vec![be16((-1i16) as u16), be16(0), be16(0), be16(100), be16(100),
+        be16(0x0003), be16(3), be16(0), be16(0)].concat();
+    let glyphs: Vec<Vec<u8>> = vec![vec![], triangle.clone(), composite, triangle.clone(), triangle];
+    let mut glyf: Vec<u8> = Vec::new();
+    let mut loca: Vec<u8> = Vec::new();
+    for glyph in glyphs.iter() {
+        loca.extend(&(glyf.len() as u32).to_be_bytes());
+        glyf.extend(glyph);
+        while glyf.len() % 4 != 0 { glyf.push(0); }
+    }
+    loca.extend(&(glyf.len() as u32).to_be_bytes());
+    let mut head = vec![0u8; 54];
+    head[..4].copy_from_slice(&[0, 1, 0, 0]);
+    head[12..16].copy_from_slice(&0x5F0F_3CF5u32.to_be_bytes());
+    head[18..20].copy_from_slice(&be16(1000));
+    head[50..52].copy_from_slice(&be16(1));
+    let mut hhea = vec![0u8; 36];
+    hhea[..4].copy_from_slice(&[0, 1, 0, 0]);
+    hhea[34..36].copy_from_slice(&be16(5));
+    let mut maxp = vec![0u8; 32];
+    maxp[..4].copy_from_slice(&[0, 1, 0, 0]);
+    maxp[4..6].copy_from_slice(&be16(5));
+    let hmtx: Vec<u8> = (0..5).flat_map(|_| vec![be16(500), be16(0)].concat()).collect();
+    let cmap: Vec<u8> = vec![be16(0), be16(1), be16(3), be16(1), 12u32.to_be_bytes().to_vec(),
+        be16(6), be16(16), be16(0), be16(0x41), be16(3), be16(1), be16(2), be16(4)].concat();
+    let tables: Vec<(&[u8; 4], Vec<u8>)> = vec![(b"cmap", cmap), (b"glyf", glyf), (b"head", head),
+        (b"hhea", hhea), (b"hmtx", hmtx), (b"loca", loca), (b"maxp", maxp)];
+    let mut font: Vec<u8> = vec![vec![0, 1, 0, 0], be16(tables.len() as u16), be16(0), be16(0), be16(0)].concat();
+    let mut offset = 12 + 16 * tables.len();
+    for (tag, table) in tables.iter() {
+        font.extend(&tag[..]);
+        font.extend(&[0; 4]);
+        font.extend(&(offset as u32).to_be_bytes());
+        font.extend(&(table.len() as u32).to_be_bytes());
+        offset += (table.len() + 3) / 4 * 4;
+    }
+    for (_, table) in tables.iter() {
+        font.extend(table);
+        while font.len() % 4 != 0 { font.push(0); }
+    }
+    font
+}
+
+/// Outline builder that discards the outline.
+struct Outline;
+impl ttf_parser::OutlineBuilder for Outline {
+    fn move_to(&mut self, _: f32, _: f32) {}
+    fn line_to(&mut self, _: f32, _: f32) {}
+    fn quad_to(&mut self, _: f32, _: f32, _: f32, _: f32) {}
+    fn curve_to(&mut self, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32) {}
+    fn close(&mut self) {}
+}
+
+#[test]
+fn test_font_subset() {
+    let font = tiny_font();
+    let has_outline = |font: &[u8], glyph: u16| ttf_parser::Face::from_slice(font, 0).unwrap()
+        .outline_glyph(ttf_parser::GlyphId(glyph), &mut Outline).is_some();
+    assert_eq!(vec![false, true, true, true, true], (0..5).map(|glyph| has_outline(&font, glyph)).collect::<Vec<bool>>());
+
+    // 'B' keeps its glyph and the glyph it is built from; glyph IDs and metrics stay.
+    let characters: BTreeSet<char> = vec!['B'].into_iter().collect();
+    let subset = subset_font(&font, &characters).unwrap();
+    assert!(subset.len() < font.len());
+    assert_eq!(vec![false, false, true, true, false], (0..5).map(|glyph| has_outline(&subset, glyph)).collect::<Vec<bool>>());
+    let face = ttf_parser::Face::from_slice(&subset, 0).unwrap();
+    assert_eq!(Some(ttf_parser::GlyphId(4)), face.glyph_index('C'));
+    assert_eq!(Some(500), face.glyph_hor_advance(ttf_parser::GlyphId(4)));
+
+    // Fonts without TrueType outlines are embedded whole.
+    let cff = b"OTTO\x00\x00\x00\x00\x00\x00\x00\x00".to_vec();
+    assert_eq!(cff, subset_font(&cff, &characters).unwrap());
+}
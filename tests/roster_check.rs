@@ -0,0 +1,106 @@
+use seventh_estate::voter_roster::*;
+
+fn voter(last_name: &str, first_name: &str, street_address: &str, zip_code: &str) -> VoterInfo {
+    VoterInfo {
+        last_name: last_name.to_owned(),
+        first_name: first_name.to_owned(),
+        street_address: street_address.to_owned(),
+        city: "Dallas".to_owned(),
+        state: "TX".to_owned(),
+        zip_code: zip_code.to_owned(),
+        ..Default::default()
+    }
+}
+
+fn roster(voters: Vec<VoterInfo>) -> VoterRoster {
+    VoterRoster {
+        records: voters.into_iter().enumerate()
+            .map(|(n, info)| VoterRosterRecord { position: n, voter_info: info })
+            .collect()
+    }
+}
+
+#[test]
+fn test_normalization() {
+    let normalized = normalize_voter_info(&voter(" mckie ", "Sioux", "29053  Bay   Plaza", "7536"));
+    assert_eq!("MCKIE", normalized.last_name);
+    assert_eq!("29053 BAY PLAZA", normalized.street_address);
+    assert_eq!("07536", normalized.zip_code);
+    assert_eq!("75367-1234", normalize_voter_info(&voter("a", "b", "c", "753671234")).zip_code);
+}
+
+#[test]
+fn test_duplicates() {
+    let (normalized, report) = check_roster(&roster(vec![
+        voter("McKie", "Sioux", "29053 Bay Plaza", "75367"),
+        voter("MCKIE", "sioux", "29053 Bay Plaza", "75367"),
+        voter("McKie", "S", "29053 Bay Plz", "75367"),
+        voter("McKie", "Tom", "29053 Bay Plaza", "75367"),
+        voter("Suffield", "Kary", "97564 Independence Terrace", "88530")
+    ]));
+    assert_eq!(5, normalized.len());
+    assert_eq!(vec![vec![0, 1]], report.exact_duplicates);
+    assert_eq!(vec![vec![0, 2], vec![1, 2]], report.fuzzy_duplicates);
+    assert_eq!(vec![vec![0, 1, 3]], report.households);
+    assert!(report.has_errors());
+}
+
+#[test]
+fn test_missing_fields() {
+    let (_, report) = check_roster(&roster(vec![
+        voter("McKie", "", "29053 Bay Plaza", "75367"),
+        voter("Suffield", "Kary", "97564 Independence Terrace", "8853X")
+    ]));
+    assert_eq!(1, report.missing_fields.len());
+    assert_eq!("first_name", report.missing_fields[0].field);
+    assert_eq!(vec![1], report.malformed_zip_codes);
+    assert!(report.exact_duplicates.is_empty());
+    assert!(report.has_errors());
+}
+
+#[test]
+fn test_roster_hash() {
+    let (first, report) = check_roster(&roster(vec![voter("McKie", "Sioux", "29053 Bay Plaza", "75367")]));
+    let (second, _) = check_roster(&roster(vec![voter("mckie", " Sioux", "29053 bay plaza", "75367")]));
+    assert!(!report.has_errors());
+    assert_eq!(first.hash(), second.hash());
+    assert_eq!(report.roster_hash, first.hash());
+    assert_eq!(64, report.roster_hash.len());
+    assert_eq!(2, edit_distance("PLAZA", "PLZ"));
+}
+
+#[test]
+fn test_roster_hash_encoding() {
+    // The encoding is fixed, whatever the serializer: see `voter_roster`.
+    assert_eq!("d80e556d9c9878645fbcc04445dd445acad44d7cc405e88b9bf86e7d0b43a8dc",
+        roster(vec![voter("McKie", "Sioux", "29053 Bay Plaza", "75367")]).hash());
+    let mut moved = roster(vec![voter("McKie", "Sioux", "29053 Bay Plaza", "75367")]);
+    moved.records[0].voter_info.apartment = "29053 Bay Plaza".to_owned();
+    moved.records[0].voter_info.street_address = "".to_owned();
+    assert_ne!(roster(vec![voter("McKie", "Sioux", "29053 Bay Plaza", "75367")]).hash(), moved.hash(),
+        "A value moved to another field changes the hash.");
+}
+
+#[test]
+fn test_fuzzy_duplicate_blocking() {
+    // Voters sorting between two spellings of one name do not hide them,
+    // as the street brings them together.
+    let mut voters: Vec<VoterInfo> = (0..20)
+        .map(|n| VoterInfo { apartment: format!("Apt {}", n), ..voter(&format!("Moore{}", n), "Ann", "1 Main Street", "75367") })
+        .collect();
+    voters.push(voter("McKie", "Sioux", "29053 Bay Plaza", "75367"));
+    voters.push(voter("NcKie", "Sioux", "29053 Bay Plaza", "75367"));
+    // The same voter in another ZIP code is another voter.
+    voters.push(voter("McKie", "Sioux", "29053 Bay Plaza", "88530"));
+    let (_, report) = check_roster(&roster(voters));
+    assert_eq!(vec![vec![20, 21]], report.fuzzy_duplicates);
+    assert!(report.exact_duplicates.is_empty());
+
+    // Records without a ZIP code are blocked like the others.
+    let (_, report) = check_roster(&roster(vec![
+        voter("McKie", "Sioux", "29053 Bay Plaza", ""),
+        voter("Suffield", "Kary", "97564 Independence Terrace", ""),
+        voter("McKie", "S", "29053 Bay Plz", "")
+    ]));
+    assert_eq!(vec![vec![0, 2]], report.fuzzy_duplicates);
+}
@@ -0,0 +1,61 @@
+use seventh_estate::cryptography::CSPRNGSeed;
+use seventh_estate::voter_roster::*;
+
+fn voter(last_name: &str, first_name: &str) -> VoterInfo {
+    VoterInfo {
+        last_name: last_name.to_owned(),
+        first_name: first_name.to_owned(),
+        street_address: "29053 Bay Plaza".to_owned(),
+        city: "Dallas".to_owned(),
+        state: "TX".to_owned(),
+        zip_code: "75367".to_owned(),
+        ..Default::default()
+    }
+}
+
+fn roster() -> VoterRoster {
+    VoterRoster {
+        records: vec![voter("McKie", "Sioux"), voter("Gabbitus", "Valle")].into_iter().enumerate()
+            .map(|(n, info)| VoterRosterRecord { position: n, voter_info: info })
+            .collect()
+    }
+}
+
+#[test]
+fn test_salts() {
+    let seed = CSPRNGSeed::from_vec(&vec![7; CSPRNGSeed::SIZE]);
+    let salts = generate_roster_salts(seed, 2);
+    assert_eq!(salts, generate_roster_salts(seed, 2));
+    assert_ne!(salts[0], salts[1]);
+}
+
+#[test]
+fn test_commitment() {
+    let roster = roster();
+    let salts = generate_roster_salts(CSPRNGSeed::from_vec(&vec![7; CSPRNGSeed::SIZE]), roster.len());
+    let committed = roster.salted_commitments(&salts);
+    let restricted = roster.restricted();
+
+    // The same voter information commits differently under each salt.
+    assert_ne!(salted_commitment(&restricted.records[0].voter_info, &salts[0]),
+               salted_commitment(&restricted.records[0].voter_info, &salts[1]));
+    assert!(!committed.records[0].voter_info.contains("McKie"));
+
+    let commitment = RestrictedVoterRosterFileRow {
+        position: 1,
+        restricted: committed.records[1].voter_info.clone()
+    };
+    let mut revealed = RevealedRosterFileRow {
+        position: 1,
+        restricted: restricted.records[1].voter_info.clone(),
+        salt: hex::encode(&salts[1].0)
+    };
+    assert!(verify_revealed_record(&revealed, &commitment));
+
+    // A tampered record or salt no longer matches.
+    revealed.salt = hex::encode(&salts[0].0);
+    assert!(!verify_revealed_record(&revealed, &commitment));
+    revealed.salt = hex::encode(&salts[1].0);
+    revealed.restricted = restricted.records[0].voter_info.clone();
+    assert!(!verify_revealed_record(&revealed, &commitment));
+}
@@ -0,0 +1,76 @@
+use std::fs;
+use std::path::PathBuf;
+use seventh_estate::voter_roster::*;
+
+fn write_roster(name: &str, contents: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(name);
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn test_default_profile() {
+    let path = write_roster("roster_default.csv",
+        "last_name,first_name,street_address,city,state,zip_code\n\
+         McKie,Sioux,29053 Bay Plaza,Dallas,TX,75367\n");
+    let roster = VoterRoster::from_file(&path).unwrap();
+    assert_eq!(1, roster.len());
+    assert_eq!("McKie", roster.records[0].voter_info.last_name);
+    assert_eq!("", roster.records[0].voter_info.country);
+}
+
+#[test]
+fn test_tsv_profile() {
+    let profile: RosterImportProfile = serde_yaml::from_str(
+        "format: tsv\n\
+         columns:\n  last_name: Surname\n  first_name: Given\n  street_address: Line1\n  address2: Line2\n  zip_code: Postcode\n").unwrap();
+    let path = write_roster("roster_profile.tsv",
+        "Surname\tGiven\tLine1\tLine2\tcity\tstate\tPostcode\tcountry\n\
+         Suffield\tKary\t97564 Independence Terrace\tFloor 2\tEl Paso\tTX\t88530\tUS\n");
+    let roster = VoterRoster::from_file_with_profile(&path, &profile).unwrap();
+    let voter = &roster.records[0].voter_info;
+    assert_eq!("Suffield", voter.last_name);
+    assert_eq!("Floor 2", voter.address2);
+    assert_eq!("", voter.apartment);
+    assert_eq!("88530", voter.zip_code);
+    assert_eq!("US", voter.country);
+}
+
+#[test]
+fn test_json_lines_profile() {
+    let profile: RosterImportProfile = serde_yaml::from_str("format: jsonl\n").unwrap();
+    let path = write_roster("roster_profile.jsonl",
+        "{\"last_name\": \"McKie\", \"first_name\": \"Sioux\", \"street_address\": \"29053 Bay Plaza\", \"apartment\": \"4B\", \"city\": \"Dallas\", \"state\": \"TX\", \"zip_code\": 75367}\n\
+         \n\
+         {\"last_name\": \"Suffield\", \"first_name\": \"Kary\", \"street_address\": \"97564 Independence Terrace\", \"city\": \"El Paso\", \"state\": \"TX\", \"zip_code\": \"88530\"}\n");
+    let roster = VoterRoster::from_file_with_profile(&path, &profile).unwrap();
+    assert_eq!(2, roster.len());
+    assert_eq!("4B", roster.records[0].voter_info.apartment);
+    assert_eq!("75367", roster.records[0].voter_info.zip_code);
+    assert_eq!(1, roster.records[1].position);
+}
+
+#[test]
+fn test_bad_rows_report_line_numbers() {
+    let profile: RosterImportProfile = serde_yaml::from_str("format: jsonl\n").unwrap();
+    let path = write_roster("roster_bad.jsonl",
+        "{\"last_name\": \"McKie\", \"first_name\": \"Sioux\", \"street_address\": \"29053 Bay Plaza\", \"city\": \"Dallas\", \"state\": \"TX\", \"zip_code\": \"75367\"}\n\
+         {\"last_name\": \"Suffield\", \"first_name\": \"Kary\", \"city\": \"El Paso\", \"state\": \"TX\", \"zip_code\": \"88530\"}\n\
+         {not json}\n");
+    let rows = profile.read(&path).unwrap();
+    assert!(rows[0].is_ok());
+    let err = rows[1].as_ref().unwrap_err();
+    assert_eq!(2, err.line);
+    assert_eq!("line 2: missing column \"street_address\"", err.to_string());
+    assert_eq!(3, rows[2].as_ref().unwrap_err().line);
+    assert!(VoterRoster::from_file_with_profile(&path, &profile).is_err());
+
+    let path = write_roster("roster_bad.csv",
+        "last_name,first_name,street_address,city,state,zip_code\n\
+         McKie,Sioux,29053 Bay Plaza,Dallas,TX,75367\n\
+         Suffield,Kary,El Paso,TX\n");
+    let rows = RosterImportProfile::default().read(&path).unwrap();
+    assert!(rows[0].is_ok());
+    assert_eq!(3, rows[1].as_ref().unwrap_err().line);
+}
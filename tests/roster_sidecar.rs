@@ -0,0 +1,49 @@
+use std::fs;
+use seventh_estate::cryptography::AEADKey;
+use seventh_estate::voter_roster::*;
+
+fn roster() -> VoterRoster {
+    VoterRoster {
+        records: vec![("McKie", "Sioux"), ("Gabbitus", "Valle")].into_iter().enumerate()
+            .map(|(n, (last_name, first_name))| VoterRosterRecord {
+                position: n,
+                voter_info: VoterInfo {
+                    last_name: last_name.to_owned(),
+                    first_name: first_name.to_owned(),
+                    street_address: "29053 Bay Plaza".to_owned(),
+                    apartment: "Apt 4".to_owned(),
+                    city: "Dallas".to_owned(),
+                    state: "TX".to_owned(),
+                    zip_code: "75367".to_owned(),
+                    ..Default::default()
+                }
+            }).collect()
+    }
+}
+
+#[test]
+fn test_sidecar() {
+    let filename = "test_roster.sidecar";
+    let key = AEADKey([7; 32]);
+    let roster = roster();
+    let reference = write_roster_sidecar(&roster, &key, filename).unwrap();
+
+    // The records come back in order, and nothing is stored in the clear.
+    let records: Vec<VoterRosterRecord> = open_roster_sidecar(&reference, &key).unwrap()
+        .collect::<Result<Vec<VoterRosterRecord>, _>>().unwrap();
+    assert_eq!(serde_yaml::to_string(&roster.records).unwrap(), serde_yaml::to_string(&records).unwrap());
+    assert!(!fs::read_to_string(filename).unwrap().contains("McKie"));
+
+    // Another key cannot read the records.
+    assert!(open_roster_sidecar(&reference, &AEADKey([8; 32])).unwrap().all(|record| record.is_err()));
+
+    // Swapped records no longer match the bound hash, nor their positions.
+    let contents = fs::read_to_string(filename).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    fs::write(filename, format!("{}\n{}\n", lines[1], lines[0])).unwrap();
+    assert!(open_roster_sidecar(&reference, &key).is_err());
+    let swapped = RosterSidecarReference { path: filename.to_owned(), hash: hash_file(filename).unwrap() };
+    assert!(open_roster_sidecar(&swapped, &key).unwrap().next().unwrap().is_err());
+
+    fs::remove_file(filename).unwrap();
+}
@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use std::fs;
+use seventh_estate::cryptography::Base64String;
+use seventh_estate::seeds::*;
+
+fn inputs() -> Vec<SeedInput> {
+    vec![
+        SeedInput {
+            kind: SeedInputKind::Beacon,
+            source: "beacon.json".to_owned(),
+            content: "{\"pulse\": 1234, \"outputValue\": \"9f2c\"}".to_owned()
+        },
+        SeedInput {
+            kind: SeedInputKind::Lottery,
+            source: "lottery.txt".to_owned(),
+            content: "4 8 15 16 23 42\n".to_owned()
+        }
+    ]
+}
+
+#[test]
+fn test_seed_derivation() {
+    let transcript = SeedTranscript::derive(DRAWN_SUMMANDS_PURPOSE, inputs());
+    assert_eq!(transcript.seed, derive_seed(DRAWN_SUMMANDS_PURPOSE, &inputs()));
+    assert_eq!(32, hex::decode(&transcript.seed).unwrap().len());
+    assert!(transcript.verify().is_ok());
+
+    // The seed depends on the purpose and on the order of the inputs.
+    assert_ne!(transcript.seed, derive_seed(AUDITED_COLUMNS_PURPOSE, &inputs()));
+    let reversed: Vec<SeedInput> = inputs().into_iter().rev().collect();
+    assert_ne!(transcript.seed, derive_seed(DRAWN_SUMMANDS_PURPOSE, &reversed));
+}
+
+#[test]
+fn test_seed_transcript_tampering() {
+    let transcript = SeedTranscript::derive(AUDITED_COLUMNS_PURPOSE, inputs());
+
+    let mut tampered = transcript.clone();
+    tampered.inputs[1].content = "4 8 15 16 23 43\n".to_owned();
+    assert!(tampered.verify().is_err());
+
+    let mut tampered = transcript.clone();
+    tampered.purpose = DRAWN_SUMMANDS_PURPOSE.to_owned();
+    assert!(tampered.verify().is_err());
+
+    let mut tampered = transcript.clone();
+    tampered.construction = "other".to_owned();
+    assert!(tampered.verify().is_err());
+}
+
+#[test]
+fn test_seed_transcript_file() {
+    let dir = std::env::temp_dir().join("seventh_estate_test_seeds");
+    fs::create_dir_all(&dir).unwrap();
+    let input_path = dir.join("block.txt");
+    fs::write(&input_path, "00000000000000000002a7c4c1e48d76c5a37902165a270156b7a8d72728a054\n").unwrap();
+
+    let argument = format!("block_hash={}", input_path.display());
+    let input = SeedInput::from_argument(&argument).unwrap();
+    assert_eq!(SeedInputKind::BlockHash, input.kind);
+    assert!(SeedInput::from_argument("coin=block.txt").is_err());
+    assert!(SeedInput::from_argument("beacon").is_err());
+
+    let transcript = SeedTranscript::derive(DRAWN_SUMMANDS_PURPOSE, vec![input]);
+    let transcript_path = dir.join("seed_transcript.yaml");
+    transcript.to_file(&transcript_path).unwrap();
+    let read = SeedTranscript::from_file(&transcript_path).unwrap();
+    assert_eq!(transcript.seed, read.seed);
+    assert!(read.verify().is_ok());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+fn commit(participants: &[&str]) -> (Vec<SeedCommitmentSecret>, Vec<SeedCommitment>, BTreeMap<String, Base64String>) {
+    let keys: Vec<ParticipantKey> = participants.iter()
+        .map(|participant| ParticipantKey::new(participant).unwrap())
+        .collect();
+    let (secrets, commitments) = keys.iter()
+        .map(|key| SeedCommitmentSecret::new(key, AUDITED_COLUMNS_PURPOSE).unwrap())
+        .unzip();
+    let participant_keys = keys.into_iter()
+        .map(|key| (key.participant, key.public_key))
+        .collect();
+    (secrets, commitments, participant_keys)
+}
+
+#[test]
+fn test_commit_reveal_seed() {
+    let (secrets, commitments, keys) = commit(&["observer", "trustee1", "trustee2"]);
+
+    // The order of the reveals does not change the seed.
+    let reveals: Vec<SeedReveal> = secrets.iter().map(|secret| secret.reveal()).collect();
+    let transcript = combine_reveals(AUDITED_COLUMNS_PURPOSE, commitments.clone(), reveals.clone(), &keys).unwrap();
+    let reversed = combine_reveals(AUDITED_COLUMNS_PURPOSE, commitments.clone(), reveals.into_iter().rev().collect(), &keys).unwrap();
+    assert_eq!(transcript.seed, reversed.seed);
+    assert_eq!(3, transcript.commitments.len());
+    assert!(transcript.verify().is_ok());
+
+    // A contribution that is not the committed one is rejected.
+    let mut tampered = transcript.clone();
+    tampered.inputs[0].content = hex::encode(&[0u8; CONTRIBUTION_SIZE]);
+    tampered.seed = derive_seed(&tampered.purpose, &tampered.inputs);
+    assert!(tampered.verify().is_err());
+
+    // So is a commitment and contribution moved to another participant.
+    let mut tampered = transcript.clone();
+    tampered.commitments[1].participant = "mallory".to_owned();
+    tampered.inputs[1].source = "mallory".to_owned();
+    assert!(tampered.verify().is_err());
+}
+
+#[test]
+fn test_commit_reveal_missing_reveal() {
+    let (secrets, commitments, keys) = commit(&["trustee1", "trustee2"]);
+    let reveals = vec![secrets[0].reveal()];
+    assert!(combine_reveals(AUDITED_COLUMNS_PURPOSE, commitments.clone(), reveals, &keys).is_err());
+
+    let reveals: Vec<SeedReveal> = secrets.iter().map(|secret| secret.reveal()).collect();
+    assert!(combine_reveals(DRAWN_SUMMANDS_PURPOSE, commitments, reveals, &keys).is_err());
+}
+
+#[test]
+fn test_commit_reveal_contributions() {
+    let (secrets, commitments, keys) = commit(&["observer", "trustee1", "trustee2"]);
+    let reveals: Vec<SeedReveal> = secrets.iter().map(|secret| secret.reveal()).collect();
+    let transcript = combine_reveals(AUDITED_COLUMNS_PURPOSE, commitments, reveals, &keys).unwrap();
+    let retranscribe = |inputs: Vec<SeedInput>| {
+        let mut tampered = transcript.clone();
+        tampered.seed = derive_seed(&tampered.purpose, &inputs);
+        tampered.inputs = inputs;
+        tampered
+    };
+
+    // A contribution counted twice in place of another one is rejected.
+    let mut inputs = transcript.inputs.clone();
+    inputs[2] = inputs[1].clone();
+    assert!(retranscribe(inputs).verify().is_err());
+
+    // So is a missing contribution, or one out of participant order.
+    let mut inputs = transcript.inputs.clone();
+    inputs.remove(0);
+    assert!(retranscribe(inputs).verify().is_err());
+    let mut inputs = transcript.inputs.clone();
+    inputs.swap(0, 2);
+    assert!(retranscribe(inputs).verify().is_err());
+    assert!(retranscribe(transcript.inputs.clone()).verify().is_ok());
+}
+
+#[test]
+fn test_commit_reveal_participant_keys() {
+    let (secrets, commitments, keys) = commit(&["observer", "trustee1"]);
+    let reveals: Vec<SeedReveal> = secrets.iter().map(|secret| secret.reveal()).collect();
+    assert!(combine_reveals(AUDITED_COLUMNS_PURPOSE, commitments.clone(), reveals.clone(), &keys).is_ok());
+
+    // A commitment signed by someone else under a listed name is rejected.
+    let mallory = ParticipantKey { participant: "trustee1".to_owned(), ..ParticipantKey::new("mallory").unwrap() };
+    let (forged_secret, forged) = SeedCommitmentSecret::new(&mallory, AUDITED_COLUMNS_PURPOSE).unwrap();
+    let forged_reveals = vec![reveals[0].clone(), forged_secret.reveal()];
+    assert!(combine_reveals(AUDITED_COLUMNS_PURPOSE, vec![commitments[0].clone(), forged.clone()], forged_reveals, &keys).is_err());
+
+    // So is a commitment whose key is swapped for the listed one.
+    let mut swapped = forged;
+    swapped.public_key = keys["trustee1"].clone();
+    assert!(swapped.check_signature().is_err());
+
+    // So is an altered commitment.
+    let mut altered = commitments.clone();
+    altered[1].commitment = hex::encode(&[0u8; CONTRIBUTION_SIZE]);
+    assert!(combine_reveals(AUDITED_COLUMNS_PURPOSE, altered, reveals.clone(), &keys).is_err());
+
+    // Every listed participant commits, and only they do.
+    let mut missing = keys.clone();
+    missing.insert("trustee2".to_owned(), keys["trustee1"].clone());
+    assert!(combine_reveals(AUDITED_COLUMNS_PURPOSE, commitments.clone(), reveals.clone(), &missing).is_err());
+    let mut unlisted = keys.clone();
+    unlisted.remove("observer");
+    assert!(combine_reveals(AUDITED_COLUMNS_PURPOSE, commitments, reveals, &unlisted).is_err());
+}
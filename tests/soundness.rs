@@ -0,0 +1,25 @@
+use seventh_estate::soundness::*;
+
+#[test]
+fn test_survival_probabilities() {
+    assert_eq!(1.0, worst_case_survival(0));
+    assert_eq!(0.5, worst_case_survival(1));
+    // A single altered row is the careful adversary's best case too.
+    assert_eq!(worst_case_survival(50), random_tampering_survival(50, 1));
+    assert_eq!(worst_case_survival(30), random_tampering_survival(10, 3));
+    assert!(random_tampering_survival(50, 2) < worst_case_survival(50));
+}
+
+#[test]
+fn test_planes_for_target() {
+    assert_eq!(1, planes_for_target(0.5).unwrap());
+    assert_eq!(2, planes_for_target(0.3).unwrap());
+    [1e-3, 1e-6, 1e-9, 1e-15].iter()
+        .for_each(|&target| {
+            let planes = planes_for_target(target).unwrap();
+            assert!(worst_case_survival(planes) <= target);
+            assert!(worst_case_survival(planes - 1) > target);
+        });
+    [0.0, 1.0, 2.0, -0.5, f64::NAN].iter()
+        .for_each(|&target| assert!(planes_for_target(target).is_err()));
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use seventh_estate::cryptography::CSPRNGSeed;
+use seventh_estate::poll_configuration::*;
+use seventh_estate::summands::*;
+use seventh_estate::voter_roster::*;
+use seventh_estate::voter_selection::*;
+
+fn roster(states: Vec<&str>) -> VoterRoster {
+    VoterRoster {
+        records: states.into_iter().enumerate()
+            .map(|(n, state)| VoterRosterRecord {
+                position: n,
+                voter_info: VoterInfo {
+                    last_name: format!("Voter{}", n),
+                    first_name: "A".to_owned(),
+                    street_address: "29053 Bay Plaza".to_owned(),
+                    city: "Dallas".to_owned(),
+                    state: state.to_owned(),
+                    zip_code: "75367".to_owned(),
+                    ..Default::default()
+                }
+            }).collect()
+    }
+}
+
+fn stratification(allocation: StratumAllocation, ballots: Vec<(&str, usize)>) -> Stratification {
+    Stratification {
+        field: StratumField::State,
+        prefix: None,
+        allocation: allocation,
+        ballots: ballots.into_iter().map(|(key, n)| (key.to_owned(), n)).collect::<BTreeMap<String, usize>>()
+    }
+}
+
+#[test]
+fn test_allocation() {
+    assert_eq!(vec![3, 2, 1], allocate_proportionally(&vec![5, 3, 2], 6));
+    assert_eq!(vec![2, 1, 1], allocate_proportionally(&vec![1, 1, 1], 4));
+    assert_eq!(vec![10, 0], allocate_proportionally(&vec![1000, 1], 10));
+}
+
+#[test]
+fn test_stratify() {
+    let roster = roster(vec!["TX", "ok", "TX", "TX", " OK", "RI"]);
+    let strata = stratify(&roster, &stratification(StratumAllocation::Proportional, vec![]), 4).unwrap();
+    assert_eq!(vec!["OK", "RI", "TX"], strata.iter().map(|s| s.key.as_str()).collect::<Vec<&str>>());
+    assert_eq!(vec![2, 1, 3], strata.iter().map(|s| s.voters).collect::<Vec<usize>>());
+    let records = roster.records.clone().into_iter().map(Ok);
+    let positions = stratum_positions(records, Some(&stratification(StratumAllocation::Proportional, vec![])), &strata).unwrap();
+    assert_eq!(vec![vec![1, 4], vec![5], vec![0, 2, 3]], positions);
+    assert_eq!(vec![1, 1, 2], strata.iter().map(|s| s.ballots).collect::<Vec<usize>>());
+
+    let explicit = stratification(StratumAllocation::Explicit, vec![("TX", 2), ("RI", 2)]);
+    let strata = stratify(&roster, &explicit, 4).unwrap();
+    assert_eq!(vec![0, 2, 2], strata.iter().map(|s| s.ballots).collect::<Vec<usize>>());
+
+    // The explicit allocation must match the number of ballots and the strata.
+    assert!(stratify(&roster, &explicit, 5).is_err());
+    assert!(stratify(&roster, &stratification(StratumAllocation::Explicit, vec![("CA", 4)]), 4).is_err());
+}
+
+#[test]
+fn test_select_voters_in_strata() {
+    let seed = CSPRNGSeed::from_vec(&vec![7; CSPRNGSeed::SIZE]);
+    let roster = roster(vec!["TX", "OK", "TX", "TX", "OK", "RI", "TX", "TX"]);
+    let by_state = stratification(StratumAllocation::Proportional, vec![]);
+    let strata = stratify(&roster, &by_state, 6).unwrap();
+    let summand_strata: Vec<(usize, usize)> = strata.iter()
+        .map(|stratum| (stratum.ballots, stratum.voters))
+        .collect();
+    let committed = CommittedSummands::from_csprng_strata(seed, &summand_strata);
+    let drawn = DrawnSummands::from_csprng_strata(seed, &summand_strata);
+    let members = select_members_in_strata(committed, drawn, &strata).unwrap();
+    let selected = member_positions(roster.records.clone().into_iter().map(Ok), Some(&by_state), &strata, &members).unwrap();
+
+    // Each ballot goes to a voter of its stratum.
+    let positions = stratum_positions(roster.records.clone().into_iter().map(Ok), Some(&by_state), &strata).unwrap();
+    let mut offset = 0;
+    strata.iter().zip(positions.iter())
+        .for_each(|(stratum, positions)| {
+            selected[offset..offset + stratum.ballots].iter()
+                .for_each(|position| assert!(positions.contains(position)));
+            offset += stratum.ballots;
+        });
+
+    // The roster must match the strata it was bound with.
+    let mut moved = roster.clone();
+    moved.records[0].voter_info.state = "OK".to_owned();
+    assert!(member_positions(moved.records.into_iter().map(Ok), Some(&by_state), &strata, &members).is_err());
+
+    // A single stratum samples the whole roster as before.
+    assert_eq!(CommittedSummands::from_csprng(seed, 6, 8),
+               CommittedSummands::from_csprng_strata(seed, &vec![(6, 8)]));
+    let whole = vec![Stratum { key: "".to_owned(), ballots: 6, voters: 8 }];
+    let members = select_members_in_strata(CommittedSummands::from_csprng(seed, 6, 8), DrawnSummands::from_csprng(seed, 6, 8), &whole).unwrap();
+    assert_eq!(
+        select_voters(CommittedSummands::from_csprng(seed, 6, 8), DrawnSummands::from_csprng(seed, 6, 8), 8).unwrap(),
+        member_positions(roster.records.clone().into_iter().map(Ok), None, &whole, &members).unwrap());
+    assert_eq!(members, member_positions(roster.records.clone().into_iter().map(Ok), None, &whole, &members).unwrap(),
+        "The members of the whole roster are its positions.");
+
+    // A stratum cannot have more ballots than voters.
+    assert!(stratify(&roster, &stratification(StratumAllocation::Explicit, vec![("RI", 2), ("TX", 4)]), 6).is_err());
+}
+
+#[test]
+fn test_selection_without_replacement() {
+    // Every ballot lands on voter 1 before probing.
+    let committed = CommittedSummands::from(Summands {
+        records: (0..4).map(|n| SummandRecord { position: n, summand: 1 }).collect()
+    });
+    let drawn = DrawnSummands::from(Summands {
+        records: (0..4).map(|n| SummandRecord { position: n, summand: 0 }).collect()
+    });
+    let whole = vec![Stratum { key: "".to_owned(), ballots: 4, voters: 4 }];
+    let (selected, collisions) = select_members_counting_collisions(committed.clone(), drawn.clone(), &whole).unwrap();
+    assert_eq!(vec![1, 2, 3, 0], selected);
+    assert_eq!(3, collisions);
+
+    // More ballots than voters cannot be sampled without replacement.
+    let small = vec![Stratum { key: "".to_owned(), ballots: 4, voters: 3 }];
+    assert!(select_members_counting_collisions(committed, drawn, &small).is_err());
+}
+
+#[test]
+fn test_stratum_from_positions() {
+    // Configurations bound before strata were counted list their positions.
+    let stratum: Stratum = serde_yaml::from_str("key: TX\nballots: 2\npositions: [0, 2, 3]\n").unwrap();
+    assert_eq!(Stratum { key: "TX".to_owned(), ballots: 2, voters: 3 }, stratum);
+    let stratum: Stratum = serde_yaml::from_str(&serde_yaml::to_string(&stratum).unwrap()).unwrap();
+    assert_eq!(3, stratum.voters);
+}
+
+#[test]
+fn test_stratum_member_commitments() {
+    let roster = roster(vec!["TX", "OK", "TX", "TX", "OK", "RI"]);
+    let by_state = stratification(StratumAllocation::Proportional, vec![]);
+    let strata = stratify(&roster, &by_state, 4).unwrap();
+    let salts = generate_roster_salts(CSPRNGSeed::from_vec(&vec![7; CSPRNGSeed::SIZE]), roster.len());
+    let commitments = stratum_member_commitments(roster.records.clone().into_iter().map(Ok), salts.clone().into_iter(), &by_state, &strata).unwrap();
+
+    // The salt of a record opens the commitment of its position, in its stratum and in roster order.
+    let positions = stratum_positions(roster.records.clone().into_iter().map(Ok), Some(&by_state), &strata).unwrap();
+    let opened: Vec<Vec<String>> = positions.iter()
+        .map(|positions| positions.iter().map(|&position| member_commitment(position, &salts[position])).collect())
+        .collect();
+    assert_eq!(opened, commitments);
+
+    // The published strata list no positions.
+    let strata_file = StrataFile { stratification: Some(by_state.clone()), strata: strata.clone(), members: commitments };
+    assert!(!serde_yaml::to_string(&strata_file).unwrap().contains("positions"));
+    assert!(stratum_member_commitments(roster.records.clone().into_iter().map(Ok), salts[..3].to_vec().into_iter(), &by_state, &strata).is_err());
+}
@@ -0,0 +1,123 @@
+use seventh_estate::ballots::*;
+use seventh_estate::cryptography::CSPRNGSeed;
+
+fn format(group_size: usize, alphabet: VoteCodeAlphabet, check: VoteCodeCheck) -> VoteCodeFormat {
+    VoteCodeFormat {
+        num_groups: 1,
+        group_size: group_size,
+        alphabet: alphabet,
+        check: check
+    }
+}
+
+#[test]
+fn test_default_format() {
+    let format = VoteCodeFormat::default();
+    assert_eq!(20, format.length());
+
+    let votecode = votecode_from_string("12340-56784-00000-99994", &format).unwrap();
+    assert_eq!("12340-56784-00000-99994", string_from_votecode(&votecode, &format));
+    votecode.chunks(format.group_size + 1)
+        .for_each(|group| {
+            assert_eq!(group[format.group_size], format.check_symbol(&group[..format.group_size]));
+        });
+}
+
+#[test]
+fn test_check_symbols() {
+    let luhn = format(10, VoteCodeAlphabet::Digits, VoteCodeCheck::Luhn);
+    assert_eq!(3, luhn.check_symbol(&[7, 9, 9, 2, 7, 3, 9, 8, 7, 1]));
+
+    let damm = format(3, VoteCodeAlphabet::Digits, VoteCodeCheck::Damm);
+    assert_eq!(4, damm.check_symbol(&[5, 7, 2]));
+}
+
+#[test]
+fn test_damm_catches_transpositions() {
+    [VoteCodeAlphabet::Digits, VoteCodeAlphabet::Crockford32].iter()
+        .for_each(|&alphabet| {
+            let damm = format(2, alphabet, VoteCodeCheck::Damm);
+            (0..damm.radix()).for_each(|x| {
+                (0..damm.radix()).filter(|&y| y != x).for_each(|y| {
+                    assert_ne!(damm.check_symbol(&[x, y]), damm.check_symbol(&[y, x]));
+                });
+            });
+        });
+}
+
+#[test]
+fn test_crockford_normalization() {
+    let format = format(4, VoteCodeAlphabet::Crockford32, VoteCodeCheck::Damm);
+    // Missing check symbol.
+    assert_eq!(None, votecode_from_string("1o2z", &format));
+
+    let check = format.char_from_symbol(format.check_symbol(&[1, 0, 2, 31]));
+    let typed = format!("lO2z{}", check.to_ascii_lowercase());
+    let votecode = votecode_from_string(&typed, &format).unwrap();
+    assert_eq!(vec![1, 0, 2, 31, format.symbol_from_char(check).unwrap()], votecode);
+    assert_eq!(format!("102Z{}", check), string_from_votecode(&votecode, &format));
+}
+
+#[test]
+fn test_generated_votecodes() {
+    let seed = CSPRNGSeed::from_vec(&vec![7; CSPRNGSeed::SIZE]);
+    let format = VoteCodeFormat {
+        num_groups: 3,
+        group_size: 5,
+        alphabet: VoteCodeAlphabet::Crockford32,
+        check: VoteCodeCheck::Luhn
+    };
+    let votecodes = generate_votecodes(seed, 10, &format);
+    assert_eq!(10, votecodes.len());
+    votecodes.iter()
+        .for_each(|votecode| {
+            let printed = string_from_votecode(votecode, &format);
+            assert_eq!(Some(votecode.clone()), votecode_from_string(&printed, &format));
+        });
+    assert_eq!(votecodes, generate_votecodes(seed, 10, &format));
+}
+
+#[test]
+fn test_validate() {
+    let format = VoteCodeFormat::default();
+    assert_eq!(votecode_from_string("12340-56784-00000-99994", &format),
+        validate("1234 0567 8400 0009 9994", &format).ok());
+    assert_eq!(Err(VoteCodeError::WrongLength { expected: 20, found: 19 }),
+        validate("12340-56784-00000-9999", &format));
+    assert_eq!(Err(VoteCodeError::InvalidCharacter { group: 3, character: 'O' }),
+        validate("12340-56784-0O000-99994", &format));
+    // A single mistyped digit is caught by the check symbol of its group.
+    assert_eq!(Err(VoteCodeError::CheckSymbolMismatch { group: 4 }),
+        validate("12340-56784-00000-99894", &format));
+
+    let damm = VoteCodeFormat { check: VoteCodeCheck::Damm, ..format };
+    let payload: Vec<u8> = vec![5, 7, 2, 1, 0, 1, 2, 3, 4, 0, 0, 0, 0, 9, 9, 9];
+    let votecode: VoteCode = payload.chunks(damm.group_size)
+        .flat_map(|group| {
+            let mut checked_group = group.to_vec();
+            checked_group.push(damm.check_symbol(group));
+            checked_group
+        }).collect();
+    let typed = string_from_votecode(&votecode, &damm);
+    assert_eq!(Ok(votecode), validate(&typed, &damm));
+    // Swap two adjacent digits of the second group.
+    let swapped: String = typed.chars().enumerate()
+        .map(|(n, c)| match n {
+            7 => typed.chars().nth(8).unwrap(),
+            8 => typed.chars().nth(7).unwrap(),
+            _ => c
+        }).collect();
+    assert_ne!(typed, swapped);
+    assert_eq!(Err(VoteCodeError::CheckSymbolMismatch { group: 2 }), validate(&swapped, &damm));
+}
+
+#[test]
+fn test_read_votes_file() {
+    let format = VoteCodeFormat::default();
+    let votes_file = "test_read_votes_file.csv";
+    std::fs::write(votes_file, "votecode\n12340-56784-00000-99994\n12340-56784-00000-99994,extra\n12340-56784-00000-99894\n").unwrap();
+    let votes = seventh_estate::subcommands::read_votes_file(votes_file, &format);
+    std::fs::remove_file(votes_file).unwrap();
+    assert_eq!(vec![votecode_from_string("12340-56784-00000-99994", &format).unwrap()], votes.unwrap(),
+        "The malformed row and the mistyped vote code are skipped.");
+}